@@ -0,0 +1,233 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+use crepedb_core::{
+    backend::Backend,
+    types::{Bytes, SnapshotId},
+    CrepeDB, Result,
+};
+use tokio::sync::{mpsc as channel, oneshot};
+
+use crate::{
+    error::worker_stopped,
+    txn::{self, serve_read, serve_write},
+    AsyncReadTxn, AsyncWriteTxn, RangeStream,
+};
+
+/// Default number of worker threads of [`AsyncCrepeDB::new`].
+pub const DEFAULT_THREADS: usize = 4;
+
+type Job<B> = Box<dyn FnOnce(&CrepeDB<B>) + Send>;
+
+/// An asynchronous handle of a [`CrepeDB`].
+///
+/// Backend calls run on a dedicated pool of worker threads, so they never
+/// block the async runtime. Transactions are opened on a worker thread and
+/// keep it until they end, so they can be held across `.await` points. At
+/// most as many transactions as there are worker threads can be open at the
+/// same time, further calls wait for one of them to end.
+///
+/// The handle is cheap to clone. The worker threads stop once every handle
+/// and transaction is dropped.
+///
+/// # Example
+///
+/// ```ignore
+/// let db = AsyncCrepeDB::new(CrepeDB::new(RedbDatabase::memory()?));
+///
+/// let txn = db.write(Some(root)).await?;
+/// txn.set("my_table", b"key".to_vec(), b"value".to_vec()).await?;
+/// let snapshot = txn.commit().await?;
+///
+/// let value = db.get(snapshot, "my_table", b"key".to_vec()).await?;
+/// ```
+pub struct AsyncCrepeDB<B> {
+    jobs: Arc<mpsc::Sender<Job<B>>>,
+}
+
+impl<B> Clone for AsyncCrepeDB<B> {
+    fn clone(&self) -> Self {
+        Self {
+            jobs: Arc::clone(&self.jobs),
+        }
+    }
+}
+
+impl<B> AsyncCrepeDB<B>
+where
+    B: Backend + Send + Sync,
+{
+    /// Run `db` on [`DEFAULT_THREADS`] worker threads.
+    pub fn new(db: CrepeDB<B>) -> Self {
+        Self::with_threads(db, DEFAULT_THREADS)
+    }
+
+    /// Run `db` on `threads` worker threads, at least 1.
+    pub fn with_threads(db: CrepeDB<B>, threads: usize) -> Self {
+        let db = Arc::new(db);
+
+        let (jobs, rx) = mpsc::channel::<Job<B>>();
+        let rx = Arc::new(Mutex::new(rx));
+
+        for i in 0..threads.max(1) {
+            let db = Arc::clone(&db);
+            let rx = Arc::clone(&rx);
+
+            thread::Builder::new()
+                .name(format!("crepedb-worker-{i}"))
+                .spawn(move || loop {
+                    let job = match rx.lock() {
+                        Ok(rx) => rx.recv(),
+                        Err(_) => return,
+                    };
+
+                    let Ok(job) = job else {
+                        return;
+                    };
+
+                    // The caller sees the dropped reply as a stopped worker.
+                    if panic::catch_unwind(AssertUnwindSafe(|| job(&db))).is_err() {
+                        log::error!("CrepeDB worker job panicked");
+                    }
+                })
+                .expect("failed to spawn CrepeDB worker thread");
+        }
+
+        Self {
+            jobs: Arc::new(jobs),
+        }
+    }
+
+    fn spawn(&self, job: impl FnOnce(&CrepeDB<B>) + Send + 'static) -> Result<()> {
+        self.jobs.send(Box::new(job)).map_err(worker_stopped)
+    }
+
+    /// Run `f` on a worker thread and wait for its result.
+    ///
+    /// Use this for work which has no async counterpart, such as
+    /// [`CrepeDB::write_with_retry`] or [`CrepeDB::verify`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `f`, or [`AsyncError::WorkerStopped`](crate::AsyncError::WorkerStopped)
+    /// if `f` panicked.
+    pub async fn run<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&CrepeDB<B>) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (reply, rx) = oneshot::channel();
+
+        self.spawn(move |db| {
+            let _ = reply.send(f(db));
+        })?;
+
+        rx.await.map_err(worker_stopped)?
+    }
+
+    /// Open a read transaction at a snapshot.
+    ///
+    /// See [`CrepeDB::read`].
+    pub async fn read(&self, snapshot_id: Option<SnapshotId>) -> Result<AsyncReadTxn> {
+        let (cmds, rx) = channel::unbounded_channel();
+        let (opened, opened_rx) = oneshot::channel();
+
+        self.spawn(move |db| match db.read(snapshot_id) {
+            Ok(txn) => {
+                let _ = opened.send(Ok(()));
+                serve_read(txn, rx);
+            }
+            Err(e) => {
+                let _ = opened.send(Err(e));
+            }
+        })?;
+
+        opened_rx.await.map_err(worker_stopped)??;
+
+        Ok(AsyncReadTxn { cmds })
+    }
+
+    /// Open a write transaction creating a child of a snapshot.
+    ///
+    /// See [`CrepeDB::write`].
+    pub async fn write(&self, snapshot_id: Option<SnapshotId>) -> Result<AsyncWriteTxn> {
+        self.write_in_lane(snapshot_id, 0).await
+    }
+
+    /// Open a write transaction creating a child of a snapshot, allocating the
+    /// new snapshot ID in `lane`.
+    ///
+    /// See [`CrepeDB::write_in_lane`].
+    pub async fn write_in_lane(
+        &self,
+        snapshot_id: Option<SnapshotId>,
+        lane: u16,
+    ) -> Result<AsyncWriteTxn> {
+        let (cmds, rx) = channel::unbounded_channel();
+        let (opened, opened_rx) = oneshot::channel();
+
+        self.spawn(move |db| match db.write_in_lane(snapshot_id, lane) {
+            Ok(txn) => {
+                let _ = opened.send(Ok(()));
+                serve_write(txn, rx);
+            }
+            Err(e) => {
+                let _ = opened.send(Err(e));
+            }
+        })?;
+
+        opened_rx.await.map_err(worker_stopped)??;
+
+        Ok(AsyncWriteTxn { cmds })
+    }
+
+    /// Get the value associated with a key of a table at a snapshot.
+    ///
+    /// Same as [`AsyncReadTxn::get`] in a new read transaction.
+    pub async fn get(
+        &self,
+        snapshot_id: SnapshotId,
+        table: &str,
+        key: Bytes,
+    ) -> Result<Option<Bytes>> {
+        let table = String::from(table);
+
+        self.run(move |db| {
+            let txn = db.read(Some(snapshot_id))?;
+            txn::get(&txn, &table, key)
+        })
+        .await
+    }
+
+    /// Stream the key-value pairs of a table with keys in `begin..end` at a
+    /// snapshot.
+    ///
+    /// Same as [`AsyncReadTxn::range`] in a new read transaction, which keeps
+    /// its worker thread until the stream ends or is dropped.
+    pub fn range(
+        &self,
+        snapshot_id: SnapshotId,
+        table: &str,
+        begin: Bytes,
+        end: Bytes,
+    ) -> RangeStream {
+        let (cmds, mut rx) = channel::unbounded_channel();
+        let stream = RangeStream::new(cmds, table.into(), begin, end);
+
+        // On failure the commands are dropped and the stream yields an error.
+        let _ = self.spawn(move |db| match db.read(Some(snapshot_id)) {
+            Ok(txn) => serve_read(txn, rx),
+            Err(e) => {
+                // The stream sends its first page request before this runs.
+                if let Ok(cmd) = rx.try_recv() {
+                    cmd.fail(e);
+                }
+            }
+        });
+
+        stream
+    }
+}
@@ -0,0 +1,28 @@
+use std::fmt::{self, Display};
+
+use crepedb_core::Error;
+
+/// Errors of the async wrapper.
+///
+/// They are returned as [`Error::BackendError`], use
+/// [`Error::backend_error`] to match them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsyncError {
+    /// The worker thread serving the call stopped, because all handles of the
+    /// database were dropped or the call panicked.
+    WorkerStopped,
+}
+
+impl Display for AsyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WorkerStopped => write!(f, "Worker thread stopped"),
+        }
+    }
+}
+
+impl std::error::Error for AsyncError {}
+
+pub(crate) fn worker_stopped<T>(_: T) -> Error {
+    Error::backend(AsyncError::WorkerStopped)
+}
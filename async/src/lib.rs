@@ -0,0 +1,253 @@
+//! # CrepeDB Async
+//!
+//! An asynchronous wrapper of [`CrepeDB`](crepedb_core::CrepeDB) for async
+//! services, such as tokio based servers.
+//!
+//! [`AsyncCrepeDB`] runs every backend call on a dedicated pool of worker
+//! threads and exposes it as `async fn`. Transactions stay on their worker
+//! thread, so they can be held across `.await` points, and range reads are
+//! exposed as [`Stream`](futures_core::Stream)s.
+//!
+//! The wrapper only needs the `sync` feature of tokio and works with any
+//! async runtime.
+//!
+//! ## Example
+//!
+//! ```ignore
+//! use crepedb::CrepeDB;
+//! use crepedb_async::AsyncCrepeDB;
+//! use crepedb_redb::RedbDatabase;
+//!
+//! let db = AsyncCrepeDB::new(CrepeDB::new(RedbDatabase::memory()?));
+//!
+//! let txn = db.write(None).await?;
+//! txn.create_versioned_table("my_table").await?;
+//! txn.set("my_table", b"key".to_vec(), b"value".to_vec()).await?;
+//! let root = txn.commit().await?;
+//!
+//! let txn = db.read(Some(root)).await?;
+//! assert_eq!(txn.get("my_table", b"key".to_vec()).await?, Some(b"value".to_vec()));
+//! ```
+
+mod error;
+pub use error::*;
+
+mod db;
+pub use db::*;
+
+mod txn;
+pub use txn::{AsyncReadTxn, AsyncWriteTxn};
+
+mod range;
+pub use range::*;
+
+#[cfg(test)]
+mod tests {
+    use std::{future::poll_fn, pin::Pin};
+
+    use crepedb_core::{
+        types::{Bytes, SnapshotId},
+        CrepeDB, Error, Result,
+    };
+    use crepedb_redb::RedbDatabase;
+    use futures_core::Stream;
+
+    use crate::{AsyncCrepeDB, AsyncError, RangeStream, PAGE_SIZE};
+
+    async fn collect(mut stream: RangeStream) -> Result<Vec<(Bytes, Bytes)>> {
+        let mut pairs = Vec::new();
+
+        while let Some(pair) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+            pairs.push(pair?);
+        }
+
+        Ok(pairs)
+    }
+
+    fn db() -> AsyncCrepeDB<RedbDatabase> {
+        AsyncCrepeDB::with_threads(CrepeDB::new(RedbDatabase::memory().unwrap()), 2)
+    }
+
+    #[tokio::test]
+    async fn test_read_write() {
+        let db = db();
+
+        let txn = db.write(None).await.unwrap();
+        txn.create_versioned_table("v").await.unwrap();
+        txn.create_basic_table("b").await.unwrap();
+        txn.set("v", vec![1], vec![1]).await.unwrap();
+        txn.set("v", vec![2], vec![2]).await.unwrap();
+        txn.set("b", vec![1], vec![1]).await.unwrap();
+        let root = txn.commit().await.unwrap();
+
+        let txn = db.write(Some(root.clone())).await.unwrap();
+        txn.del("v", vec![1]).await.unwrap();
+        txn.set("v", vec![3], vec![3]).await.unwrap();
+        let child = txn.commit().await.unwrap();
+
+        // Held across other transactions and awaits.
+        let read = db.read(Some(root.clone())).await.unwrap();
+
+        let txn = db.write(Some(root.clone())).await.unwrap();
+        txn.set("v", vec![1], vec![9]).await.unwrap();
+        tokio::task::yield_now().await;
+        txn.commit().await.unwrap();
+
+        assert_eq!(read.get("v", vec![1]).await.unwrap(), Some(vec![1]));
+        assert_eq!(
+            collect(read.range("v", vec![], vec![0xff])).await.unwrap(),
+            vec![(vec![1], vec![1]), (vec![2], vec![2])]
+        );
+        drop(read);
+
+        assert_eq!(db.get(child.clone(), "v", vec![1]).await.unwrap(), None);
+        assert_eq!(
+            collect(db.range(child.clone(), "v", vec![2], vec![0xff]))
+                .await
+                .unwrap(),
+            vec![(vec![2], vec![2]), (vec![3], vec![3])]
+        );
+        assert_eq!(
+            collect(db.range(child.clone(), "b", vec![], vec![0xff]))
+                .await
+                .unwrap(),
+            vec![(vec![1], vec![1])]
+        );
+
+        // A dropped transaction discards its changes.
+        let txn = db.write(Some(child.clone())).await.unwrap();
+        txn.set("v", vec![4], vec![4]).await.unwrap();
+        drop(txn);
+
+        let txn = db.write(Some(child.clone())).await.unwrap();
+        let next = txn.commit().await.unwrap();
+        assert_eq!(db.get(next, "v", vec![4]).await.unwrap(), None);
+
+        let report = db.run(|db| db.verify()).await.unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.snapshots, 4);
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let db = db();
+
+        assert!(matches!(
+            db.write(Some(SnapshotId::root())).await.unwrap_err(),
+            Error::MissingSnaopshot(_)
+        ));
+
+        let txn = db.write(None).await.unwrap();
+        assert!(matches!(
+            txn.set("missing", vec![1], vec![1]).await,
+            Err(Error::MissingTable)
+        ));
+        txn.create_versioned_table("v").await.unwrap();
+        txn.set("v", vec![1], vec![1]).await.unwrap();
+        let root = txn.commit().await.unwrap();
+
+        let mut stream = db.range(root.clone(), "missing", vec![], vec![0xff]);
+        let next = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+        assert!(matches!(next, Some(Err(Error::MissingTable))));
+        let next = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+        assert!(next.is_none());
+
+        // The error of opening the read transaction ends the stream.
+        let missing = SnapshotId::from_bytes(&100u64.to_be_bytes()).unwrap();
+        let mut stream = db.range(missing, "v", vec![], vec![0xff]);
+        let next = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+        assert!(matches!(next, Some(Err(Error::MissingSnaopshot(_)))));
+        let next = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+        assert!(next.is_none());
+
+        // A panic is reported and does not stop the worker.
+        let e = db
+            .run(|_| -> Result<()> { panic!("job") })
+            .await
+            .unwrap_err();
+        assert_eq!(e.backend_error(), Some(&AsyncError::WorkerStopped));
+
+        assert_eq!(db.get(root, "v", vec![1]).await.unwrap(), Some(vec![1]));
+    }
+
+    #[tokio::test]
+    async fn test_range_pages() {
+        let db = db();
+
+        let keys: Vec<_> = (0..PAGE_SIZE as u16 * 2 + 10)
+            .map(|i| i.to_be_bytes().to_vec())
+            .collect();
+
+        let txn = db.write(None).await.unwrap();
+        txn.create_versioned_table("v").await.unwrap();
+        for key in &keys {
+            txn.set("v", key.clone(), key.clone()).await.unwrap();
+        }
+        let root = txn.commit().await.unwrap();
+
+        let read = db.read(Some(root.clone())).await.unwrap();
+        let mut stream = read.range("v", vec![], vec![0xff]);
+
+        // Other calls of the transaction are served while the stream is open.
+        for key in &keys[..PAGE_SIZE + 1] {
+            let next = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+            assert_eq!(next.unwrap().unwrap(), (key.clone(), key.clone()));
+        }
+        assert_eq!(
+            read.get("v", keys[0].clone()).await.unwrap(),
+            Some(keys[0].clone())
+        );
+
+        let rest = collect(stream).await.unwrap();
+        assert_eq!(rest.len(), keys.len() - PAGE_SIZE - 1);
+        assert_eq!(rest.last().unwrap().0, *keys.last().unwrap());
+
+        // The range ends between pages.
+        let end = keys[PAGE_SIZE].clone();
+        let pairs = collect(db.range(root.clone(), "v", vec![], end))
+            .await
+            .unwrap();
+        assert_eq!(pairs.len(), PAGE_SIZE);
+
+        // A dropped stream releases its worker thread.
+        for _ in 0..4 {
+            drop(db.range(root.clone(), "v", vec![], vec![0xff]));
+        }
+        assert_eq!(
+            db.get(root, "v", keys[0].clone()).await.unwrap(),
+            Some(keys[0].clone())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reads() {
+        let db = db();
+
+        let txn = db.write(None).await.unwrap();
+        txn.create_versioned_table("v").await.unwrap();
+        let root = txn.commit().await.unwrap();
+
+        let mut snapshots = Vec::new();
+        for i in 0..8u8 {
+            let txn = db.write(Some(root.clone())).await.unwrap();
+            txn.set("v", vec![0], vec![i]).await.unwrap();
+            snapshots.push(txn.commit().await.unwrap());
+        }
+
+        let tasks: Vec<_> = snapshots
+            .into_iter()
+            .enumerate()
+            .map(|(i, snapshot)| {
+                let db = db.clone();
+                tokio::spawn(async move {
+                    let value = db.get(snapshot, "v", vec![0]).await.unwrap();
+                    assert_eq!(value, Some(vec![i as u8]));
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    vec,
+};
+
+use crepedb_core::{types::Bytes, Result};
+use futures_core::Stream;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{error::worker_stopped, txn::ReadCmd};
+
+/// Maximum number of key-value pairs read from the backend at once.
+pub const PAGE_SIZE: usize = 256;
+
+type Page = Vec<(Bytes, Bytes)>;
+
+/// A stream of the key-value pairs of a range, ordered by key.
+///
+/// Created by [`AsyncReadTxn::range`](crate::AsyncReadTxn::range) and
+/// [`AsyncCrepeDB::range`](crate::AsyncCrepeDB::range). The range is read on
+/// the worker thread of its read transaction in pages of at most
+/// [`PAGE_SIZE`] pairs. The next page is requested when a page arrives, so at
+/// most two pages are held at once, and other calls of the transaction are
+/// served between pages. An error ends the stream.
+pub struct RangeStream {
+    /// The commands of the read transaction, `None` once the last page
+    /// arrived, so that a transaction opened for the stream ends with it.
+    cmds: Option<mpsc::UnboundedSender<ReadCmd>>,
+    table: String,
+    end: Bytes,
+    pairs: vec::IntoIter<(Bytes, Bytes)>,
+    /// The requested page, `None` after the last one.
+    next: Option<oneshot::Receiver<Result<Page>>>,
+}
+
+impl RangeStream {
+    pub(crate) fn new(
+        cmds: mpsc::UnboundedSender<ReadCmd>,
+        table: String,
+        begin: Bytes,
+        end: Bytes,
+    ) -> Self {
+        let mut stream = Self {
+            cmds: Some(cmds),
+            table,
+            end,
+            pairs: Vec::new().into_iter(),
+            next: None,
+        };
+
+        stream.request(begin);
+        stream
+    }
+
+    /// Request the page starting at `begin`.
+    fn request(&mut self, begin: Bytes) {
+        let Some(cmds) = &self.cmds else {
+            return;
+        };
+
+        let (reply, rx) = oneshot::channel();
+
+        // On failure the reply is dropped and the stream yields an error.
+        let _ = cmds.send(ReadCmd::Page {
+            table: self.table.clone(),
+            begin,
+            end: self.end.clone(),
+            reply,
+        });
+
+        self.next = Some(rx);
+    }
+}
+
+impl Stream for RangeStream {
+    type Item = Result<(Bytes, Bytes)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(pair) = self.pairs.next() {
+                return Poll::Ready(Some(Ok(pair)));
+            }
+
+            let Some(next) = &mut self.next else {
+                return Poll::Ready(None);
+            };
+
+            let res = ready!(Pin::new(next).poll(cx));
+            self.next = None;
+
+            let page = match res.map_err(worker_stopped).and_then(|r| r) {
+                Ok(page) => page,
+                Err(e) => {
+                    self.cmds = None;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            };
+
+            // A shorter page is the last one.
+            match page.last() {
+                Some((key, _)) if page.len() == PAGE_SIZE => {
+                    let mut begin = key.clone();
+                    begin.push(0);
+                    self.request(begin);
+                }
+                _ => self.cmds = None,
+            }
+
+            self.pairs = page.into_iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.pairs.len();
+
+        match self.next {
+            Some(_) => (len, None),
+            None => (len, Some(len)),
+        }
+    }
+}
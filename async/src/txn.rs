@@ -0,0 +1,270 @@
+use crepedb_core::{
+    backend::{BackendError, ReadTxn as BackendReadTxn, WriteTxn as BackendWriteTxn},
+    types::{Bytes, SnapshotId},
+    Error, ReadTxn, Result, WriteTxn,
+};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{error::worker_stopped, RangeStream, PAGE_SIZE};
+
+type Reply<T> = oneshot::Sender<Result<T>>;
+
+pub(crate) enum ReadCmd {
+    Get {
+        table: String,
+        key: Bytes,
+        reply: Reply<Option<Bytes>>,
+    },
+    Page {
+        table: String,
+        begin: Bytes,
+        end: Bytes,
+        reply: Reply<Vec<(Bytes, Bytes)>>,
+    },
+}
+
+impl ReadCmd {
+    /// Reply `e` to the command.
+    pub(crate) fn fail(self, e: Error) {
+        match self {
+            Self::Get { reply, .. } => {
+                let _ = reply.send(Err(e));
+            }
+            Self::Page { reply, .. } => {
+                let _ = reply.send(Err(e));
+            }
+        }
+    }
+}
+
+pub(crate) enum WriteCmd {
+    CreateTable {
+        table: String,
+        versioned: bool,
+        reply: Reply<()>,
+    },
+    Set {
+        table: String,
+        key: Bytes,
+        value: Bytes,
+        reply: Reply<()>,
+    },
+    Del {
+        table: String,
+        key: Bytes,
+        reply: Reply<()>,
+    },
+    Commit {
+        reply: Reply<SnapshotId>,
+    },
+}
+
+pub(crate) fn get<T, E>(txn: &ReadTxn<T, E>, table: &str, key: Bytes) -> Result<Option<Bytes>>
+where
+    T: BackendReadTxn<E>,
+    E: BackendError,
+{
+    let table = txn.open_table(table)?;
+    table.get(key)
+}
+
+/// Read at most [`PAGE_SIZE`] key-value pairs of a range.
+pub(crate) fn page<T, E>(
+    txn: &ReadTxn<T, E>,
+    table: &str,
+    begin: Bytes,
+    end: Bytes,
+) -> Result<Vec<(Bytes, Bytes)>>
+where
+    T: BackendReadTxn<E>,
+    E: BackendError,
+{
+    let table = txn.open_table(table)?;
+    let page = table.scan(begin, Some(&end))?.take(PAGE_SIZE).collect();
+    page
+}
+
+/// Serve the commands of an [`AsyncReadTxn`] until it is dropped.
+pub(crate) fn serve_read<T, E>(txn: ReadTxn<T, E>, mut cmds: mpsc::UnboundedReceiver<ReadCmd>)
+where
+    T: BackendReadTxn<E>,
+    E: BackendError,
+{
+    while let Some(cmd) = cmds.blocking_recv() {
+        match cmd {
+            ReadCmd::Get { table, key, reply } => {
+                let _ = reply.send(get(&txn, &table, key));
+            }
+            ReadCmd::Page {
+                table,
+                begin,
+                end,
+                reply,
+            } => {
+                let _ = reply.send(page(&txn, &table, begin, end));
+            }
+        }
+    }
+}
+
+/// Serve the commands of an [`AsyncWriteTxn`] until it is committed or
+/// dropped.
+pub(crate) fn serve_write<T, E>(txn: WriteTxn<T, E>, mut cmds: mpsc::UnboundedReceiver<WriteCmd>)
+where
+    T: BackendWriteTxn<E>,
+    E: BackendError,
+{
+    while let Some(cmd) = cmds.blocking_recv() {
+        match cmd {
+            WriteCmd::CreateTable {
+                table,
+                versioned,
+                reply,
+            } => {
+                let res = if versioned {
+                    txn.create_versioned_table(&table)
+                } else {
+                    txn.create_basic_table(&table)
+                };
+
+                let _ = reply.send(res);
+            }
+            WriteCmd::Set {
+                table,
+                key,
+                value,
+                reply,
+            } => {
+                let res = txn
+                    .open_table(&table)
+                    .and_then(|mut table| table.set(key, value));
+
+                let _ = reply.send(res);
+            }
+            WriteCmd::Del { table, key, reply } => {
+                let res = txn.open_table(&table).and_then(|mut table| table.del(key));
+
+                let _ = reply.send(res);
+            }
+            WriteCmd::Commit { reply } => {
+                let _ = reply.send(txn.commit());
+                return;
+            }
+        }
+    }
+
+    log::debug!("Abort write transaction dropped without commit");
+}
+
+/// Send `cmd` built with a reply channel and wait for the reply.
+async fn call<C, T>(cmds: &mpsc::UnboundedSender<C>, cmd: impl FnOnce(Reply<T>) -> C) -> Result<T> {
+    let (reply, rx) = oneshot::channel();
+
+    cmds.send(cmd(reply)).map_err(worker_stopped)?;
+
+    rx.await.map_err(worker_stopped)?
+}
+
+/// A read transaction running on a worker thread.
+///
+/// Created by [`AsyncCrepeDB::read`](crate::AsyncCrepeDB::read). The
+/// transaction keeps its worker thread until it is dropped.
+#[derive(Debug)]
+pub struct AsyncReadTxn {
+    pub(crate) cmds: mpsc::UnboundedSender<ReadCmd>,
+}
+
+impl AsyncReadTxn {
+    /// Get the value associated with a key of a table.
+    ///
+    /// See [`ReadTable::get`](crepedb_core::ReadTable::get).
+    pub async fn get(&self, table: &str, key: Bytes) -> Result<Option<Bytes>> {
+        call(&self.cmds, |reply| ReadCmd::Get {
+            table: table.into(),
+            key,
+            reply,
+        })
+        .await
+    }
+
+    /// Stream the key-value pairs of a table with keys in `begin..end`.
+    ///
+    /// See [`ReadTable::range`](crepedb_core::ReadTable::range).
+    /// The pairs are read in pages between the other calls of the
+    /// transaction, see [`RangeStream`].
+    pub fn range(&self, table: &str, begin: Bytes, end: Bytes) -> RangeStream {
+        RangeStream::new(self.cmds.clone(), table.into(), begin, end)
+    }
+}
+
+/// A write transaction running on a worker thread.
+///
+/// Created by [`AsyncCrepeDB::write`](crate::AsyncCrepeDB::write). The
+/// transaction keeps its worker thread until it is committed or dropped,
+/// dropping it discards its changes.
+#[derive(Debug)]
+pub struct AsyncWriteTxn {
+    pub(crate) cmds: mpsc::UnboundedSender<WriteCmd>,
+}
+
+impl AsyncWriteTxn {
+    /// Create a new basic (non-versioned) table.
+    ///
+    /// See [`WriteTxn::create_basic_table`].
+    pub async fn create_basic_table(&self, table: &str) -> Result<()> {
+        call(&self.cmds, |reply| WriteCmd::CreateTable {
+            table: table.into(),
+            versioned: false,
+            reply,
+        })
+        .await
+    }
+
+    /// Create a new versioned table.
+    ///
+    /// See [`WriteTxn::create_versioned_table`].
+    pub async fn create_versioned_table(&self, table: &str) -> Result<()> {
+        call(&self.cmds, |reply| WriteCmd::CreateTable {
+            table: table.into(),
+            versioned: true,
+            reply,
+        })
+        .await
+    }
+
+    /// Set a key-value pair in a table.
+    ///
+    /// See [`WriteTable::set`](crepedb_core::WriteTable::set).
+    pub async fn set(&self, table: &str, key: Bytes, value: Bytes) -> Result<()> {
+        call(&self.cmds, |reply| WriteCmd::Set {
+            table: table.into(),
+            key,
+            value,
+            reply,
+        })
+        .await
+    }
+
+    /// Delete a key from a table.
+    ///
+    /// See [`WriteTable::del`](crepedb_core::WriteTable::del).
+    pub async fn del(&self, table: &str, key: Bytes) -> Result<()> {
+        call(&self.cmds, |reply| WriteCmd::Del {
+            table: table.into(),
+            key,
+            reply,
+        })
+        .await
+    }
+
+    /// Commit the write transaction.
+    ///
+    /// See [`WriteTxn::commit`].
+    ///
+    /// # Returns
+    ///
+    /// The snapshot ID of the newly created snapshot.
+    pub async fn commit(self) -> Result<SnapshotId> {
+        call(&self.cmds, |reply| WriteCmd::Commit { reply }).await
+    }
+}
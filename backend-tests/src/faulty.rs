@@ -0,0 +1,679 @@
+//! Fault injection.
+//!
+//! [`FaultyBackend`] wraps a backend and makes a chosen call fail, either once
+//! or as a crash which fails every call until the backend is restarted. The
+//! tests in this module use it to check that CrepeDB keeps its internal tables
+//! consistent when the backend fails at any point, see [`check_invariants`].
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    sync::{Mutex, MutexGuard},
+};
+
+use crepedb_core::{
+    backend::{Backend, Range, ReadTable, ReadTxn, WriteTable, WriteTxn},
+    consts,
+    types::{Bytes, SnapshotId},
+    CrepeDB, Error,
+};
+
+use crate::raw::collect;
+
+const TABLE: &str = "faulty";
+
+/// A backend call which can be made to fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Call {
+    /// `open_table` of a read or write transaction.
+    OpenTable,
+    /// `set` of a table.
+    Set,
+    /// `del` of a table.
+    Del,
+    /// `back` of a range, which returns its next item.
+    RangeNext,
+    /// `commit` of a write transaction.
+    Commit,
+}
+
+/// What happens when a call hits an injected fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The call fails, later calls succeed.
+    Error,
+    /// The call fails and so does every later call until
+    /// [`FaultyBackend::restart`], as if the process died at that point.
+    Crash,
+}
+
+/// Errors returned by [`FaultyBackend`].
+#[derive(Debug)]
+pub enum FaultyError<E> {
+    /// The call hit an injected fault.
+    Injected(Call),
+    /// The backend crashed and was not restarted yet.
+    Crashed,
+    /// An error of the wrapped backend.
+    Backend(E),
+}
+
+impl<E: Display> Display for FaultyError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Injected(call) => write!(f, "injected fault in {call:?}"),
+            Self::Crashed => write!(f, "backend crashed"),
+            Self::Backend(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Armed {
+    call: Call,
+    skip: usize,
+    fault: Fault,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    armed: Option<Armed>,
+    crashed: bool,
+    calls: [usize; 5],
+}
+
+impl State {
+    fn alive<E>(&self) -> Result<(), FaultyError<E>> {
+        if self.crashed {
+            Err(FaultyError::Crashed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Count `call` and fail it if it hits the armed fault.
+fn hit<E>(state: &Mutex<State>, call: Call) -> Result<(), FaultyError<E>> {
+    let mut state = state.lock().unwrap();
+
+    state.alive()?;
+    state.calls[call as usize] += 1;
+
+    let fault = match &mut state.armed {
+        Some(armed) if armed.call == call && armed.skip > 0 => {
+            armed.skip -= 1;
+            return Ok(());
+        }
+        Some(armed) if armed.call == call => armed.fault,
+        _ => return Ok(()),
+    };
+
+    state.armed = None;
+    state.crashed = fault == Fault::Crash;
+
+    Err(FaultyError::Injected(call))
+}
+
+/// A backend which fails chosen calls of the wrapped backend.
+///
+/// At most one fault is armed at a time. Calls are counted per [`Call`] kind
+/// and the armed fault is disarmed once it was hit.
+///
+/// A write transaction whose commit fails is dropped without committing it.
+/// After a [`Fault::Crash`] every call fails with [`FaultyError::Crashed`], so
+/// open transactions can only be dropped, until [`restart`](Self::restart)
+/// is called.
+///
+/// # Example
+///
+/// ```ignore
+/// let db = CrepeDB::new(FaultyBackend::new(MemoryBackend::new()));
+///
+/// // Fail the third `set` from now on.
+/// db.backend().inject(Call::Set, 2, Fault::Error);
+/// ```
+#[derive(Debug)]
+pub struct FaultyBackend<B> {
+    inner: B,
+    state: Mutex<State>,
+}
+
+impl<B> FaultyBackend<B> {
+    /// Wrap `inner` without any armed fault.
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Get a reference to the wrapped backend.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the wrapper and return the wrapped backend.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Let the next `skip` calls of kind `call` succeed and fail the one after
+    /// them with `fault`.
+    ///
+    /// Replaces the fault armed before, if any.
+    pub fn inject(&self, call: Call, skip: usize, fault: Fault) {
+        self.state().armed = Some(Armed { call, skip, fault });
+    }
+
+    /// Disarm the armed fault, if any.
+    pub fn clear(&self) {
+        self.state().armed = None;
+    }
+
+    /// Whether a fault is armed and was not hit yet.
+    pub fn is_armed(&self) -> bool {
+        self.state().armed.is_some()
+    }
+
+    /// Whether a [`Fault::Crash`] was hit since the last restart.
+    pub fn is_crashed(&self) -> bool {
+        self.state().crashed
+    }
+
+    /// Recover from a crash and disarm the armed fault.
+    ///
+    /// Only committed changes survive, as all transactions open during the
+    /// crash must have been dropped.
+    pub fn restart(&self) {
+        let mut state = self.state();
+
+        state.armed = None;
+        state.crashed = false;
+    }
+
+    /// The number of calls of kind `call` made so far, including failed ones.
+    pub fn calls(&self, call: Call) -> usize {
+        self.state().calls[call as usize]
+    }
+}
+
+impl<B: Backend> Backend for FaultyBackend<B> {
+    type Error = FaultyError<B::Error>;
+
+    type ReadTxn<'a> = FaultyReadTxn<'a, B::ReadTxn<'a>>;
+
+    type WriteTxn<'a> = FaultyWriteTxn<'a, B::WriteTxn<'a>>;
+
+    fn is_conflict(error: &Self::Error) -> bool {
+        matches!(error, FaultyError::Backend(e) if B::is_conflict(e))
+    }
+
+    fn read_txn(&self) -> Result<Self::ReadTxn<'_>, Self::Error> {
+        self.state().alive()?;
+
+        Ok(FaultyReadTxn {
+            inner: self.inner.read_txn().map_err(FaultyError::Backend)?,
+            state: &self.state,
+        })
+    }
+
+    fn write_txn(&self) -> Result<Self::WriteTxn<'_>, Self::Error> {
+        self.state().alive()?;
+
+        Ok(FaultyWriteTxn {
+            inner: self.inner.write_txn().map_err(FaultyError::Backend)?,
+            state: &self.state,
+        })
+    }
+}
+
+/// A read transaction of [`FaultyBackend`].
+pub struct FaultyReadTxn<'s, T> {
+    inner: T,
+    state: &'s Mutex<State>,
+}
+
+impl<'s, T, E> ReadTxn<FaultyError<E>> for FaultyReadTxn<'s, T>
+where
+    T: ReadTxn<E>,
+{
+    type Table<'a>
+        = FaultyTable<'a, T::Table<'a>>
+    where
+        Self: 'a;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, FaultyError<E>> {
+        hit(self.state, Call::OpenTable)?;
+
+        Ok(FaultyTable {
+            inner: self.inner.open_table(table).map_err(FaultyError::Backend)?,
+            state: self.state,
+        })
+    }
+}
+
+/// A write transaction of [`FaultyBackend`].
+pub struct FaultyWriteTxn<'s, T> {
+    inner: T,
+    state: &'s Mutex<State>,
+}
+
+impl<'s, T, E> WriteTxn<FaultyError<E>> for FaultyWriteTxn<'s, T>
+where
+    T: WriteTxn<E>,
+{
+    type Table<'a>
+        = FaultyTable<'a, T::Table<'a>>
+    where
+        Self: 'a;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, FaultyError<E>> {
+        hit(self.state, Call::OpenTable)?;
+
+        Ok(FaultyTable {
+            inner: self.inner.open_table(table).map_err(FaultyError::Backend)?,
+            state: self.state,
+        })
+    }
+
+    fn set_savepoint(&self) -> Result<bool, FaultyError<E>> {
+        self.state.lock().unwrap().alive()?;
+
+        self.inner.set_savepoint().map_err(FaultyError::Backend)
+    }
+
+    fn rollback_to_savepoint(&self) -> Result<(), FaultyError<E>> {
+        self.state.lock().unwrap().alive()?;
+
+        self.inner
+            .rollback_to_savepoint()
+            .map_err(FaultyError::Backend)
+    }
+
+    fn remove_table(&self, table: &str) -> Result<(), FaultyError<E>> {
+        self.state.lock().unwrap().alive()?;
+
+        self.inner.remove_table(table).map_err(FaultyError::Backend)
+    }
+
+    fn commit(self) -> Result<(), FaultyError<E>> {
+        // On failure `self.inner` is dropped without committing it.
+        hit(self.state, Call::Commit)?;
+
+        self.inner.commit().map_err(FaultyError::Backend)
+    }
+}
+
+/// A table opened in a transaction of [`FaultyBackend`].
+pub struct FaultyTable<'s, T> {
+    inner: T,
+    state: &'s Mutex<State>,
+}
+
+impl<'s, T, E> ReadTable<FaultyError<E>> for FaultyTable<'s, T>
+where
+    T: ReadTable<E>,
+{
+    type Range<'a>
+        = FaultyRange<'a, T::Range<'a>>
+    where
+        Self: 'a;
+
+    type Value<'a>
+        = T::Value<'a>
+    where
+        Self: 'a;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, FaultyError<E>> {
+        self.state.lock().unwrap().alive()?;
+
+        self.inner.get(key).map_err(FaultyError::Backend)
+    }
+
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, FaultyError<E>> {
+        self.state.lock().unwrap().alive()?;
+
+        self.inner.get_ref(key).map_err(FaultyError::Backend)
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, FaultyError<E>> {
+        self.state.lock().unwrap().alive()?;
+
+        Ok(FaultyRange {
+            inner: self.inner.range(begin, end).map_err(FaultyError::Backend)?,
+            state: self.state,
+        })
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+impl<'s, T, E> WriteTable<FaultyError<E>> for FaultyTable<'s, T>
+where
+    T: WriteTable<E>,
+{
+    fn set(&mut self, key: &[u8], value: &[u8]) -> Result<(), FaultyError<E>> {
+        hit(self.state, Call::Set)?;
+
+        self.inner.set(key, value).map_err(FaultyError::Backend)
+    }
+
+    fn del(&mut self, key: &[u8]) -> Result<(), FaultyError<E>> {
+        hit(self.state, Call::Del)?;
+
+        self.inner.del(key).map_err(FaultyError::Backend)
+    }
+}
+
+/// A range iterator of [`FaultyBackend`].
+pub struct FaultyRange<'s, T> {
+    inner: T,
+    state: &'s Mutex<State>,
+}
+
+impl<'s, T, E> Range<FaultyError<E>> for FaultyRange<'s, T>
+where
+    T: Range<E>,
+{
+    fn back(&mut self) -> Result<Option<(Bytes, Bytes)>, FaultyError<E>> {
+        hit(self.state, Call::RangeNext)?;
+
+        self.inner.back().map_err(FaultyError::Backend)
+    }
+
+    fn back_key(&mut self) -> Result<Option<Bytes>, FaultyError<E>> {
+        hit(self.state, Call::RangeNext)?;
+
+        self.inner.back_key().map_err(FaultyError::Backend)
+    }
+}
+
+fn parse_u64(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes(bytes.try_into().expect("8 byte integer"))
+}
+
+/// Check the internal tables of CrepeDB on `backend`.
+///
+/// Snapshot IDs of every lane must be allocated without gaps from the start of
+/// the lane up to its next snapshot ID, every snapshot must be one version
+/// above an existing parent and the
+/// snapshot index must hold exactly the rows derived from the snapshot table.
+///
+/// # Panics
+///
+/// Panics if an invariant is violated.
+pub fn check_invariants<B: Backend>(backend: &B) -> Result<(), B::Error> {
+    let txn = backend.read_txn()?;
+
+    let snapshot_table = txn.open_table(consts::SNAPSHOT_TABLE)?;
+    let index_table = txn.open_table(consts::SNAPSHOT_INDEX_TABLE)?;
+
+    let mut next = BTreeMap::new();
+    let mut snapshots = BTreeMap::new();
+
+    for (key, value) in collect(snapshot_table.range(&[], None)?)? {
+        if key.starts_with(consts::SNAPSHOT_NEXT_KEY) {
+            let lane = match key[8..] {
+                [] => 0,
+                [a, b] => u16::from_be_bytes([a, b]),
+                _ => panic!("malformed next snapshot ID key {key:?}"),
+            };
+            next.insert(lane, parse_u64(&value));
+            continue;
+        }
+
+        assert!(
+            value.len() >= 16 && (value.len() - 16).is_multiple_of(consts::SNAPSHOT_FIELD_LEN),
+            "snapshot {key:?} has a malformed row"
+        );
+
+        let version = parse_u64(&value[..8]);
+        let parent = parse_u64(&value[8..16]);
+
+        snapshots.insert(parse_u64(&key), (version, parent));
+    }
+
+    let mut lanes = BTreeMap::<_, Vec<_>>::new();
+    for &id in snapshots.keys() {
+        lanes
+            .entry(id >> consts::SNAPSHOT_LANE_SHIFT)
+            .or_default()
+            .push(id);
+    }
+
+    for (lane, ids) in lanes {
+        let start = lane << consts::SNAPSHOT_LANE_SHIFT;
+        let next = next.get(&(lane as u16)).copied().unwrap_or(start);
+
+        assert!(
+            ids.iter().copied().eq(start..next),
+            "snapshots {ids:?} do not match the next snapshot ID {next} of lane {lane}"
+        );
+    }
+
+    let mut index = BTreeMap::new();
+
+    for (&id, &(version, parent)) in &snapshots {
+        if id == 0 {
+            assert_eq!((version, parent), (0, u64::MAX), "malformed root snapshot");
+            continue;
+        }
+
+        let parent_version = snapshots
+            .get(&parent)
+            .unwrap_or_else(|| panic!("snapshot {id} has a missing parent {parent}"))
+            .0;
+
+        assert_eq!(
+            version,
+            parent_version + 1,
+            "snapshot {id} has a wrong version"
+        );
+
+        // Row `i` of a snapshot points to its ancestor `2^i` versions back.
+        for i in (1..u64::BITS).take_while(|i| 1 << i <= version) {
+            let ancestor = (0..1u64 << i).fold(id, |s, _| snapshots[&s].1);
+
+            let mut key = id.to_be_bytes().to_vec();
+            key.extend_from_slice(&i.to_le_bytes());
+
+            index.insert(key, ancestor.to_be_bytes().to_vec());
+        }
+    }
+
+    let rows: BTreeMap<_, _> = collect(index_table.range(&[], None)?)?
+        .into_iter()
+        .collect();
+
+    assert_eq!(rows, index, "snapshot index does not match the snapshots");
+
+    Ok(())
+}
+
+/// Commit a snapshot on `parent`, or the root, failing the commit at each of
+/// its backend calls first and checking that every failure leaves no trace.
+///
+/// Returns the committed snapshots. A child is committed once for every kind
+/// of call, the root only once.
+fn commit_with_faults<B: Backend>(
+    db: &CrepeDB<FaultyBackend<B>>,
+    parent: Option<SnapshotId>,
+    value: u8,
+) -> crepedb_core::Result<Vec<SnapshotId>> {
+    let backend = db.backend();
+
+    let mut pending = vec![Call::OpenTable, Call::Set, Call::Commit];
+    let mut committed = Vec::new();
+
+    for skip in 0.. {
+        for call in pending.clone() {
+            for fault in [Fault::Error, Fault::Crash] {
+                let txn = db.write(parent.clone())?;
+
+                if parent.is_none() {
+                    txn.create_versioned_table(TABLE)?;
+                }
+
+                txn.open_table(TABLE)?.set(vec![0], vec![value])?;
+
+                backend.inject(call, skip, fault);
+                let res = txn.commit();
+
+                if backend.is_armed() {
+                    // The commit makes at most `skip` calls of this kind.
+                    backend.clear();
+                    committed.push(res?);
+
+                    if parent.is_none() {
+                        return Ok(committed);
+                    }
+
+                    pending.retain(|c| *c != call);
+                    break;
+                }
+
+                assert!(
+                    res.is_err(),
+                    "commit hit {fault:?} in {call:?} but succeeded"
+                );
+                assert_eq!(backend.is_crashed(), fault == Fault::Crash);
+
+                backend.restart();
+
+                if parent.is_some() {
+                    check_invariants(backend).map_err(Error::backend)?;
+                } else {
+                    assert!(db.get_snapshot_info(SnapshotId::root()).is_err());
+                }
+            }
+        }
+
+        if pending.is_empty() {
+            break;
+        }
+    }
+
+    Ok(committed)
+}
+
+/// A commit failing or crashing at any backend call leaves no trace.
+pub fn test_commit_faults<B: Backend>(backend: B) -> crepedb_core::Result<()> {
+    let db = CrepeDB::new(FaultyBackend::new(backend));
+
+    let mut snapshots = commit_with_faults(&db, None, 0)?
+        .into_iter()
+        .map(|s| (s, 0))
+        .collect::<Vec<_>>();
+
+    // A chain of forks, deep enough for several index rows per snapshot.
+    for value in 1..10 {
+        let parent = snapshots.last().unwrap().0.clone();
+
+        for snapshot in commit_with_faults(&db, Some(parent), value)? {
+            snapshots.push((snapshot, value));
+        }
+    }
+
+    check_invariants(db.backend()).map_err(Error::backend)?;
+
+    for (snapshot, value) in snapshots {
+        let txn = db.read(Some(snapshot))?;
+        let table = txn.open_table(TABLE)?;
+
+        assert_eq!(table.get(vec![0])?, Some(vec![value]));
+    }
+
+    Ok(())
+}
+
+/// Writes of a transaction crashing before its commit are lost and the
+/// database works after a restart.
+pub fn test_crash_during_writes<B: Backend>(backend: B) -> crepedb_core::Result<()> {
+    let db = CrepeDB::new(FaultyBackend::new(backend));
+
+    let txn = db.write(None)?;
+    txn.create_versioned_table(TABLE)?;
+    let root = txn.commit()?;
+
+    let txn = db.write(Some(root))?;
+    let mut table = txn.open_table(TABLE)?;
+    for i in 0..4 {
+        table.set(vec![i], vec![1])?;
+    }
+    drop(table);
+    let s1 = txn.commit()?;
+
+    let txn = db.write(Some(s1.clone()))?;
+    let mut table = txn.open_table(TABLE)?;
+
+    // Deleting from a versioned table writes a tombstone with `set`.
+    db.backend().inject(Call::Set, 2, Fault::Crash);
+
+    table.del(vec![0])?;
+    table.set(vec![1], vec![2])?;
+    assert!(table.set(vec![2], vec![2]).is_err());
+    assert!(db.backend().is_crashed());
+
+    // Everything fails until the restart.
+    assert!(table.set(vec![3], vec![2]).is_err());
+    drop(table);
+    assert!(txn.commit().is_err());
+    assert!(db.read(Some(s1.clone())).is_err());
+
+    db.backend().restart();
+    check_invariants(db.backend()).map_err(Error::backend)?;
+
+    let txn = db.read(Some(s1.clone()))?;
+    let table = txn.open_table(TABLE)?;
+    for i in 0..4 {
+        assert_eq!(table.get(vec![i])?, Some(vec![1]));
+    }
+
+    let txn = db.write(Some(s1))?;
+    txn.open_table(TABLE)?.set(vec![1], vec![3])?;
+    let s2 = txn.commit()?;
+
+    check_invariants(db.backend()).map_err(Error::backend)?;
+
+    let txn = db.read(Some(s2))?;
+    let table = txn.open_table(TABLE)?;
+    assert_eq!(table.get(vec![0])?, Some(vec![1]));
+    assert_eq!(table.get(vec![1])?, Some(vec![3]));
+
+    Ok(())
+}
+
+/// Failed reads return errors and do not affect later reads.
+pub fn test_read_faults<B: Backend>(backend: B) -> crepedb_core::Result<()> {
+    let db = CrepeDB::new(FaultyBackend::new(backend));
+
+    let txn = db.write(None)?;
+    txn.create_versioned_table(TABLE)?;
+    let root = txn.commit()?;
+
+    let txn = db.write(Some(root))?;
+    txn.open_table(TABLE)?.set(vec![1], vec![1])?;
+    let s1 = txn.commit()?;
+
+    let txn = db.read(Some(s1))?;
+
+    // Opening a table opens several internal tables, fail one of them.
+    db.backend().inject(Call::OpenTable, 1, Fault::Error);
+    assert!(txn.open_table(TABLE).is_err());
+    assert!(!db.backend().is_armed());
+
+    let table = txn.open_table(TABLE)?;
+
+    db.backend().inject(Call::RangeNext, 0, Fault::Error);
+    assert!(table.get(vec![1]).is_err());
+    assert_eq!(table.get(vec![1])?, Some(vec![1]));
+    assert_eq!(table.get(vec![2])?, None);
+
+    Ok(())
+}
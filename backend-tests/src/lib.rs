@@ -0,0 +1,164 @@
+//! # CrepeDB Backend Tests
+//!
+//! A conformance test suite for CrepeDB storage backends.
+//!
+//! Add this crate as a dev-dependency of a backend and instantiate the suite
+//! with [`backend_tests!`], passing an expression that creates a new, empty
+//! backend. The expression is evaluated once per test.
+//!
+//! ## Suites
+//!
+//! - [`suite`]: CrepeDB-level behaviour (snapshots, forks, versioned and basic tables)
+//! - [`raw`]: Semantics of the backend traits themselves (range bounds, empty
+//!   ranges, delete of missing keys, overwrites, table isolation, commit and
+//!   abort visibility)
+//! - [`model`]: Random snapshot trees checked against a reference model
+//! - [`faulty`]: Failures and crashes injected into every backend call of a
+//!   commit, checked against the invariants of the internal tables
+//!
+//! ## Example
+//!
+//! ```ignore
+//! #[cfg(test)]
+//! mod tests {
+//!     use crate::RedbDatabase;
+//!
+//!     crepedb_backend_tests::backend_tests!(RedbDatabase::memory().unwrap());
+//! }
+//! ```
+
+pub mod faulty;
+pub mod model;
+pub mod raw;
+
+#[doc(hidden)]
+pub use proptest;
+
+/// CrepeDB-level tests, run against a [`CrepeDB`](crepedb_core::CrepeDB) on top of the backend.
+pub mod suite {
+    pub use crepedb_core::{
+        export_tests::*, lane_tests::*, merkle_tests::*, read_tests::*, rebuild_tests::*,
+        savepoint_tests::*, staged_tests::*, state_hash_tests::*, tests::test_db_10,
+        typed_tests::*, verify_tests::*,
+    };
+}
+
+/// Initialize logging for tests, ignoring repeated calls.
+pub fn init_logger() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+/// Instantiate the full conformance suite for a backend.
+///
+/// Expands to `suite`, `raw`, `model` and `faulty` modules holding the `#[test]`
+/// functions. The modules import everything from the enclosing module, so the
+/// backend expression can use names imported there.
+///
+/// # Example
+///
+/// ```ignore
+/// crepedb_backend_tests::backend_tests!(SledDatabase::memory().unwrap());
+/// ```
+#[macro_export]
+macro_rules! backend_tests {
+    ($backend:expr) => {
+        mod suite {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $crate::backend_tests!(@tests suite, $backend;
+                test_db_10,
+                test_read,
+                test_snapshot_isolation,
+                test_multiple_keys,
+                test_error_handling,
+                test_multiple_tables,
+                test_basic_table_type,
+                test_edge_cases,
+                test_version_chain,
+                test_delete_operations,
+                test_root_snapshot,
+                test_transaction_lifecycle,
+                test_mixed_operations,
+                test_parent_child_visibility,
+                test_complex_branching,
+                test_overwrite_in_chain,
+                test_prefix_keys,
+                test_range,
+                test_get_ref,
+                test_verify,
+                test_rebuild_index,
+                test_lanes,
+                test_staged_write,
+                test_savepoints,
+                test_typed_tables,
+                test_state_root,
+                test_no_state_root,
+                test_proofs,
+                test_state_hash,
+                test_export,
+                test_delta,
+            );
+        }
+
+        mod raw {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $crate::backend_tests!(@tests raw, $backend;
+                test_range_bounds,
+                test_empty_ranges,
+                test_delete_missing,
+                test_overwrite,
+                test_table_isolation,
+                test_commit_visibility,
+                test_abort_visibility,
+            );
+        }
+
+        mod faulty {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $crate::backend_tests!(@tests faulty, $backend;
+                test_commit_faults,
+                test_crash_during_writes,
+                test_read_faults,
+            );
+        }
+
+        mod model {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $crate::proptest::proptest! {
+                #![proptest_config($crate::model::config())]
+
+                #[test]
+                fn test_model(plan in $crate::model::plan()) {
+                    $crate::init_logger();
+
+                    $crate::model::check(&plan, $backend).unwrap();
+                }
+            }
+        }
+    };
+
+    (@tests $module:ident, $backend:expr; $($name:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                $crate::init_logger();
+
+                $crate::$module::$name($backend).unwrap();
+            }
+        )*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crepedb_core::memory::MemoryBackend;
+
+    crate::backend_tests!(MemoryBackend::new());
+}
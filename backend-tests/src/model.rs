@@ -0,0 +1,202 @@
+//! Model checking of versioned tables.
+//!
+//! [`plan`] generates random snapshot trees where every snapshot applies random
+//! `set` and `del` operations on top of a random earlier snapshot. [`check`]
+//! runs a plan against CrepeDB and compares every key at every snapshot to a
+//! reference model, which is simply a map cloned per snapshot, and checks range
+//! reads between touched keys and scans from them as well.
+//!
+//! Keys are drawn from a tiny alphabet and some keys end with a valid version
+//! and snapshot ID, so different keys often share prefixes with each other and
+//! with the internal key layout of versioned tables.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crepedb_core::{
+    backend::{Backend, BackendError, WriteTxn as BackendWriteTxn},
+    types::Bytes,
+    CrepeDB, Result, WriteTxn,
+};
+use proptest::{collection::vec, prelude::*, test_runner::Config};
+
+const TABLE: &str = "model";
+
+/// A single change to the versioned table.
+#[derive(Debug, Clone)]
+pub enum Op {
+    /// Set a key to a value.
+    Set(Bytes, Bytes),
+    /// Delete a key.
+    Del(Bytes),
+}
+
+/// A snapshot to create.
+#[derive(Debug, Clone)]
+pub struct Step {
+    /// The parent snapshot, as an index into the snapshots created so far,
+    /// modulo their number.
+    pub parent: usize,
+    /// The changes made in the snapshot.
+    pub ops: Vec<Op>,
+}
+
+/// A snapshot tree to build.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// The changes made in the root snapshot.
+    pub root: Vec<Op>,
+    /// The snapshots created after the root, in order.
+    pub steps: Vec<Step>,
+}
+
+/// The proptest configuration used by [`backend_tests!`](crate::backend_tests).
+///
+/// Every case creates a new backend, so fewer cases than proptest's default
+/// are run.
+pub fn config() -> Config {
+    Config::with_cases(64)
+}
+
+fn key() -> impl Strategy<Value = Bytes> {
+    let short = vec(0u8..3, 0..3);
+
+    prop_oneof![
+        3 => short.clone(),
+        1 => (short, 0u64..4, 0u64..8).prop_map(|(mut key, version, snapshot)| {
+            key.extend_from_slice(&version.to_be_bytes());
+            key.extend_from_slice(&snapshot.to_be_bytes());
+            key
+        }),
+    ]
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => (key(), vec(any::<u8>(), 0..3)).prop_map(|(key, value)| Op::Set(key, value)),
+        1 => key().prop_map(Op::Del),
+    ]
+}
+
+fn ops() -> impl Strategy<Value = Vec<Op>> {
+    vec(op(), 0..6)
+}
+
+/// Generate a random snapshot tree.
+pub fn plan() -> impl Strategy<Value = Plan> {
+    let step = (any::<usize>(), ops()).prop_map(|(parent, ops)| Step { parent, ops });
+
+    (ops(), vec(step, 0..24)).prop_map(|(root, steps)| Plan { root, steps })
+}
+
+/// Apply `ops` to the model and record the touched keys.
+fn apply(ops: &[Op], model: &mut BTreeMap<Bytes, Bytes>, keys: &mut BTreeSet<Bytes>) {
+    for op in ops {
+        match op {
+            Op::Set(key, value) => {
+                model.insert(key.clone(), value.clone());
+                keys.insert(key.clone());
+            }
+            Op::Del(key) => {
+                model.remove(key);
+                keys.insert(key.clone());
+            }
+        }
+    }
+}
+
+/// Apply `ops` to the table in a write transaction.
+fn write<T, E>(txn: &WriteTxn<T, E>, ops: &[Op]) -> Result<()>
+where
+    T: BackendWriteTxn<E>,
+    E: BackendError,
+{
+    let mut table = txn.open_table(TABLE)?;
+
+    for op in ops {
+        match op {
+            Op::Set(key, value) => table.set(key.clone(), value.clone())?,
+            Op::Del(key) => table.del(key.clone())?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `plan` against CrepeDB on `backend` and compare every read with the model.
+///
+/// # Panics
+///
+/// Panics if a read returns something else than the model.
+pub fn check(plan: &Plan, backend: impl Backend) -> Result<()> {
+    let db = CrepeDB::new(backend);
+
+    let mut keys = BTreeSet::new();
+    let mut models = Vec::new();
+    let mut snapshots = Vec::new();
+
+    let txn = db.write(None)?;
+    txn.create_versioned_table(TABLE)?;
+    write(&txn, &plan.root)?;
+    snapshots.push(txn.commit()?);
+
+    let mut model = BTreeMap::new();
+    apply(&plan.root, &mut model, &mut keys);
+    models.push(model);
+
+    for step in &plan.steps {
+        let parent = step.parent % snapshots.len();
+
+        let txn = db.write(Some(snapshots[parent].clone()))?;
+        write(&txn, &step.ops)?;
+        snapshots.push(txn.commit()?);
+
+        let mut model = models[parent].clone();
+        apply(&step.ops, &mut model, &mut keys);
+        models.push(model);
+    }
+
+    for (i, (snapshot, model)) in snapshots.iter().zip(&models).enumerate() {
+        let txn = db.read(Some(snapshot.clone()))?;
+        let table = txn.open_table(TABLE)?;
+
+        for key in &keys {
+            assert_eq!(
+                table.get(key.clone())?.as_ref(),
+                model.get(key),
+                "key {key:?} at snapshot {i} ({snapshot:?})"
+            );
+        }
+
+        // Every key is below `[3]`, the touched keys are used as bounds.
+        let bounds: Vec<_> = keys.iter().cloned().chain([vec![3]]).collect();
+
+        for (begin, end) in bounds.iter().zip(bounds.iter().rev()) {
+            let expected: Vec<_> = model
+                .range(begin.clone()..)
+                .take_while(|(key, _)| *key < end)
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+
+            assert_eq!(
+                table.range(begin.clone(), end.clone())?,
+                expected,
+                "range {begin:?}..{end:?} at snapshot {i} ({snapshot:?})"
+            );
+        }
+
+        for begin in &bounds {
+            let expected: Vec<_> = model
+                .range(begin.clone()..)
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+
+            assert_eq!(
+                table.scan(begin, None)?.collect::<Result<Vec<_>>>()?,
+                expected,
+                "scan {begin:?}.. at snapshot {i} ({snapshot:?})"
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,370 @@
+//! Tests of the backend traits.
+//!
+//! These tests use a backend directly, without CrepeDB on top, and check the
+//! behaviour CrepeDB relies on. Each test takes a new, empty backend, panics if
+//! the backend misbehaves and returns backend errors unchanged.
+
+use crepedb_core::{
+    backend::{Backend, Range, ReadTable, ReadTxn, WriteTable, WriteTxn},
+    types::Bytes,
+};
+
+type Entries = Vec<(Bytes, Bytes)>;
+
+/// Drain a range into a vector.
+pub(crate) fn collect<E>(mut range: impl Range<E>) -> Result<Entries, E> {
+    let mut entries = Vec::new();
+
+    while let Some(entry) = range.back()? {
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+fn entries(items: &[(&[u8], &[u8])]) -> Entries {
+    items
+        .iter()
+        .map(|(k, v)| (k.to_vec(), v.to_vec()))
+        .collect()
+}
+
+/// Write `items` to `table` in a new write transaction and commit it.
+fn fill<B: Backend>(backend: &B, table: &str, items: &[(&[u8], &[u8])]) -> Result<(), B::Error> {
+    let txn = backend.write_txn()?;
+
+    {
+        let mut t = txn.open_table(table)?;
+
+        for (key, value) in items {
+            t.set(key, value)?;
+        }
+    }
+
+    txn.commit()
+}
+
+/// Read `[begin, end)` of `table` in a new read transaction.
+fn read_range<B: Backend>(
+    backend: &B,
+    table: &str,
+    begin: &[u8],
+    end: &[u8],
+) -> Result<Entries, B::Error> {
+    let txn = backend.read_txn()?;
+    let t = txn.open_table(table)?;
+
+    let entries = collect(t.range(begin, Some(end))?)?;
+    Ok(entries)
+}
+
+/// Read `[begin, ..)` of `table` in a new read transaction.
+fn read_range_from<B: Backend>(
+    backend: &B,
+    table: &str,
+    begin: &[u8],
+) -> Result<Entries, B::Error> {
+    let txn = backend.read_txn()?;
+    let t = txn.open_table(table)?;
+
+    let entries = collect(t.range(begin, None)?)?;
+    Ok(entries)
+}
+
+/// Read `key` of `table` in a new read transaction.
+fn read_get<B: Backend>(backend: &B, table: &str, key: &[u8]) -> Result<Option<Bytes>, B::Error> {
+    let txn = backend.read_txn()?;
+    let t = txn.open_table(table)?;
+
+    let value = t.get(key)?;
+    Ok(value)
+}
+
+/// Ranges include `begin`, exclude `end` and return keys in lexicographic order.
+pub fn test_range_bounds<B: Backend>(backend: B) -> Result<(), B::Error> {
+    fill(
+        &backend,
+        "t",
+        &[
+            (&[3], b"3"),
+            (&[1, 255], b"1ff"),
+            (&[255], b"ff"),
+            (&[1], b"1"),
+            (&[2, 0], b"20"),
+            (&[255, 255], b"ffff"),
+            (&[1, 0], b"10"),
+            (&[2], b"2"),
+        ],
+    )?;
+
+    assert_eq!(
+        read_range(&backend, "t", &[], &[255, 255, 255])?,
+        entries(&[
+            (&[1], b"1"),
+            (&[1, 0], b"10"),
+            (&[1, 255], b"1ff"),
+            (&[2], b"2"),
+            (&[2, 0], b"20"),
+            (&[3], b"3"),
+            (&[255], b"ff"),
+            (&[255, 255], b"ffff"),
+        ])
+    );
+
+    assert_eq!(
+        read_range(&backend, "t", &[1], &[2])?,
+        entries(&[(&[1], b"1"), (&[1, 0], b"10"), (&[1, 255], b"1ff")])
+    );
+
+    assert_eq!(
+        read_range(&backend, "t", &[1, 0], &[2, 0])?,
+        entries(&[(&[1, 0], b"10"), (&[1, 255], b"1ff"), (&[2], b"2")])
+    );
+
+    assert_eq!(
+        read_range(&backend, "t", &[2, 1], &[255])?,
+        entries(&[(&[3], b"3")])
+    );
+
+    assert_eq!(
+        read_range(&backend, "t", &[255], &[255, 255])?,
+        entries(&[(&[255], b"ff")])
+    );
+
+    // Ranges without an end include the largest keys.
+    assert_eq!(
+        read_range_from(&backend, "t", &[2, 1])?,
+        entries(&[(&[3], b"3"), (&[255], b"ff"), (&[255, 255], b"ffff")])
+    );
+
+    // The same bounds in a write transaction.
+    let txn = backend.write_txn()?;
+
+    {
+        let t = txn.open_table("t")?;
+
+        assert_eq!(
+            collect(t.range(&[1], Some(&[2]))?)?,
+            entries(&[(&[1], b"1"), (&[1, 0], b"10"), (&[1, 255], b"1ff")])
+        );
+
+        assert_eq!(
+            collect(t.range(&[255], None)?)?,
+            entries(&[(&[255], b"ff"), (&[255, 255], b"ffff")])
+        );
+    }
+
+    Ok(())
+}
+
+/// Ranges without keys, including empty and inverted bounds, yield nothing.
+pub fn test_empty_ranges<B: Backend>(backend: B) -> Result<(), B::Error> {
+    fill(&backend, "t", &[(&[1], b"1"), (&[2], b"2"), (&[3], b"3")])?;
+    fill(&backend, "empty", &[])?;
+
+    assert_eq!(read_range(&backend, "t", &[2], &[2])?, vec![]);
+    assert_eq!(read_range(&backend, "t", &[3], &[1])?, vec![]);
+    assert_eq!(read_range(&backend, "t", &[4], &[9])?, vec![]);
+    assert_eq!(read_range(&backend, "t", &[1, 5], &[2])?, vec![]);
+
+    assert_eq!(read_range(&backend, "empty", &[], &[255])?, vec![]);
+    assert_eq!(read_range_from(&backend, "t", &[4])?, vec![]);
+    assert_eq!(read_range_from(&backend, "empty", &[])?, vec![]);
+    assert_eq!(read_get(&backend, "empty", &[1])?, None);
+
+    let txn = backend.write_txn()?;
+
+    {
+        let t = txn.open_table("t")?;
+
+        assert_eq!(collect(t.range(&[2], Some(&[2]))?)?, vec![]);
+        assert_eq!(collect(t.range(&[3], Some(&[1]))?)?, vec![]);
+
+        let empty = txn.open_table("empty")?;
+
+        assert_eq!(collect(empty.range(&[], Some(&[255]))?)?, vec![]);
+    }
+
+    Ok(())
+}
+
+/// Deleting a missing key succeeds and changes nothing.
+pub fn test_delete_missing<B: Backend>(backend: B) -> Result<(), B::Error> {
+    fill(&backend, "t", &[(&[1], b"1")])?;
+
+    let txn = backend.write_txn()?;
+
+    {
+        let mut t = txn.open_table("t")?;
+
+        t.del(&[2])?;
+        t.del(&[1])?;
+        t.del(&[1])?;
+
+        assert_eq!(t.get(&[1])?, None);
+        assert_eq!(t.get(&[2])?, None);
+
+        let mut fresh = txn.open_table("fresh")?;
+        fresh.del(&[1])?;
+    }
+
+    txn.commit()?;
+
+    assert_eq!(read_get(&backend, "t", &[1])?, None);
+    assert_eq!(read_get(&backend, "t", &[2])?, None);
+    assert_eq!(read_range(&backend, "t", &[], &[255])?, vec![]);
+
+    Ok(())
+}
+
+/// Setting an existing key replaces its value, an empty value is not a missing key.
+pub fn test_overwrite<B: Backend>(backend: B) -> Result<(), B::Error> {
+    let txn = backend.write_txn()?;
+
+    {
+        let mut t = txn.open_table("t")?;
+
+        t.set(&[1], b"a")?;
+        t.set(&[1], b"b")?;
+
+        assert_eq!(t.get(&[1])?, Some(b"b".to_vec()));
+        assert_eq!(
+            collect(t.range(&[], Some(&[255]))?)?,
+            entries(&[(&[1], b"b")])
+        );
+    }
+
+    txn.commit()?;
+
+    assert_eq!(read_get(&backend, "t", &[1])?, Some(b"b".to_vec()));
+
+    fill(&backend, "t", &[(&[1], b"c"), (&[1], b"")])?;
+
+    assert_eq!(read_get(&backend, "t", &[1])?, Some(vec![]));
+    assert_eq!(
+        read_range(&backend, "t", &[], &[255])?,
+        entries(&[(&[1], b"")])
+    );
+
+    Ok(())
+}
+
+/// Tables never see each other's keys, even when names and keys share prefixes.
+pub fn test_table_isolation<B: Backend>(backend: B) -> Result<(), B::Error> {
+    let txn = backend.write_txn()?;
+
+    {
+        let mut a = txn.open_table("a")?;
+        a.set(b"b:1", b"a")?;
+        a.set(&[9], b"a")?;
+
+        let mut ab = txn.open_table("a:b")?;
+        ab.set(b"1", b"a:b")?;
+
+        let mut b = txn.open_table("b")?;
+        b.set(&[9], b"b")?;
+    }
+
+    txn.commit()?;
+
+    assert_eq!(read_get(&backend, "a", b"1")?, None);
+    assert_eq!(read_get(&backend, "a:b", b"b:1")?, None);
+    assert_eq!(read_get(&backend, "a:b", b"1")?, Some(b"a:b".to_vec()));
+
+    assert_eq!(
+        read_range(&backend, "a", &[], &[255])?,
+        entries(&[(&[9], b"a"), (b"b:1", b"a")])
+    );
+    assert_eq!(
+        read_range(&backend, "a:b", &[], &[255])?,
+        entries(&[(b"1", b"a:b")])
+    );
+
+    let txn = backend.write_txn()?;
+    txn.open_table("a")?.del(&[9])?;
+    txn.commit()?;
+
+    assert_eq!(read_get(&backend, "a", &[9])?, None);
+    assert_eq!(read_get(&backend, "b", &[9])?, Some(b"b".to_vec()));
+
+    Ok(())
+}
+
+/// Changes are visible in their own transaction at once and to others after commit.
+pub fn test_commit_visibility<B: Backend>(backend: B) -> Result<(), B::Error> {
+    fill(&backend, "t", &[(&[1], b"1")])?;
+    fill(&backend, "u", &[])?;
+
+    let txn = backend.write_txn()?;
+
+    {
+        let mut t = txn.open_table("t")?;
+        t.set(&[2], b"2")?;
+        t.del(&[1])?;
+
+        assert_eq!(t.get(&[1])?, None);
+        assert_eq!(t.get(&[2])?, Some(b"2".to_vec()));
+        assert_eq!(
+            collect(t.range(&[], Some(&[255]))?)?,
+            entries(&[(&[2], b"2")])
+        );
+
+        let mut u = txn.open_table("u")?;
+        u.set(&[1], b"u")?;
+    }
+
+    // Uncommitted changes are invisible to other transactions.
+    assert_eq!(
+        read_range(&backend, "t", &[], &[255])?,
+        entries(&[(&[1], b"1")])
+    );
+    assert_eq!(read_get(&backend, "u", &[1])?, None);
+
+    txn.commit()?;
+
+    assert_eq!(
+        read_range(&backend, "t", &[], &[255])?,
+        entries(&[(&[2], b"2")])
+    );
+    assert_eq!(read_get(&backend, "u", &[1])?, Some(b"u".to_vec()));
+
+    Ok(())
+}
+
+/// Dropping a write transaction without committing discards its changes.
+pub fn test_abort_visibility<B: Backend>(backend: B) -> Result<(), B::Error> {
+    fill(&backend, "t", &[(&[1], b"1")])?;
+
+    {
+        let txn = backend.write_txn()?;
+
+        let mut t = txn.open_table("t")?;
+        t.set(&[2], b"2")?;
+        t.del(&[1])?;
+
+        let mut new = txn.open_table("new")?;
+        new.set(&[1], b"new")?;
+    }
+
+    assert_eq!(
+        read_range(&backend, "t", &[], &[255])?,
+        entries(&[(&[1], b"1")])
+    );
+
+    // The backend accepts new writes after the abort.
+    fill(&backend, "t", &[(&[3], b"3")])?;
+
+    assert_eq!(
+        read_range(&backend, "t", &[], &[255])?,
+        entries(&[(&[1], b"1"), (&[3], b"3")])
+    );
+
+    let txn = backend.write_txn()?;
+
+    {
+        let new = txn.open_table("new")?;
+        assert_eq!(new.get(&[1])?, None);
+    }
+
+    Ok(())
+}
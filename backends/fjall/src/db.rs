@@ -0,0 +1,150 @@
+use std::{
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crepedb_core::backend::Backend;
+use fjall::{DatabaseBuilder, KeyspaceCreateOptions, OptimisticTxDatabase, OptimisticTxKeyspace};
+
+use crate::{FjallError, FjallReadTxn, FjallWriteTxn};
+
+/// A CrepeDB backend implementation using fjall.
+///
+/// This struct wraps a fjall `OptimisticTxDatabase` and implements the CrepeDB
+/// `Backend` trait, allowing fjall to be used as the storage engine for CrepeDB.
+///
+/// Every CrepeDB table is mapped to a fjall keyspace with the same name.
+/// Keyspaces are created the first time a table is opened in a write transaction.
+pub struct FjallDatabase {
+    inner: OptimisticTxDatabase,
+}
+
+impl FjallDatabase {
+    /// Open or create a persistent fjall database at the specified path.
+    ///
+    /// If the database doesn't exist, it will be created. If it exists, it will be opened.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory path where the database should be stored
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let db = FjallDatabase::open_or_create("mydb")?;
+    /// ```
+    pub fn open_or_create(path: impl AsRef<Path>) -> Result<Self, FjallError> {
+        Self::open_with_builder(OptimisticTxDatabase::builder(path))
+    }
+
+    /// Open or create a fjall database configured by a builder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let builder = OptimisticTxDatabase::builder("mydb").cache_size(256 << 20);
+    /// let db = FjallDatabase::open_with_builder(builder)?;
+    /// ```
+    pub fn open_with_builder(
+        builder: DatabaseBuilder<OptimisticTxDatabase>,
+    ) -> Result<Self, FjallError> {
+        let db = builder.open()?;
+        Ok(Self { inner: db })
+    }
+
+    /// Create a temporary fjall database.
+    ///
+    /// The database is stored in a new directory below the system temporary
+    /// directory, which is removed when the database is dropped.
+    /// This is useful for testing or temporary storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let db = FjallDatabase::temporary()?;
+    /// ```
+    pub fn temporary() -> Result<Self, FjallError> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        let path = std::env::temp_dir().join(format!(
+            "crepedb-fjall-{}-{}-{}",
+            std::process::id(),
+            nanos,
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        Self::open_with_builder(OptimisticTxDatabase::builder(path).temporary(true))
+    }
+
+    /// Get a reference to the underlying fjall database.
+    pub fn inner(&self) -> &OptimisticTxDatabase {
+        &self.inner
+    }
+
+    /// Consume the database and return the underlying fjall database.
+    pub fn into_inner(self) -> OptimisticTxDatabase {
+        self.inner
+    }
+}
+
+/// Open the keyspace of a table, creating it if `create` is set.
+pub(crate) fn open_keyspace(
+    db: &OptimisticTxDatabase,
+    name: &str,
+    create: bool,
+) -> Result<Option<OptimisticTxKeyspace>, FjallError> {
+    // fjall panics on invalid keyspace names.
+    if name.is_empty() || name.len() > u8::MAX as usize {
+        return Err(FjallError::InvalidTableName(name.to_string()));
+    }
+
+    if !create && !db.keyspace_exists(name) {
+        return Ok(None);
+    }
+
+    Ok(Some(db.keyspace(name, KeyspaceCreateOptions::default)?))
+}
+
+impl Backend for FjallDatabase {
+    type Error = FjallError;
+
+    type ReadTxn<'a> = FjallReadTxn;
+
+    type WriteTxn<'a> = FjallWriteTxn;
+
+    fn is_conflict(error: &Self::Error) -> bool {
+        matches!(error, FjallError::Conflict)
+    }
+
+    fn read_txn(&self) -> Result<Self::ReadTxn<'_>, Self::Error> {
+        Ok(FjallReadTxn {
+            snapshot: self.inner.read_tx(),
+            db: self.inner.clone(),
+        })
+    }
+
+    fn write_txn(&self) -> Result<Self::WriteTxn<'_>, Self::Error> {
+        Ok(FjallWriteTxn {
+            inner: self.inner.write_tx()?.into(),
+            db: self.inner.clone(),
+        })
+    }
+}
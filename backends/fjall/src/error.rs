@@ -0,0 +1,32 @@
+use std::fmt::{self, Display};
+
+/// Errors returned by the fjall backend.
+#[derive(Debug)]
+pub enum FjallError {
+    /// Error returned by fjall.
+    Fjall(fjall::Error),
+    /// The write transaction conflicts with a transaction committed after it started.
+    Conflict,
+    /// The table name cannot be used as a keyspace name.
+    ///
+    /// Keyspace names must be between 1 and 255 bytes long.
+    InvalidTableName(String),
+}
+
+impl Display for FjallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fjall(e) => write!(f, "{e}"),
+            Self::Conflict => write!(f, "Transaction conflict"),
+            Self::InvalidTableName(name) => write!(f, "Invalid table name: {name:?}"),
+        }
+    }
+}
+
+impl std::error::Error for FjallError {}
+
+impl From<fjall::Error> for FjallError {
+    fn from(e: fjall::Error) -> Self {
+        Self::Fjall(e)
+    }
+}
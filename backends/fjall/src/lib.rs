@@ -0,0 +1,165 @@
+//! # CrepeDB Fjall Backend
+//!
+//! This crate provides a [fjall](https://github.com/fjall-rs/fjall) backend implementation for CrepeDB.
+//!
+//! Fjall is a pure-Rust, LSM-tree based embedded key-value storage engine.
+//! This backend allows CrepeDB to use fjall as its underlying storage engine.
+//!
+//! ## Features
+//!
+//! - **ACID Transactions**: Full transactional support via fjall's optimistic transactional database
+//! - **Pure Rust**: No C or C++ toolchain required
+//! - **Keyspace per Table**: Each table is stored in its own fjall keyspace
+//! - **Temporary Mode**: Support for temporary databases removed on drop
+//!
+//! ## Example
+//!
+//! ```ignore
+//! use crepedb::{CrepeDB, SnapshotId, TableType};
+//! use crepedb_fjall::FjallDatabase;
+//!
+//! // Open a persistent database
+//! let backend = FjallDatabase::open_or_create("mydb")?;
+//! let db = CrepeDB::new(backend);
+//!
+//! // Or create a temporary database
+//! let backend = FjallDatabase::temporary()?;
+//! let db = CrepeDB::new(backend);
+//! ```
+
+mod error;
+pub use error::*;
+
+mod db;
+pub use db::*;
+
+mod read;
+pub use read::*;
+
+mod write;
+pub use write::*;
+
+mod table;
+pub use table::*;
+
+mod range;
+pub use range::*;
+
+#[cfg(test)]
+mod tests {
+    use crate::FjallDatabase;
+
+    crepedb_backend_tests::backend_tests!(FjallDatabase::temporary().unwrap());
+
+    #[test]
+    fn test_conflict() {
+        use crepedb_core::backend::{Backend, ReadTable, ReadTxn, WriteTable, WriteTxn};
+
+        use crate::FjallError;
+
+        let backend = FjallDatabase::temporary().unwrap();
+
+        let a = backend.write_txn().unwrap();
+        let b = backend.write_txn().unwrap();
+
+        a.open_table("t").unwrap().set(&[1], &[1]).unwrap();
+
+        // Only keys read by a transaction are checked for conflicts.
+        let mut t = b.open_table("t").unwrap();
+        assert_eq!(t.get(&[1]).unwrap(), None);
+        t.set(&[1], &[2]).unwrap();
+        drop(t);
+
+        a.commit().unwrap();
+        assert!(matches!(b.commit(), Err(FjallError::Conflict)));
+
+        let txn = backend.read_txn().unwrap();
+        let t = txn.open_table("t").unwrap();
+        assert_eq!(t.get(&[1]).unwrap(), Some(vec![1]));
+    }
+
+    #[test]
+    fn test_concurrent_forks() {
+        use crepedb_core::CrepeDB;
+
+        let db = CrepeDB::new(FjallDatabase::temporary().unwrap());
+
+        let txn = db.write(None).unwrap();
+        txn.create_versioned_table("t").unwrap();
+        let root = txn.commit().unwrap();
+
+        // Forks in different lanes write no common row.
+        let a = db.write_in_lane(Some(root.clone()), 1).unwrap();
+        let b = db.write_in_lane(Some(root.clone()), 2).unwrap();
+        a.open_table("t").unwrap().set(vec![1], vec![1]).unwrap();
+        b.open_table("t").unwrap().set(vec![1], vec![2]).unwrap();
+        let a = a.commit().unwrap();
+        let b = b.commit().unwrap();
+
+        for (snapshot, value) in [(a, 1), (b, 2)] {
+            let txn = db.read(Some(snapshot)).unwrap();
+            let table = txn.open_table("t").unwrap();
+            assert_eq!(table.get(vec![1]).unwrap(), Some(vec![value]));
+        }
+
+        // Forks in the same lane allocate the same snapshot ID.
+        let a = db.write_in_lane(Some(root.clone()), 1).unwrap();
+        let b = db.write_in_lane(Some(root.clone()), 1).unwrap();
+        a.commit().unwrap();
+        let e = b.commit().unwrap_err();
+        assert!(db.is_conflict(&e));
+
+        // Writers sharing a lane retry until every snapshot is committed.
+        let snapshots = std::thread::scope(|s| {
+            let writers: Vec<_> = (0..4u8)
+                .map(|i| {
+                    let db = &db;
+                    let root = root.clone();
+
+                    s.spawn(move || {
+                        (0..8u8)
+                            .map(|j| {
+                                db.write_with_retry(root.clone(), 3 + (i % 2) as u16, |txn| {
+                                    txn.open_table("t")?.set(vec![i], vec![j])
+                                })
+                                .unwrap()
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            writers
+                .into_iter()
+                .map(|w| w.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        for (i, snapshots) in snapshots.into_iter().enumerate() {
+            for (j, snapshot) in snapshots.into_iter().enumerate() {
+                let txn = db.read(Some(snapshot)).unwrap();
+                let table = txn.open_table("t").unwrap();
+                assert_eq!(table.get(vec![i as u8]).unwrap(), Some(vec![j as u8]));
+            }
+        }
+
+        let report = db.verify().unwrap();
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.snapshots, 36);
+    }
+
+    #[test]
+    fn test_invalid_table_name() {
+        use crepedb_core::backend::{Backend, WriteTxn};
+
+        use crate::FjallError;
+
+        let backend = FjallDatabase::temporary().unwrap();
+
+        let txn = backend.write_txn().unwrap();
+        assert!(matches!(
+            txn.open_table(""),
+            Err(FjallError::InvalidTableName(_))
+        ));
+    }
+}
@@ -0,0 +1,23 @@
+use crepedb_core::types::Bytes;
+use fjall::Iter;
+
+use crate::FjallError;
+
+/// A range iterator wrapper for fjall.
+///
+/// Implements the CrepeDB `Range` trait for fjall's range iterator.
+pub struct FjallRange {
+    pub(crate) inner: Option<Iter>,
+}
+
+impl crepedb_core::backend::Range<FjallError> for FjallRange {
+    fn back(&mut self) -> Result<Option<(Bytes, Bytes)>, FjallError> {
+        let Some(guard) = self.inner.as_mut().and_then(|iter| iter.next()) else {
+            return Ok(None);
+        };
+
+        let (key, value) = guard.into_inner()?;
+
+        Ok(Some((key.to_vec(), value.to_vec())))
+    }
+}
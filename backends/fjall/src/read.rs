@@ -0,0 +1,25 @@
+use crepedb_core::backend::ReadTxn;
+use fjall::{OptimisticTxDatabase, Snapshot};
+
+use crate::{db::open_keyspace, FjallError, FjallReadTable};
+
+/// A read transaction wrapper for fjall.
+///
+/// Implements the CrepeDB `ReadTxn` trait, providing read-only access to tables
+/// through a fjall snapshot.
+pub struct FjallReadTxn {
+    pub(crate) snapshot: Snapshot,
+    pub(crate) db: OptimisticTxDatabase,
+}
+
+impl ReadTxn<FjallError> for FjallReadTxn {
+    type Table<'a> = FjallReadTable<'a>;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, FjallError> {
+        Ok(FjallReadTable {
+            snapshot: &self.snapshot,
+            keyspace: open_keyspace(&self.db, table, false)?,
+            name: table.to_string(),
+        })
+    }
+}
@@ -0,0 +1,135 @@
+use std::{cell::RefCell, ops::Bound};
+
+use crepedb_core::{
+    backend::{ReadTable, WriteTable},
+    types::Bytes,
+};
+use fjall::{OptimisticTxKeyspace, OptimisticWriteTx, Readable, Snapshot, UserValue};
+
+use crate::{FjallError, FjallRange};
+
+/// A read-only table wrapper for fjall.
+///
+/// Implements the CrepeDB `ReadTable` trait. A table without keyspace is read
+/// as an empty table.
+pub struct FjallReadTable<'a> {
+    pub(crate) snapshot: &'a Snapshot,
+    pub(crate) keyspace: Option<OptimisticTxKeyspace>,
+    pub(crate) name: String,
+}
+
+impl<'a> ReadTable<FjallError> for FjallReadTable<'a> {
+    type Range<'c>
+        = FjallRange
+    where
+        Self: 'c;
+
+    type Value<'c>
+        = UserValue
+    where
+        Self: 'c;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, FjallError> {
+        let Some(keyspace) = &self.keyspace else {
+            return Ok(None);
+        };
+
+        Ok(self.snapshot.get(keyspace, key)?.map(|v| v.to_vec()))
+    }
+
+    // Values are reference counted slices shared with fjall's cache.
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, FjallError> {
+        let Some(keyspace) = &self.keyspace else {
+            return Ok(None);
+        };
+
+        Ok(self.snapshot.get(keyspace, key)?)
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, FjallError> {
+        let inner = match &self.keyspace {
+            Some(keyspace) if !empty(begin, end) => Some(
+                self.snapshot
+                    .range::<&[u8], _>(keyspace, bounds(begin, end)),
+            ),
+            _ => None,
+        };
+
+        Ok(FjallRange { inner })
+    }
+}
+
+/// A writable table wrapper for fjall.
+///
+/// Implements both the CrepeDB `ReadTable` and `WriteTable` traits. Reads see
+/// the uncommitted changes of the write transaction.
+pub struct FjallWriteTable<'a> {
+    pub(crate) txn: &'a RefCell<OptimisticWriteTx>,
+    pub(crate) keyspace: OptimisticTxKeyspace,
+    pub(crate) name: String,
+}
+
+impl<'a> ReadTable<FjallError> for FjallWriteTable<'a> {
+    type Range<'c>
+        = FjallRange
+    where
+        Self: 'c;
+
+    type Value<'c>
+        = UserValue
+    where
+        Self: 'c;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, FjallError> {
+        let value = self.txn.borrow().get(&self.keyspace, key)?;
+        Ok(value.map(|v| v.to_vec()))
+    }
+
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, FjallError> {
+        Ok(self.txn.borrow().get(&self.keyspace, key)?)
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, FjallError> {
+        let inner = (!empty(begin, end)).then(|| {
+            self.txn
+                .borrow()
+                .range::<&[u8], _>(&self.keyspace, bounds(begin, end))
+        });
+
+        Ok(FjallRange { inner })
+    }
+}
+
+impl<'a> WriteTable<FjallError> for FjallWriteTable<'a> {
+    fn set(&mut self, key: &[u8], value: &[u8]) -> Result<(), FjallError> {
+        self.txn.borrow_mut().insert(&self.keyspace, key, value);
+        Ok(())
+    }
+
+    fn del(&mut self, key: &[u8]) -> Result<(), FjallError> {
+        self.txn.borrow_mut().remove(&self.keyspace, key);
+        Ok(())
+    }
+}
+
+/// Whether the range from `begin` to `end` is empty, fjall panics on inverted
+/// bounds.
+fn empty(begin: &[u8], end: Option<&[u8]>) -> bool {
+    end.is_some_and(|end| begin >= end)
+}
+
+/// The bounds of a range from `begin` to `end`, unbounded for `None`.
+fn bounds<'a>(begin: &'a [u8], end: Option<&'a [u8]>) -> (Bound<&'a [u8]>, Bound<&'a [u8]>) {
+    (
+        Bound::Included(begin),
+        end.map_or(Bound::Unbounded, Bound::Excluded),
+    )
+}
@@ -0,0 +1,40 @@
+use std::cell::RefCell;
+
+use crepedb_core::backend::WriteTxn;
+use fjall::{OptimisticTxDatabase, OptimisticWriteTx};
+
+use crate::{db::open_keyspace, FjallError, FjallWriteTable};
+
+/// A write transaction wrapper for fjall.
+///
+/// Implements the CrepeDB `WriteTxn` trait, providing read-write access to tables
+/// and the ability to commit changes. Commit fails with [`FjallError::Conflict`]
+/// if a key read or written by this transaction was changed by another one.
+pub struct FjallWriteTxn {
+    pub(crate) inner: RefCell<OptimisticWriteTx>,
+    pub(crate) db: OptimisticTxDatabase,
+}
+
+impl WriteTxn<FjallError> for FjallWriteTxn {
+    type Table<'a>
+        = FjallWriteTable<'a>
+    where
+        Self: 'a;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, FjallError> {
+        let keyspace = open_keyspace(&self.db, table, true)?.expect("created if missing");
+
+        Ok(FjallWriteTable {
+            txn: &self.inner,
+            keyspace,
+            name: table.to_string(),
+        })
+    }
+
+    fn commit(self) -> Result<(), FjallError> {
+        self.inner
+            .into_inner()
+            .commit()?
+            .map_err(|_| FjallError::Conflict)
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crepedb_core::backend::Backend;
+use heed::{Env, Error, WithoutTls};
+
+use crate::{LmdbOptions, LmdbReadTxn, LmdbWriteTxn};
+
+/// A CrepeDB backend implementation using LMDB.
+///
+/// This struct wraps a heed `Env` and implements the CrepeDB `Backend` trait,
+/// allowing LMDB to be used as the storage engine for CrepeDB.
+///
+/// Every CrepeDB table is stored in a named LMDB database with the same name.
+pub struct LmdbDatabase {
+    pub(crate) inner: Env<WithoutTls>,
+    // Declared after `inner` so the files are removed after the environment is closed.
+    pub(crate) temporary: Option<TemporaryDir>,
+}
+
+/// A directory that is removed when dropped.
+pub(crate) struct TemporaryDir(PathBuf);
+
+impl TemporaryDir {
+    /// Pick a new unique directory below the system temporary directory.
+    pub(crate) fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        let path = std::env::temp_dir().join(format!(
+            "crepedb-lmdb-{}-{}-{}",
+            std::process::id(),
+            nanos,
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        Self(path)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TemporaryDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.0) {
+            log::warn!("Failed to remove {}: {e}", self.0.display());
+        }
+    }
+}
+
+impl LmdbDatabase {
+    /// Open or create a persistent LMDB database at the specified path.
+    ///
+    /// If the database doesn't exist, it will be created. If it exists, it will be opened.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory path where the database should be stored
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let db = LmdbDatabase::open_or_create("mydb")?;
+    /// ```
+    pub fn open_or_create(path: impl AsRef<Path>) -> Result<Self, Error> {
+        LmdbOptions::default().open(path)
+    }
+
+    /// Create a temporary LMDB database.
+    ///
+    /// The database is stored in a new directory below the system temporary
+    /// directory, which is removed when the database is dropped.
+    /// This is useful for testing or temporary storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let db = LmdbDatabase::temporary()?;
+    /// ```
+    pub fn temporary() -> Result<Self, Error> {
+        LmdbOptions::default().temporary()
+    }
+
+    /// Get a reference to the underlying LMDB environment.
+    pub fn inner(&self) -> &Env<WithoutTls> {
+        &self.inner
+    }
+
+    /// Consume the database and return the underlying LMDB environment.
+    ///
+    /// The files of a temporary database are kept.
+    pub fn into_inner(self) -> Env<WithoutTls> {
+        if let Some(dir) = self.temporary {
+            std::mem::forget(dir);
+        }
+
+        self.inner
+    }
+}
+
+impl Backend for LmdbDatabase {
+    type Error = Error;
+
+    type ReadTxn<'a> = LmdbReadTxn<'a>;
+
+    type WriteTxn<'a> = LmdbWriteTxn<'a>;
+
+    fn read_txn(&self) -> Result<Self::ReadTxn<'_>, Self::Error> {
+        let txn = self.inner.read_txn()?;
+
+        Ok(LmdbReadTxn {
+            env: &self.inner,
+            inner: txn,
+        })
+    }
+
+    fn write_txn(&self) -> Result<Self::WriteTxn<'_>, Self::Error> {
+        let txn = self.inner.write_txn()?;
+
+        Ok(LmdbWriteTxn {
+            env: &self.inner,
+            inner: txn.into(),
+        })
+    }
+}
@@ -0,0 +1,138 @@
+//! # CrepeDB LMDB Backend
+//!
+//! This crate provides an [LMDB](http://www.lmdb.tech/doc/) backend implementation for CrepeDB,
+//! built on [heed](https://github.com/meilisearch/heed).
+//!
+//! LMDB is a small, fast, memory-mapped, transactional key-value store. It is
+//! shipped by many operating systems, so this backend avoids bundling a second
+//! native database where LMDB is already available.
+//!
+//! ## Features
+//!
+//! - **ACID Transactions**: Full transactional support via LMDB
+//! - **Named Database per Table**: Each table is stored in its own named LMDB database
+//! - **Temporary Mode**: Support for temporary databases removed on drop
+//! - **Configurable**: Map size, table and reader limits and read-only open via [`LmdbOptions`]
+//!
+//! ## Key Size
+//!
+//! LMDB limits the size of keys. The `longer-keys` build of LMDB is used, which
+//! allows keys up to about half a page (close to 2 KiB with 4 KiB pages) instead
+//! of 511 bytes. Versioned tables add a few bytes to every key.
+//!
+//! ## Example
+//!
+//! ```ignore
+//! use crepedb::{CrepeDB, SnapshotId, TableType};
+//! use crepedb_lmdb::LmdbDatabase;
+//!
+//! // Open a persistent database
+//! let backend = LmdbDatabase::open_or_create("mydb")?;
+//! let db = CrepeDB::new(backend);
+//!
+//! // Or create a temporary database
+//! let backend = LmdbDatabase::temporary()?;
+//! let db = CrepeDB::new(backend);
+//! ```
+
+mod db;
+pub use db::*;
+
+mod options;
+pub use options::*;
+
+mod read;
+pub use read::*;
+
+mod write;
+pub use write::*;
+
+mod table;
+pub use table::*;
+
+mod range;
+pub use range::*;
+
+#[cfg(test)]
+mod tests {
+    use crate::{LmdbDatabase, LmdbOptions};
+
+    crepedb_backend_tests::backend_tests!(LmdbDatabase::temporary().unwrap());
+
+    #[test]
+    fn test_range_while_writing() {
+        use crepedb_core::backend::{Backend, Range, ReadTable, WriteTable, WriteTxn};
+
+        let backend = LmdbDatabase::temporary().unwrap();
+
+        let txn = backend.write_txn().unwrap();
+        {
+            let mut t = txn.open_table("t").unwrap();
+            for i in 0..10u8 {
+                t.set(&[i], &[i]).unwrap();
+            }
+
+            let mut range = t.range(&[2], Some(&[8])).unwrap();
+            assert_eq!(range.back().unwrap(), Some((vec![2], vec![2])));
+
+            let mut t2 = txn.open_table("t").unwrap();
+            t2.del(&[3]).unwrap();
+            t2.set(&[4], &[40]).unwrap();
+
+            assert_eq!(range.back().unwrap(), Some((vec![4], vec![40])));
+            assert_eq!(range.back().unwrap(), Some((vec![5], vec![5])));
+        }
+        txn.commit().unwrap();
+    }
+
+    #[test]
+    fn test_options_read_only() {
+        use crepedb_core::backend::Backend;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let temp_dir = std::env::temp_dir().join(format!(
+            "crepedb-lmdb-test-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+
+        let backend = LmdbOptions::new()
+            .with_max_tables(16)
+            .with_map_size(64 << 20)
+            .open(&temp_dir)
+            .unwrap();
+        let result = crepedb_backend_tests::suite::test_read(backend);
+
+        let backend = LmdbOptions::new()
+            .with_read_only(true)
+            .open(&temp_dir)
+            .unwrap();
+        let read_ok = backend.read_txn().is_ok();
+        let write_err = backend.write_txn().is_err();
+        drop(backend);
+
+        // Clean up
+        let _ = std::fs::remove_dir_all(temp_dir);
+
+        result.unwrap();
+        assert!(read_ok);
+        assert!(write_err);
+    }
+
+    #[test]
+    fn test_temporary_cleanup() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let backend = LmdbDatabase::temporary().unwrap();
+        let path = backend.temporary.as_ref().unwrap().path().to_path_buf();
+
+        assert!(path.exists());
+
+        crepedb_backend_tests::suite::test_read(backend).unwrap();
+        assert!(!path.exists());
+    }
+}
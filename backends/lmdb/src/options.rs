@@ -0,0 +1,129 @@
+use std::path::Path;
+
+use heed::{EnvFlags, EnvOpenOptions, Error};
+
+use crate::{db::TemporaryDir, LmdbDatabase};
+
+/// Options used to open a [`LmdbDatabase`].
+///
+/// LMDB maps the whole database into memory, so the map size is the maximum
+/// size of the database. It defaults to 1 GiB and only reserves address space,
+/// the file grows on demand.
+///
+/// # Example
+///
+/// ```ignore
+/// let db = LmdbOptions::new()
+///     .with_map_size(64 << 30)
+///     .with_max_tables(64)
+///     .open("mydb")?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct LmdbOptions {
+    max_tables: u32,
+    max_readers: Option<u32>,
+    map_size: usize,
+    read_only: bool,
+}
+
+impl Default for LmdbOptions {
+    fn default() -> Self {
+        Self {
+            max_tables: 1024,
+            max_readers: None,
+            map_size: 1 << 30,
+            read_only: false,
+        }
+    }
+}
+
+impl LmdbOptions {
+    /// Create options with the default settings, allowing up to 1024 tables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of tables, including the internal tables.
+    pub fn with_max_tables(mut self, max_tables: u32) -> Self {
+        self.max_tables = max_tables;
+        self
+    }
+
+    /// Set the maximum number of concurrent read transactions.
+    pub fn with_max_readers(mut self, max_readers: u32) -> Self {
+        self.max_readers = Some(max_readers);
+        self
+    }
+
+    /// Set the size of the memory map, which is the maximum size of the database.
+    ///
+    /// The size should be a multiple of the OS page size.
+    pub fn with_map_size(mut self, bytes: usize) -> Self {
+        self.map_size = bytes;
+        self
+    }
+
+    /// Open the database read-only, write transactions will fail.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Open or create a persistent LMDB database at the specified path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory path where the database should be stored
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened, if it
+    /// does not exist when opened read-only, or if it is already open in this
+    /// process with different options.
+    pub fn open(self, path: impl AsRef<Path>) -> Result<LmdbDatabase, Error> {
+        let path = path.as_ref();
+
+        if !self.read_only {
+            std::fs::create_dir_all(path)?;
+        }
+
+        let mut options = EnvOpenOptions::new().read_txn_without_tls();
+        options.max_dbs(self.max_tables).map_size(self.map_size);
+
+        if let Some(max_readers) = self.max_readers {
+            options.max_readers(max_readers);
+        }
+
+        if self.read_only {
+            // SAFETY: `READ_ONLY` is not one of the flags that weaken durability
+            // or locking.
+            unsafe {
+                options.flags(EnvFlags::READ_ONLY);
+            }
+        }
+
+        // SAFETY: the environment is only accessed through LMDB, which locks the
+        // files against concurrent modification. Opening the same path twice in
+        // one process is detected by heed.
+        let env = unsafe { options.open(path)? };
+
+        Ok(LmdbDatabase {
+            inner: env,
+            temporary: None,
+        })
+    }
+
+    /// Create a temporary LMDB database, which is removed when dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created.
+    pub fn temporary(self) -> Result<LmdbDatabase, Error> {
+        let dir = TemporaryDir::new();
+
+        let mut db = self.open(dir.path())?;
+        db.temporary = Some(dir);
+
+        Ok(db)
+    }
+}
@@ -0,0 +1,73 @@
+use std::ops::Bound;
+
+use crepedb_core::types::Bytes;
+use heed::Error;
+
+use crate::{table::RawTable, Txn};
+
+/// A range iterator wrapper for LMDB.
+///
+/// Implements the CrepeDB `Range` trait. Every step seeks past the last
+/// returned key instead of keeping a cursor open, so the table can be written
+/// while the range is alive.
+pub struct LmdbRange<'a, 'e> {
+    pub(crate) table: Option<RawTable>,
+    pub(crate) txn: Txn<'a, 'e>,
+    pub(crate) begin: Bound<Bytes>,
+    pub(crate) end: Option<Bytes>,
+}
+
+impl<'a, 'e> LmdbRange<'a, 'e> {
+    pub(crate) fn new(
+        table: Option<RawTable>,
+        txn: Txn<'a, 'e>,
+        begin: &[u8],
+        end: Option<&[u8]>,
+    ) -> Self {
+        Self {
+            table,
+            txn,
+            begin: Bound::Included(begin.to_vec()),
+            end: end.map(<[u8]>::to_vec),
+        }
+    }
+}
+
+impl<'a, 'e> crepedb_core::backend::Range<Error> for LmdbRange<'a, 'e> {
+    fn back(&mut self) -> Result<Option<(Bytes, Bytes)>, Error> {
+        let Some(table) = self.table else {
+            return Ok(None);
+        };
+
+        // Reject inverted bounds before they reach LMDB.
+        if let (Bound::Included(b) | Bound::Excluded(b), Some(end)) = (&self.begin, &self.end) {
+            if b >= end {
+                return Ok(None);
+            }
+        }
+
+        // LMDB rejects empty keys, even for seeking.
+        let begin = match &self.begin {
+            Bound::Included(b) if b.is_empty() => Bound::Unbounded,
+            b => b.as_ref().map(Vec::as_slice),
+        };
+
+        let end = match &self.end {
+            Some(end) => Bound::Excluded(end.as_slice()),
+            None => Bound::Unbounded,
+        };
+
+        let bounds = (begin, end);
+
+        let item = self.txn.read(|txn| {
+            let item = table.range(txn, &bounds)?.next().transpose()?;
+            Ok(item.map(|(k, v)| (k.to_vec(), v.to_vec())))
+        })?;
+
+        if let Some((key, _)) = &item {
+            self.begin = Bound::Excluded(key.clone());
+        }
+
+        Ok(item)
+    }
+}
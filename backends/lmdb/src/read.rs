@@ -0,0 +1,31 @@
+use crepedb_core::backend::ReadTxn;
+use heed::{Env, Error, RoTxn, WithoutTls};
+
+use crate::{LmdbReadTable, Txn};
+
+/// A read transaction wrapper for LMDB.
+///
+/// Implements the CrepeDB `ReadTxn` trait, providing read-only access to tables.
+pub struct LmdbReadTxn<'a> {
+    pub(crate) env: &'a Env<WithoutTls>,
+    pub(crate) inner: RoTxn<'a, WithoutTls>,
+}
+
+impl<'a> ReadTxn<Error> for LmdbReadTxn<'a> {
+    type Table<'b>
+        = LmdbReadTable<'b>
+    where
+        Self: 'b;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, Error> {
+        // A table that was never written reads as empty.
+        let inner = self.env.open_database(&self.inner, Some(table))?;
+        let name = table.to_string();
+
+        Ok(LmdbReadTable {
+            inner,
+            txn: Txn::Read(&self.inner),
+            name,
+        })
+    }
+}
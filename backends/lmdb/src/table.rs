@@ -0,0 +1,136 @@
+use std::{borrow::Cow, cell::RefCell};
+
+use crepedb_core::{
+    backend::{ReadTable, WriteTable},
+    types::Bytes,
+};
+use heed::{types, Database, Error, RoTxn, RwTxn, WithoutTls};
+
+use crate::LmdbRange;
+
+/// The type of the LMDB database storing a table.
+pub(crate) type RawTable = Database<types::Bytes, types::Bytes>;
+
+/// The transaction a table was opened in.
+#[derive(Clone, Copy)]
+pub(crate) enum Txn<'a, 'e> {
+    Read(&'a RoTxn<'e, WithoutTls>),
+    Write(&'a RefCell<RwTxn<'e>>),
+}
+
+impl<'a, 'e> Txn<'a, 'e> {
+    /// Run a read operation in the transaction.
+    ///
+    /// The write transaction is only borrowed for the duration of `f`, so ranges
+    /// and writes on the same transaction can be interleaved.
+    pub(crate) fn read<R>(&self, f: impl FnOnce(&RoTxn) -> Result<R, Error>) -> Result<R, Error> {
+        match self {
+            Self::Read(txn) => f(txn),
+            Self::Write(txn) => f(&txn.borrow()),
+        }
+    }
+}
+
+/// A read-only table wrapper for LMDB.
+///
+/// Implements the CrepeDB `ReadTable` trait for LMDB's read-only transactions.
+pub struct LmdbReadTable<'a> {
+    pub(crate) inner: Option<RawTable>,
+    pub(crate) txn: Txn<'a, 'a>,
+    pub(crate) name: String,
+}
+
+impl<'a> ReadTable<Error> for LmdbReadTable<'a> {
+    type Range<'c>
+        = LmdbRange<'c, 'a>
+    where
+        Self: 'c;
+
+    type Value<'c>
+        = Cow<'c, [u8]>
+    where
+        Self: 'c;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, Error> {
+        let Some(inner) = self.inner else {
+            return Ok(None);
+        };
+
+        self.txn
+            .read(|txn| Ok(inner.get(txn, key)?.map(|v| v.to_vec())))
+    }
+
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, Error> {
+        let Some(inner) = self.inner else {
+            return Ok(None);
+        };
+
+        match self.txn {
+            // Pages of a read transaction stay mapped until it ends.
+            Txn::Read(txn) => Ok(inner.get(txn, key)?.map(Cow::Borrowed)),
+            Txn::Write(_) => Ok(self.get(key)?.map(Cow::Owned)),
+        }
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, Error> {
+        Ok(LmdbRange::new(self.inner, self.txn, begin, end))
+    }
+}
+
+/// A writable table wrapper for LMDB.
+///
+/// Implements both the CrepeDB `ReadTable` and `WriteTable` traits for LMDB's write transactions.
+pub struct LmdbWriteTable<'a, 'e> {
+    pub(crate) inner: RawTable,
+    pub(crate) txn: &'a RefCell<RwTxn<'e>>,
+    pub(crate) name: String,
+}
+
+impl<'a, 'e> ReadTable<Error> for LmdbWriteTable<'a, 'e> {
+    type Range<'c>
+        = LmdbRange<'c, 'e>
+    where
+        Self: 'c;
+
+    // The write transaction is only borrowed while reading, values are copied.
+    type Value<'c>
+        = Bytes
+    where
+        Self: 'c;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, Error> {
+        Txn::Write(self.txn).read(|txn| Ok(self.inner.get(txn, key)?.map(|v| v.to_vec())))
+    }
+
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, Error> {
+        self.get(key)
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, Error> {
+        Ok(LmdbRange::new(
+            Some(self.inner),
+            Txn::Write(self.txn),
+            begin,
+            end,
+        ))
+    }
+}
+
+impl<'a, 'e> WriteTable<Error> for LmdbWriteTable<'a, 'e> {
+    fn set(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.inner.put(&mut self.txn.borrow_mut(), key, value)
+    }
+
+    fn del(&mut self, key: &[u8]) -> Result<(), Error> {
+        self.inner.delete(&mut self.txn.borrow_mut(), key)?;
+        Ok(())
+    }
+}
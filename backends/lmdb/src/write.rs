@@ -0,0 +1,40 @@
+use std::cell::RefCell;
+
+use crepedb_core::backend::WriteTxn;
+use heed::{Env, Error, RwTxn, WithoutTls};
+
+use crate::LmdbWriteTable;
+
+/// A write transaction wrapper for LMDB.
+///
+/// Implements the CrepeDB `WriteTxn` trait, providing read-write access to tables
+/// and the ability to commit changes.
+pub struct LmdbWriteTxn<'a> {
+    pub(crate) env: &'a Env<WithoutTls>,
+    pub(crate) inner: RefCell<RwTxn<'a>>,
+}
+
+impl<'a> WriteTxn<Error> for LmdbWriteTxn<'a> {
+    type Table<'b>
+        = LmdbWriteTable<'b, 'a>
+    where
+        Self: 'b;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, Error> {
+        let inner = self
+            .env
+            .create_database(&mut self.inner.borrow_mut(), Some(table))?;
+        let name = table.to_string();
+
+        Ok(LmdbWriteTable {
+            inner,
+            txn: &self.inner,
+            name,
+        })
+    }
+
+    fn commit(self) -> Result<(), Error> {
+        self.inner.into_inner().commit()?;
+        Ok(())
+    }
+}
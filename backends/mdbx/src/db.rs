@@ -1,16 +1,58 @@
-use std::path::Path;
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crepedb_core::backend::Backend;
-use libmdbx::{Database, DatabaseOptions, Error, NoWriteMap};
+use libmdbx::{Database, Error, NoWriteMap};
 
-use crate::{MdbxReadTxn, MdbxWriteTxn};
+use crate::{MdbxOptions, MdbxReadTxn, MdbxWriteTxn};
 
 /// A CrepeDB backend implementation using MDBX.
 ///
 /// This struct wraps a MDBX `Database` and implements the CrepeDB `Backend` trait,
 /// allowing MDBX to be used as the storage engine for CrepeDB.
 pub struct MdbxDatabase {
-    inner: Database<NoWriteMap>,
+    pub(crate) inner: Database<NoWriteMap>,
+    // Declared after `inner` so the files are removed after the database is closed.
+    pub(crate) temporary: Option<TemporaryDir>,
+}
+
+/// A directory that is removed when dropped.
+pub(crate) struct TemporaryDir(PathBuf);
+
+impl TemporaryDir {
+    /// Pick a new unique directory below the system temporary directory.
+    pub(crate) fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        let path = std::env::temp_dir().join(format!(
+            "crepedb-mdbx-{}-{}-{}",
+            std::process::id(),
+            nanos,
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        Self(path)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TemporaryDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.0) {
+            log::warn!("Failed to remove {}: {e}", self.0.display());
+        }
+    }
 }
 
 impl MdbxDatabase {
@@ -32,12 +74,26 @@ impl MdbxDatabase {
     /// let db = MdbxDatabase::open_or_create("mydb.mdbx")?;
     /// ```
     pub fn open_or_create(path: impl AsRef<Path>) -> Result<Self, Error> {
-        let mut options = DatabaseOptions::default();
-        options.max_tables = Some(1024);
-
-        let db = Database::open_with_options(path, options)?;
+        MdbxOptions::default().open(path)
+    }
 
-        Ok(Self { inner: db })
+    /// Create a temporary MDBX database.
+    ///
+    /// The database is stored in a new directory below the system temporary
+    /// directory, which is removed when the database is dropped.
+    /// This is useful for testing or temporary storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let db = MdbxDatabase::temporary()?;
+    /// ```
+    pub fn temporary() -> Result<Self, Error> {
+        MdbxOptions::default().temporary()
     }
 
     /// Get a reference to the underlying MDBX database.
@@ -46,7 +102,13 @@ impl MdbxDatabase {
     }
 
     /// Consume the database and return the underlying MDBX database.
+    ///
+    /// The files of a temporary database are kept.
     pub fn into_inner(self) -> Database<NoWriteMap> {
+        if let Some(dir) = self.temporary {
+            std::mem::forget(dir);
+        }
+
         self.inner
     }
 }
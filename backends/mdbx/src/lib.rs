@@ -10,7 +10,9 @@
 //! - **ACID Transactions**: Full transactional support via MDBX
 //! - **Embedded**: No separate server process required
 //! - **Persistent Storage**: Data is stored on disk
+//! - **Temporary Mode**: Databases in a temporary directory removed on drop
 //! - **High Performance**: Optimized for fast reads and writes
+//! - **Configurable**: Geometry, sync mode and read-only open via [`MdbxOptions`]
 //!
 //! ## Example
 //!
@@ -21,11 +23,18 @@
 //! // Open a persistent database
 //! let backend = MdbxDatabase::open_or_create("mydb.mdbx")?;
 //! let db = CrepeDB::new(backend);
+//!
+//! // Or create a temporary database, removed when dropped
+//! let backend = MdbxDatabase::temporary()?;
+//! let db = CrepeDB::new(backend);
 //! ```
 
 mod db;
 pub use db::*;
 
+mod options;
+pub use options::*;
+
 mod read;
 pub use read::*;
 
@@ -40,102 +49,14 @@ pub use range::*;
 
 #[cfg(test)]
 mod tests {
-    use crate::MdbxDatabase;
-
-    #[test]
-    fn test_db_10() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::tests::test_db_10(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_read() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_read(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_snapshot_isolation() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_snapshot_isolation(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_multiple_keys() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
+    use crepedb_core::backend::Backend;
 
-        let result = crepedb_core::read_tests::test_multiple_keys(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
+    use crate::{MdbxDatabase, MdbxOptions, SyncMode};
 
-        result.unwrap();
-    }
+    crepedb_backend_tests::backend_tests!(MdbxDatabase::temporary().unwrap());
 
     #[test]
-    fn test_error_handling() {
+    fn test_options_read_only() {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let temp_dir = std::env::temp_dir().join(format!(
@@ -147,243 +68,40 @@ mod tests {
                 .as_nanos()
         ));
 
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_error_handling(backend);
+        let backend = MdbxOptions::new()
+            .with_max_tables(16)
+            .with_max_size(64 << 20)
+            .with_sync_mode(SyncMode::SafeNoSync)
+            .open(&temp_dir)
+            .unwrap();
+        let result = crepedb_backend_tests::suite::test_read(backend);
+
+        let backend = MdbxOptions::new()
+            .with_read_only(true)
+            .open(&temp_dir)
+            .unwrap();
+        let read_ok = backend.read_txn().is_ok();
+        let write_err = backend.write_txn().is_err();
+        drop(backend);
 
         // Clean up
         let _ = std::fs::remove_dir_all(temp_dir);
 
         result.unwrap();
+        assert!(read_ok);
+        assert!(write_err);
     }
 
     #[test]
-    fn test_multiple_tables() {
+    fn test_temporary_cleanup() {
         let _ = env_logger::builder().is_test(true).try_init();
 
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
+        let backend = MdbxDatabase::temporary().unwrap();
+        let path = backend.temporary.as_ref().unwrap().path().to_path_buf();
 
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
+        assert!(path.exists());
 
-        let result = crepedb_core::read_tests::test_multiple_tables(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_basic_table_type() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_basic_table_type(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_edge_cases() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_edge_cases(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_version_chain() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_version_chain(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_delete_operations() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_delete_operations(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_root_snapshot() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_root_snapshot(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_transaction_lifecycle() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_transaction_lifecycle(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_mixed_operations() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_mixed_operations(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_parent_child_visibility() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_parent_child_visibility(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_complex_branching() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let temp_dir = std::env::temp_dir().join(format!(
-            "crepedb-mdbx-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-        ));
-
-        let backend = MdbxDatabase::open_or_create(&temp_dir).unwrap();
-
-        let result = crepedb_core::read_tests::test_complex_branching(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
+        crepedb_backend_tests::suite::test_read(backend).unwrap();
+        assert!(!path.exists());
     }
 }
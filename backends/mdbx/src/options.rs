@@ -0,0 +1,156 @@
+use std::path::Path;
+
+use libmdbx::{Database, DatabaseOptions, Error, Mode, ReadWriteOptions};
+
+pub use libmdbx::SyncMode;
+
+use crate::{db::TemporaryDir, MdbxDatabase};
+
+/// Options used to open a [`MdbxDatabase`].
+///
+/// The geometry settings bound the size of the memory map and control how the
+/// database file grows and shrinks. Sizes are in bytes and only take effect
+/// when the database is opened read-write. MDBX does not compress data, so there
+/// is no compression setting.
+///
+/// # Example
+///
+/// ```ignore
+/// let db = MdbxOptions::new()
+///     .with_max_size(64 << 30)
+///     .with_sync_mode(SyncMode::SafeNoSync)
+///     .open("mydb.mdbx")?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct MdbxOptions {
+    max_tables: u64,
+    max_readers: Option<u32>,
+    read_only: bool,
+    sync_mode: SyncMode,
+    min_size: Option<isize>,
+    max_size: Option<isize>,
+    growth_step: Option<isize>,
+    shrink_threshold: Option<isize>,
+}
+
+impl Default for MdbxOptions {
+    fn default() -> Self {
+        Self {
+            max_tables: 1024,
+            max_readers: None,
+            read_only: false,
+            sync_mode: SyncMode::Durable,
+            min_size: None,
+            max_size: None,
+            growth_step: None,
+            shrink_threshold: None,
+        }
+    }
+}
+
+impl MdbxOptions {
+    /// Create options with the default settings, allowing up to 1024 tables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of tables, including the internal tables.
+    pub fn with_max_tables(mut self, max_tables: u64) -> Self {
+        self.max_tables = max_tables;
+        self
+    }
+
+    /// Set the maximum number of concurrent read transactions.
+    pub fn with_max_readers(mut self, max_readers: u32) -> Self {
+        self.max_readers = Some(max_readers);
+        self
+    }
+
+    /// Open the database read-only, write transactions will fail.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Set how commits are flushed to disk.
+    pub fn with_sync_mode(mut self, sync_mode: SyncMode) -> Self {
+        self.sync_mode = sync_mode;
+        self
+    }
+
+    /// Set the lower bound of the database size.
+    pub fn with_min_size(mut self, bytes: isize) -> Self {
+        self.min_size = Some(bytes);
+        self
+    }
+
+    /// Set the upper bound of the database size, which is also the size of the memory map.
+    pub fn with_max_size(mut self, bytes: isize) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Set the step by which the database file grows.
+    pub fn with_growth_step(mut self, bytes: isize) -> Self {
+        self.growth_step = Some(bytes);
+        self
+    }
+
+    /// Set the amount of free space at the end of the file before it is shrunk.
+    pub fn with_shrink_threshold(mut self, bytes: isize) -> Self {
+        self.shrink_threshold = Some(bytes);
+        self
+    }
+
+    /// Open or create a persistent MDBX database at the specified path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory path where the database should be stored
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened, or if it
+    /// does not exist when opened read-only.
+    pub fn open(self, path: impl AsRef<Path>) -> Result<MdbxDatabase, Error> {
+        let mode = if self.read_only {
+            Mode::ReadOnly
+        } else {
+            Mode::ReadWrite(ReadWriteOptions {
+                sync_mode: self.sync_mode,
+                min_size: self.min_size,
+                max_size: self.max_size,
+                growth_step: self.growth_step,
+                shrink_threshold: self.shrink_threshold,
+            })
+        };
+
+        let options = DatabaseOptions {
+            max_tables: Some(self.max_tables),
+            max_readers: self.max_readers,
+            mode,
+            ..Default::default()
+        };
+
+        let db = Database::open_with_options(path, options)?;
+
+        Ok(MdbxDatabase {
+            inner: db,
+            temporary: None,
+        })
+    }
+
+    /// Create a temporary MDBX database, which is removed when dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created.
+    pub fn temporary(self) -> Result<MdbxDatabase, Error> {
+        let dir = TemporaryDir::new();
+
+        let mut db = self.open(dir.path())?;
+        db.temporary = Some(dir);
+
+        Ok(db)
+    }
+}
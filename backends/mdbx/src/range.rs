@@ -7,7 +7,7 @@ use libmdbx::{Cursor, Error, TransactionKind};
 pub struct MdbxRange<'a, K: TransactionKind> {
     pub(crate) cursor: Cursor<'a, K>,
     pub(crate) begin: Bytes,
-    pub(crate) end: Bytes,
+    pub(crate) end: Option<Bytes>,
     pub(crate) started: bool,
 }
 
@@ -25,7 +25,7 @@ impl<'a, K: TransactionKind> crepedb_core::backend::Range<Error> for MdbxRange<'
         match result {
             Ok(Some((key, value))) => {
                 // Check if key is within range
-                if key < self.end {
+                if self.end.as_ref().is_none_or(|end| &key < end) {
                     Ok(Some((key, value)))
                 } else {
                     Ok(None)
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use crepedb_core::{
     backend::{ReadTable, WriteTable},
     types::Bytes,
@@ -21,21 +23,30 @@ impl<'a> ReadTable<Error> for MdbxReadTable<'a> {
     where
         Self: 'c;
 
+    type Value<'c>
+        = Cow<'c, [u8]>
+    where
+        Self: 'c;
+
     fn name(&self) -> &str {
         &self.name
     }
 
-    fn get(&self, key: Bytes) -> Result<Option<Bytes>, Error> {
-        self.txn.get::<Vec<u8>>(&self.inner, &key)
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, Error> {
+        self.txn.get::<Vec<u8>>(&self.inner, key)
     }
 
-    fn range(&self, begin: Bytes, end: Bytes) -> Result<Self::Range<'_>, Error> {
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, Error> {
+        self.txn.get::<Cow<'_, [u8]>>(&self.inner, key)
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, Error> {
         let cursor = self.txn.cursor(&self.inner)?;
-        
+
         Ok(MdbxRange {
             cursor,
-            begin,
-            end,
+            begin: begin.to_vec(),
+            end: end.map(<[u8]>::to_vec),
             started: false,
         })
     }
@@ -56,34 +67,43 @@ impl<'a> ReadTable<Error> for MdbxWriteTable<'a> {
     where
         Self: 'c;
 
+    type Value<'c>
+        = Cow<'c, [u8]>
+    where
+        Self: 'c;
+
     fn name(&self) -> &str {
         &self.name
     }
 
-    fn get(&self, key: Bytes) -> Result<Option<Bytes>, Error> {
-        self.txn.get::<Vec<u8>>(&self.inner, &key)
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, Error> {
+        self.txn.get::<Vec<u8>>(&self.inner, key)
     }
 
-    fn range(&self, begin: Bytes, end: Bytes) -> Result<Self::Range<'_>, Error> {
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, Error> {
+        self.txn.get::<Cow<'_, [u8]>>(&self.inner, key)
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, Error> {
         let cursor = self.txn.cursor(&self.inner)?;
-        
+
         Ok(MdbxRange {
             cursor,
-            begin,
-            end,
+            begin: begin.to_vec(),
+            end: end.map(<[u8]>::to_vec),
             started: false,
         })
     }
 }
 
 impl<'a> WriteTable<Error> for MdbxWriteTable<'a> {
-    fn set(&mut self, key: Bytes, value: Bytes) -> Result<(), Error> {
+    fn set(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
         self.txn.put(&self.inner, key, value, Default::default())?;
         Ok(())
     }
 
-    fn del(&mut self, key: Bytes) -> Result<(), Error> {
-        match self.txn.del(&self.inner, &key, None) {
+    fn del(&mut self, key: &[u8]) -> Result<(), Error> {
+        match self.txn.del(&self.inner, key, None) {
             Ok(_) => Ok(()),
             Err(Error::NotFound) => Ok(()),
             Err(e) => Err(e),
@@ -0,0 +1,96 @@
+//! Time of state root commits and proofs over chains of different lengths.
+//!
+//! Run with `cargo bench -p crepedb-redb`. The cost of a commit and of a proof
+//! should stay flat as the chain grows.
+
+use std::{hint::black_box, time::Instant};
+
+use crepedb_core::{types::SnapshotId, CrepeDB};
+use crepedb_redb::RedbDatabase;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const KEYS: u32 = 1000;
+
+/// Keys written by each commit.
+const WRITES: u32 = 10;
+
+const LENGTHS: [u32; 3] = [100, 1000, 10000];
+
+/// A database with state roots and a chain of `length` snapshots, each
+/// writing `WRITES` of `KEYS` keys of a versioned table.
+fn setup(length: u32) -> (CrepeDB<RedbDatabase>, SnapshotId) {
+    let db = CrepeDB::new(RedbDatabase::memory().unwrap()).with_state_root(true);
+
+    let wtxn = db.write(None).unwrap();
+    wtxn.create_versioned_table("v").unwrap();
+    let mut snapshot = wtxn.commit().unwrap();
+
+    for n in 0..length {
+        snapshot = extend(&db, snapshot, n);
+    }
+
+    (db, snapshot)
+}
+
+/// Commit the `n`th snapshot of the chain on top of `snapshot`.
+fn extend(db: &CrepeDB<RedbDatabase>, snapshot: SnapshotId, n: u32) -> SnapshotId {
+    let wtxn = db.write(Some(snapshot)).unwrap();
+    {
+        let mut v = wtxn.open_table("v").unwrap();
+
+        for i in 0..WRITES {
+            let key = (n * WRITES + i) % KEYS;
+            v.set(key.to_be_bytes(), n.to_be_bytes()).unwrap();
+        }
+    }
+    wtxn.commit().unwrap()
+}
+
+fn bench_commit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("commit");
+    group.sample_size(10);
+
+    for length in LENGTHS {
+        let (db, mut tip) = setup(length);
+        let mut n = length;
+
+        // Extend the chain instead of forking from the tip, forks of the same
+        // version are all scanned by reads at that version. The chain grows
+        // by the measured commits, which is small next to its length.
+        group.bench_with_input(BenchmarkId::from_parameter(length), &length, |b, _| {
+            b.iter_custom(|iters| {
+                let start = Instant::now();
+                for _ in 0..iters {
+                    tip = extend(&db, tip.clone(), n);
+                    n += 1;
+                }
+                black_box(start.elapsed())
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_proof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_with_proof");
+
+    for length in LENGTHS {
+        let (db, tip) = setup(length);
+        let rtxn = db.read(Some(tip)).unwrap();
+        let table = rtxn.open_table("v").unwrap();
+
+        group.bench_with_input(BenchmarkId::from_parameter(length), &length, |b, _| {
+            let mut i = 0u32;
+            b.iter(|| {
+                i = (i + 1) % KEYS;
+                black_box(table.get_with_proof(i.to_be_bytes()).unwrap())
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_commit, bench_proof);
+criterion_main!(benches);
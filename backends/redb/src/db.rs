@@ -1,16 +1,31 @@
-use std::path::Path;
+use std::{collections::BTreeSet, io, path::Path};
 
 use crepedb_core::backend::Backend;
-use redb::{backends::InMemoryBackend, Builder, Database, Error, ReadableDatabase};
+use redb::{
+    backends::InMemoryBackend, Builder, Database, Durability, Error, Key, ReadTransaction,
+    ReadableDatabase, ReadableTableMetadata, TableDefinition, TableError, TableHandle, Value,
+};
 
-use crate::{RedbReadTxn, RedbWriteTxn};
+use crate::{
+    types::{BytesTy, LegacyBytesTy},
+    RedbOptions, RedbReadTxn, RedbWriteTxn,
+};
+
+/// Number of entries moved per write transaction by
+/// [`RedbDatabase::migrate_legacy_encoding`].
+pub const MIGRATE_BATCH_SIZE: usize = 10_000;
+
+/// Suffix of the staging table of a table that is migrated.
+const MIGRATE_SUFFIX: &str = "\0migrating";
 
 /// A CrepeDB backend implementation using redb.
 ///
 /// This struct wraps a redb `Database` and implements the CrepeDB `Backend` trait,
 /// allowing redb to be used as the storage engine for CrepeDB.
 pub struct RedbDatabase {
-    inner: Database,
+    pub(crate) inner: Database,
+    pub(crate) durability: Durability,
+    pub(crate) read_only: bool,
 }
 
 impl RedbDatabase {
@@ -32,8 +47,7 @@ impl RedbDatabase {
     /// let db = RedbDatabase::open_or_create("mydb.redb")?;
     /// ```
     pub fn open_or_create(path: impl AsRef<Path>) -> Result<Self, Error> {
-        let db = Database::create(path)?;
-        Ok(Self { inner: db })
+        RedbOptions::default().open(path)
     }
 
     /// Create an in-memory redb database.
@@ -54,7 +68,11 @@ impl RedbDatabase {
         let backend = InMemoryBackend::new();
 
         let db = Builder::new().create_with_backend(backend)?;
-        Ok(Self { inner: db })
+        Ok(Self {
+            inner: db,
+            durability: Durability::Immediate,
+            read_only: false,
+        })
     }
 
     /// Get a reference to the underlying redb database.
@@ -66,6 +84,124 @@ impl RedbDatabase {
     pub fn into_inner(self) -> Database {
         self.inner
     }
+
+    /// Re-encode the tables written by older versions of this crate.
+    ///
+    /// Older versions stored keys and values with redb's `Vec<u8>` encoding,
+    /// which orders keys by length first. Such tables can be read, but not
+    /// written, until they are migrated.
+    ///
+    /// Each table is moved into a staging table and back with the current
+    /// encoding, at most [`MIGRATE_BATCH_SIZE`] entries per write
+    /// transaction, so memory use is bounded by the batch size. The database
+    /// should not be used while it is migrated. An interrupted migration is
+    /// finished by running it again, and tables with the current encoding are
+    /// left untouched, so migration is idempotent.
+    ///
+    /// # Returns
+    ///
+    /// The number of tables that were migrated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database is read-only, or if reading or
+    /// writing fails.
+    pub fn migrate_legacy_encoding(&self) -> Result<usize, Error> {
+        let mut names = BTreeSet::new();
+
+        let txn = self.inner.begin_read()?;
+        for table in txn.list_tables()? {
+            let name = table.name();
+
+            if let Some(name) = name.strip_suffix(MIGRATE_SUFFIX) {
+                names.insert(name.to_string());
+            } else if exists(&txn, legacy_definition(name))? {
+                names.insert(name.to_string());
+            }
+        }
+        drop(txn);
+
+        for name in &names {
+            self.migrate_table(name)?;
+        }
+
+        if !names.is_empty() {
+            log::info!("Migrated {} tables from the legacy encoding", names.len());
+        }
+
+        Ok(names.len())
+    }
+
+    /// Move the entries of a legacy table into its staging table, and from
+    /// there into the table with the current encoding.
+    fn migrate_table(&self, name: &str) -> Result<(), Error> {
+        let legacy = legacy_definition(name);
+        let staging_name = format!("{name}{MIGRATE_SUFFIX}");
+        let staging = TableDefinition::<BytesTy, BytesTy>::new(&staging_name);
+        let current = TableDefinition::<BytesTy, BytesTy>::new(name);
+
+        while exists(&self.inner.begin_read()?, legacy)? {
+            let txn = self.write_txn()?.inner;
+
+            let mut from = txn.open_table(legacy)?;
+            if from.is_empty()? {
+                drop(from);
+                txn.delete_table(legacy)?;
+            } else {
+                let mut to = txn.open_table(staging)?;
+                for _ in 0..MIGRATE_BATCH_SIZE {
+                    let Some((key, value)) = from.pop_first()? else {
+                        break;
+                    };
+                    to.insert(key.value().as_slice(), value.value().as_slice())?;
+                }
+                drop((from, to));
+            }
+
+            txn.commit()?;
+        }
+
+        while exists(&self.inner.begin_read()?, staging)? {
+            let txn = self.write_txn()?.inner;
+
+            let mut from = txn.open_table(staging)?;
+            // Opening the table creates it, even if it has no entries.
+            let mut to = txn.open_table(current)?;
+            if from.is_empty()? {
+                drop((from, to));
+                txn.delete_table(staging)?;
+            } else {
+                for _ in 0..MIGRATE_BATCH_SIZE {
+                    let Some((key, value)) = from.pop_first()? else {
+                        break;
+                    };
+                    to.insert(key.value(), value.value())?;
+                }
+                drop((from, to));
+            }
+
+            txn.commit()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The definition of a table with the legacy encoding.
+fn legacy_definition(name: &str) -> TableDefinition<'_, LegacyBytesTy, LegacyBytesTy> {
+    TableDefinition::new(name)
+}
+
+/// Whether a table exists with the key and value types of `definition`.
+fn exists<K: Key + 'static, V: Value + 'static>(
+    txn: &ReadTransaction,
+    definition: TableDefinition<K, V>,
+) -> Result<bool, Error> {
+    match txn.open_table(definition) {
+        Ok(_) => Ok(true),
+        Err(TableError::TableDoesNotExist(_) | TableError::TableTypeMismatch { .. }) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
 }
 
 impl Backend for RedbDatabase {
@@ -82,7 +218,13 @@ impl Backend for RedbDatabase {
     }
 
     fn write_txn(&self) -> Result<Self::WriteTxn<'_>, Self::Error> {
-        let txn = self.inner.begin_write()?;
+        if self.read_only {
+            let e = io::Error::new(io::ErrorKind::PermissionDenied, "database is read-only");
+            return Err(Error::Io(e));
+        }
+
+        let mut txn = self.inner.begin_write()?;
+        txn.set_durability(self.durability)?;
 
         Ok(RedbWriteTxn { inner: txn })
     }
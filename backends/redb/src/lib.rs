@@ -11,6 +11,7 @@
 //! - **Embedded**: No separate server process required
 //! - **Persistent Storage**: Data is stored on disk
 //! - **In-Memory Mode**: Support for temporary in-memory databases
+//! - **Configurable**: Cache size, durability and read-only open via [`RedbOptions`]
 //!
 //! ## Example
 //!
@@ -25,11 +26,31 @@
 //! // Or create an in-memory database
 //! let backend = RedbDatabase::memory()?;
 //! let db = CrepeDB::new(backend);
+//!
+//! // Or tune the open options
+//! let backend = RedbOptions::new()
+//!     .with_cache_size(256 << 20)
+//!     .open("mydb.redb")?;
+//! ```
+//!
+//! ## Key Encoding
+//!
+//! Keys and values are stored as plain byte strings and keys are ordered
+//! lexicographically. Older versions stored them with redb's `Vec<u8>`
+//! encoding, which orders keys by length first. Tables written by those
+//! versions can be read, but must be re-encoded before they can be written:
+//!
+//! ```ignore
+//! let backend = RedbDatabase::open_or_create("mydb.redb")?;
+//! backend.migrate_legacy_encoding()?;
 //! ```
 
 mod db;
 pub use db::*;
 
+mod options;
+pub use options::*;
+
 mod read;
 pub use read::*;
 
@@ -43,143 +64,160 @@ mod range;
 pub use range::*;
 
 pub(crate) mod types;
+pub use types::RedbValue;
 
 #[cfg(test)]
 mod tests {
-    use crate::RedbDatabase;
+    use crepedb_core::backend::Backend;
 
-    #[test]
-    fn test_db_10() {
-        let _ = env_logger::builder().is_test(true).try_init();
+    use crate::{Durability, RedbDatabase, RedbOptions};
 
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::tests::test_db_10(backend).unwrap();
-    }
+    crepedb_backend_tests::backend_tests!(RedbDatabase::memory().unwrap());
 
     #[test]
-    fn test_read() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_read(backend).unwrap();
+    fn test_savepoint_removes_table() {
+        use crepedb_core::CrepeDB;
+        use redb::TableHandle;
+
+        let db = CrepeDB::new(RedbDatabase::memory().unwrap());
+
+        let txn = db.write(None).unwrap();
+        txn.create_basic_table("a").unwrap();
+        let savepoint = txn.savepoint().unwrap();
+        txn.create_basic_table("b").unwrap();
+        txn.open_table("b").unwrap().set(vec![1], vec![1]).unwrap();
+        savepoint.rollback_to().unwrap();
+        txn.commit().unwrap();
+
+        let txn = db.backend().read_txn().unwrap();
+        let tables: Vec<_> = txn
+            .inner
+            .list_tables()
+            .unwrap()
+            .map(|table| table.name().to_string())
+            .collect();
+        assert!(tables.contains(&"__crepe_meta".to_string()));
+        assert!(!tables.contains(&"b".to_string()));
     }
 
     #[test]
-    fn test_snapshot_isolation() {
+    fn test_options_read_only() {
         let _ = env_logger::builder().is_test(true).try_init();
 
-        let backend = RedbDatabase::memory().unwrap();
+        let path =
+            std::env::temp_dir().join(format!("crepedb_redb_options_{}.redb", std::process::id()));
 
-        crepedb_core::read_tests::test_snapshot_isolation(backend).unwrap();
-    }
+        let backend = RedbOptions::new()
+            .with_cache_size(1 << 20)
+            .with_durability(Durability::None)
+            .open(&path)
+            .unwrap();
+        crepedb_backend_tests::suite::test_read(backend).unwrap();
 
-    #[test]
-    fn test_multiple_keys() {
-        let _ = env_logger::builder().is_test(true).try_init();
+        let backend = RedbOptions::new().with_read_only(true).open(&path).unwrap();
+        assert!(backend.read_txn().is_ok());
+        assert!(backend.write_txn().is_err());
+        drop(backend);
 
-        let backend = RedbDatabase::memory().unwrap();
+        std::fs::remove_file(&path).unwrap();
 
-        crepedb_core::read_tests::test_multiple_keys(backend).unwrap();
+        assert!(RedbOptions::new().with_read_only(true).open(&path).is_err());
     }
 
     #[test]
-    fn test_error_handling() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_error_handling(backend).unwrap();
-    }
-
-    #[test]
-    fn test_multiple_tables() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_multiple_tables(backend).unwrap();
-    }
-
-    #[test]
-    fn test_basic_table_type() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_basic_table_type(backend).unwrap();
-    }
-
-    #[test]
-    fn test_edge_cases() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_edge_cases(backend).unwrap();
-    }
-
-    #[test]
-    fn test_version_chain() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_version_chain(backend).unwrap();
-    }
-
-    #[test]
-    fn test_delete_operations() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_delete_operations(backend).unwrap();
-    }
-
-    #[test]
-    fn test_root_snapshot() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_root_snapshot(backend).unwrap();
-    }
-
-    #[test]
-    fn test_transaction_lifecycle() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_transaction_lifecycle(backend).unwrap();
-    }
-
-    #[test]
-    fn test_mixed_operations() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_mixed_operations(backend).unwrap();
-    }
-
-    #[test]
-    fn test_parent_child_visibility() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_parent_child_visibility(backend).unwrap();
-    }
-
-    #[test]
-    fn test_complex_branching() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let backend = RedbDatabase::memory().unwrap();
-
-        crepedb_core::read_tests::test_complex_branching(backend).unwrap();
+    fn test_migrate_legacy_encoding() {
+        use crepedb_core::backend::{Range, ReadTable, ReadTxn, WriteTable, WriteTxn};
+        use redb::TableDefinition;
+
+        use crate::{types::LegacyBytesTy, MIGRATE_BATCH_SIZE};
+
+        let path =
+            std::env::temp_dir().join(format!("crepedb_redb_legacy_{}.redb", std::process::id()));
+
+        let check = |backend: &RedbDatabase| {
+            let txn = backend.read_txn().unwrap();
+            let table = txn.open_table("a").unwrap();
+            assert_eq!(table.get(&[1]).unwrap(), Some(vec![]));
+
+            let mut range = table.range(&[], Some(&[0xff, 0xff])).unwrap();
+            assert_eq!(range.back().unwrap(), Some((vec![0, 0], vec![2])));
+            assert_eq!(range.back().unwrap(), Some((vec![1], vec![])));
+            assert_eq!(range.back().unwrap(), Some((vec![0xff], vec![1])));
+            assert_eq!(range.back().unwrap(), None);
+
+            let table = txn.open_table("b").unwrap();
+            let mut range = table.range(&[], None).unwrap();
+            for i in 0..MIGRATE_BATCH_SIZE as u32 * 2 + 1 {
+                let key = i.to_be_bytes().to_vec();
+                assert_eq!(range.back().unwrap(), Some((key.clone(), key)));
+            }
+            assert_eq!(range.back().unwrap(), None);
+        };
+
+        // Write tables with the encoding of older versions.
+        {
+            let db = redb::Database::create(&path).unwrap();
+            let txn = db.begin_write().unwrap();
+            {
+                let definition = TableDefinition::<LegacyBytesTy, LegacyBytesTy>::new("a");
+                let mut table = txn.open_table(definition).unwrap();
+                table.insert(vec![0xff], vec![1]).unwrap();
+                table.insert(vec![0, 0], vec![2]).unwrap();
+                table.insert(vec![1], vec![]).unwrap();
+
+                let definition = TableDefinition::<LegacyBytesTy, LegacyBytesTy>::new("b");
+                let mut table = txn.open_table(definition).unwrap();
+                for i in 0..MIGRATE_BATCH_SIZE as u32 * 2 + 1 {
+                    table
+                        .insert(i.to_be_bytes().to_vec(), i.to_be_bytes().to_vec())
+                        .unwrap();
+                }
+            }
+            txn.commit().unwrap();
+        }
+
+        // Legacy tables can be read before they are migrated.
+        let backend = RedbOptions::new().with_read_only(true).open(&path).unwrap();
+        check(&backend);
+        assert!(backend.migrate_legacy_encoding().is_err());
+        drop(backend);
+
+        let backend = RedbOptions::new().open(&path).unwrap();
+        let txn = backend.write_txn().unwrap();
+        assert!(txn.open_table("a").is_err());
+        drop(txn);
+
+        assert_eq!(backend.migrate_legacy_encoding().unwrap(), 2);
+        assert_eq!(backend.migrate_legacy_encoding().unwrap(), 0);
+        check(&backend);
+
+        let txn = backend.write_txn().unwrap();
+        txn.open_table("a").unwrap().set(&[2], &[3]).unwrap();
+        txn.commit().unwrap();
+
+        // A migration interrupted after its legacy table was deleted is
+        // finished from the staging table.
+        let txn = backend.write_txn().unwrap();
+        let definition =
+            TableDefinition::<crate::types::BytesTy, crate::types::BytesTy>::new("c\0migrating");
+        txn.inner
+            .open_table(definition)
+            .unwrap()
+            .insert([1].as_slice(), [2].as_slice())
+            .unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(backend.migrate_legacy_encoding().unwrap(), 1);
+
+        let txn = backend.read_txn().unwrap();
+        assert_eq!(
+            txn.open_table("c").unwrap().get(&[1]).unwrap(),
+            Some(vec![2])
+        );
+        assert!(txn.open_table("c\0migrating").is_err());
+        drop(txn);
+        drop(backend);
+
+        std::fs::remove_file(&path).unwrap();
     }
 }
@@ -0,0 +1,102 @@
+use std::path::Path;
+
+use redb::{Builder, Error};
+
+pub use redb::Durability;
+
+use crate::RedbDatabase;
+
+/// Options used to open a [`RedbDatabase`].
+///
+/// redb does not compress data and grows its file on demand, so there are no
+/// compression or map size settings.
+///
+/// # Example
+///
+/// ```ignore
+/// let db = RedbOptions::new()
+///     .with_cache_size(256 << 20)
+///     .with_durability(Durability::None)
+///     .open("mydb.redb")?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct RedbOptions {
+    cache_size: Option<usize>,
+    durability: Durability,
+    read_only: bool,
+}
+
+impl Default for RedbOptions {
+    fn default() -> Self {
+        Self {
+            cache_size: None,
+            durability: Durability::Immediate,
+            read_only: false,
+        }
+    }
+}
+
+impl RedbOptions {
+    /// Create options with redb's default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the size of the page cache in bytes.
+    pub fn with_cache_size(mut self, bytes: usize) -> Self {
+        self.cache_size = Some(bytes);
+        self
+    }
+
+    /// Set the durability of every write transaction.
+    ///
+    /// With [`Durability::None`], commits are only persisted by a later commit
+    /// with [`Durability::Immediate`].
+    pub fn with_durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Open an existing database without creating it and reject write transactions.
+    ///
+    /// This is only enforced by [`RedbDatabase`]: the file is still opened
+    /// for writing with redb's `Builder::open`, so it must be writable and is
+    /// locked against other processes, and [`RedbDatabase::inner`] can write
+    /// to it.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Open or create a persistent redb database at the specified path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file path where the database should be stored
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened, or if it
+    /// does not exist when opened read-only. Tables written by older versions
+    /// of this crate are not migrated, see
+    /// [`RedbDatabase::migrate_legacy_encoding`].
+    pub fn open(self, path: impl AsRef<Path>) -> Result<RedbDatabase, Error> {
+        let mut builder = Builder::new();
+
+        if let Some(bytes) = self.cache_size {
+            builder.set_cache_size(bytes);
+        }
+
+        let db = if self.read_only {
+            builder.open(path)?
+        } else {
+            builder.create(path)?
+        };
+
+        Ok(RedbDatabase {
+            inner: db,
+            durability: self.durability,
+            read_only: self.read_only,
+        })
+    }
+}
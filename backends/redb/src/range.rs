@@ -1,3 +1,6 @@
+use std::vec;
+
+use crepedb_core::types::Bytes;
 use redb::{Error, Range};
 
 use crate::types::BytesTy;
@@ -6,22 +9,48 @@ use crate::types::BytesTy;
 ///
 /// Implements the CrepeDB `Range` trait for redb's range iterator.
 pub struct RedbRange<'a> {
-    pub(crate) inner: Range<'a, BytesTy, BytesTy>,
+    pub(crate) inner: RangeInner<'a>,
+}
+
+/// A range of a table with the current encoding, or the collected pairs of a
+/// table with the legacy encoding.
+pub(crate) enum RangeInner<'a> {
+    Plain(Range<'a, BytesTy, BytesTy>),
+    Legacy(vec::IntoIter<(Bytes, Bytes)>),
 }
 
 impl<'a> crepedb_core::backend::Range<Error> for RedbRange<'a> {
-    fn back(&mut self) -> Result<Option<(crepedb_core::types::Bytes, crepedb_core::types::Bytes)>, Error> {
-        let r = self.inner.next();
+    fn back(
+        &mut self,
+    ) -> Result<Option<(crepedb_core::types::Bytes, crepedb_core::types::Bytes)>, Error> {
+        let range = match &mut self.inner {
+            RangeInner::Plain(range) => range,
+            RangeInner::Legacy(pairs) => return Ok(pairs.next()),
+        };
+
+        let r = range.next();
 
         if let Some(r) = r {
             let r = r?;
 
-            let key = r.0.value();
-            let value = r.1.value();
+            let key = r.0.value().to_vec();
+            let value = r.1.value().to_vec();
 
             Ok(Some((key, value)))
         } else {
             Ok(None)
         }
     }
+
+    fn back_key(&mut self) -> Result<Option<crepedb_core::types::Bytes>, Error> {
+        let range = match &mut self.inner {
+            RangeInner::Plain(range) => range,
+            RangeInner::Legacy(pairs) => return Ok(pairs.next().map(|(key, _)| key)),
+        };
+
+        match range.next() {
+            Some(r) => Ok(Some(r?.0.value().to_vec())),
+            None => Ok(None),
+        }
+    }
 }
@@ -1,7 +1,11 @@
 use crepedb_core::backend::ReadTxn;
-use redb::{Error, ReadTransaction, TableDefinition};
+use redb::{Error, ReadTransaction, TableDefinition, TableError};
 
-use crate::RedbReadTable;
+use crate::{
+    table::ReadOnly,
+    types::{BytesTy, LegacyBytesTy},
+    RedbReadTable,
+};
 
 /// A read transaction wrapper for redb.
 ///
@@ -18,8 +22,16 @@ impl ReadTxn<Error> for RedbReadTxn {
 
     fn open_table(&self, table: &str) -> Result<Self::Table<'_>, Error> {
         let name = table.into();
-        let definition = TableDefinition::new(table);
-        let inner = self.inner.open_table(definition)?;
+        let definition = TableDefinition::<BytesTy, BytesTy>::new(table);
+
+        let inner = match self.inner.open_table(definition) {
+            Ok(table) => ReadOnly::Plain(table),
+            Err(TableError::TableTypeMismatch { .. }) => {
+                let definition = TableDefinition::<LegacyBytesTy, LegacyBytesTy>::new(table);
+                ReadOnly::Legacy(self.inner.open_table(definition)?)
+            }
+            Err(e) => return Err(e.into()),
+        };
 
         Ok(RedbReadTable { inner, name })
     }
@@ -35,6 +47,6 @@ mod tests {
 
         let backend = RedbDatabase::memory().unwrap();
 
-        crepedb_core::read_tests::test_read(backend).unwrap();
+        crepedb_backend_tests::suite::test_read(backend).unwrap();
     }
 }
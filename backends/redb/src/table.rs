@@ -1,41 +1,84 @@
-use crepedb_core::{
-    backend::{ReadTable, WriteTable},
-    types::Bytes,
-};
+use std::ops::Bound;
+
+use crepedb_core::backend::{ReadTable, WriteTable};
 use redb::{Error, ReadOnlyTable, ReadableTable, Table, TableHandle};
 
-use crate::{types::BytesTy, RedbRange};
+use crate::{
+    range::RangeInner,
+    types::{BytesTy, LegacyBytesTy, RedbValue, ValueGuard},
+    RedbRange,
+};
 
 /// A read-only table wrapper for redb.
 ///
 /// Implements the CrepeDB `ReadTable` trait for redb's `ReadOnlyTable`.
+///
+/// Tables with the legacy encoding are read as well, so that databases can be
+/// inspected before they are migrated. Their keys are not ordered
+/// lexicographically, so a range over such a table reads the whole table.
 pub struct RedbReadTable {
-    pub(crate) inner: ReadOnlyTable<BytesTy, BytesTy>,
+    pub(crate) inner: ReadOnly,
     pub(crate) name: String,
 }
 
+/// A table of a read transaction, with the current or the legacy encoding.
+pub(crate) enum ReadOnly {
+    Plain(ReadOnlyTable<BytesTy, BytesTy>),
+    Legacy(ReadOnlyTable<LegacyBytesTy, LegacyBytesTy>),
+}
+
 impl ReadTable<Error> for RedbReadTable {
     type Range<'c>
         = RedbRange<'c>
     where
         Self: 'c;
 
+    type Value<'c>
+        = RedbValue<'c>
+    where
+        Self: 'c;
+
     fn name(&self) -> &str {
         &self.name
     }
 
-    fn get(&self, key: Bytes) -> Result<Option<crepedb_core::types::Bytes>, Error> {
-        if let Some(r) = self.inner.get(key)? {
-            Ok(Some(r.value()))
-        } else {
-            Ok(None)
-        }
+    fn get(&self, key: &[u8]) -> Result<Option<crepedb_core::types::Bytes>, Error> {
+        Ok(self.get_ref(key)?.map(|value| value.as_ref().to_vec()))
     }
 
-    fn range(&self, begin: Bytes, end: Bytes) -> Result<Self::Range<'_>, Error> {
-        let r = self.inner.range(begin..end)?;
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, Error> {
+        let value = match &self.inner {
+            ReadOnly::Plain(table) => table.get(key)?.map(ValueGuard::Plain),
+            ReadOnly::Legacy(table) => table
+                .get(key.to_vec())?
+                .map(|value| ValueGuard::Legacy(value.value())),
+        };
 
-        Ok(RedbRange { inner: r })
+        Ok(value.map(RedbValue))
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, Error> {
+        let inner = match &self.inner {
+            ReadOnly::Plain(table) => RangeInner::Plain(table.range::<&[u8]>(bounds(begin, end))?),
+            ReadOnly::Legacy(table) => {
+                let mut pairs = Vec::new();
+
+                for item in table.iter()? {
+                    let (key, value) = item?;
+                    let key = key.value();
+
+                    if key.as_slice() >= begin && end.is_none_or(|end| key.as_slice() < end) {
+                        pairs.push((key, value.value()));
+                    }
+                }
+
+                pairs.sort();
+
+                RangeInner::Legacy(pairs.into_iter())
+            }
+        };
+
+        Ok(RedbRange { inner })
     }
 }
 
@@ -52,35 +95,57 @@ impl<'a> ReadTable<Error> for RedbWriteTable<'a> {
     where
         Self: 'c;
 
+    type Value<'c>
+        = RedbValue<'c>
+    where
+        Self: 'c;
+
     fn name(&self) -> &str {
         self.inner.name()
     }
 
-    fn get(&self, key: Bytes) -> Result<Option<crepedb_core::types::Bytes>, Error> {
+    fn get(&self, key: &[u8]) -> Result<Option<crepedb_core::types::Bytes>, Error> {
         if let Some(r) = self.inner.get(key)? {
-            Ok(Some(r.value()))
+            Ok(Some(r.value().to_vec()))
         } else {
             Ok(None)
         }
     }
 
-    fn range(&self, begin: Bytes, end: Bytes) -> Result<Self::Range<'_>, Error> {
-        let r = self.inner.range(begin..end)?;
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, Error> {
+        Ok(self
+            .inner
+            .get(key)?
+            .map(|r| RedbValue(ValueGuard::Plain(r))))
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, Error> {
+        let r = self.inner.range::<&[u8]>(bounds(begin, end))?;
 
-        Ok(RedbRange { inner: r })
+        Ok(RedbRange {
+            inner: RangeInner::Plain(r),
+        })
     }
 }
 
 impl<'a> WriteTable<Error> for RedbWriteTable<'a> {
-    fn set(&mut self, key: Bytes, value: Bytes) -> Result<(), Error> {
+    fn set(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
         self.inner.insert(key, value)?;
 
         Ok(())
     }
 
-    fn del(&mut self, key: Bytes) -> Result<(), Error> {
+    fn del(&mut self, key: &[u8]) -> Result<(), Error> {
         self.inner.remove(key)?;
 
         Ok(())
     }
 }
+
+/// The bounds of a range from `begin` to `end`, unbounded for `None`.
+fn bounds<'a>(begin: &'a [u8], end: Option<&'a [u8]>) -> (Bound<&'a [u8]>, Bound<&'a [u8]>) {
+    (
+        Bound::Included(begin),
+        end.map_or(Bound::Unbounded, Bound::Excluded),
+    )
+}
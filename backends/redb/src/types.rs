@@ -4,22 +4,26 @@
 //! type system.
 
 use crepedb_core::types::Bytes;
-use redb::{Key, Value};
+use redb::{AccessGuard, Key, Value};
 
 /// A type adapter for using CrepeDB's `Bytes` type with redb.
 ///
-/// This struct implements redb's `Key` and `Value` traits, allowing
-/// `Vec<u8>` to be used as both keys and values in redb tables.
+/// This struct implements redb's `Key` and `Value` traits for byte slices,
+/// which are borrowed from redb's pages when read.
+///
+/// The bytes are stored as they are, with the encoding and ordering of redb's
+/// `&[u8]`. redb's own `Vec<u8>` encoding starts with the length, which would
+/// order shorter keys before longer ones instead of lexicographically.
 #[derive(Debug)]
 pub struct BytesTy;
 
 impl Value for BytesTy {
-    type AsBytes<'a> = <Bytes as Value>::AsBytes<'a>;
+    type AsBytes<'a> = &'a [u8];
 
-    type SelfType<'a> = <Bytes as Value>::SelfType<'a>;
+    type SelfType<'a> = &'a [u8];
 
     fn type_name() -> redb::TypeName {
-        crepedb_core::types::Bytes::type_name()
+        <&[u8]>::type_name()
     }
 
     fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a>
@@ -27,18 +31,18 @@ impl Value for BytesTy {
         Self: 'a,
         Self: 'b,
     {
-        Bytes::as_bytes(value)
+        value
     }
 
     fn from_bytes<'a>(data: &'a [u8]) -> Self::SelfType<'a>
     where
         Self: 'a,
     {
-        Bytes::from_bytes(data)
+        data
     }
 
     fn fixed_width() -> Option<usize> {
-        Bytes::fixed_width()
+        None
     }
 }
 
@@ -47,3 +51,67 @@ impl Key for BytesTy {
         data1.cmp(data2)
     }
 }
+
+/// The encoding of keys and values written by older versions of this crate.
+///
+/// This is redb's `Vec<u8>` encoding, a length followed by the bytes, and keys
+/// are ordered by their encoding, so shorter keys come first. Such tables can
+/// still be read, and are re-encoded by
+/// [`RedbDatabase::migrate_legacy_encoding`](crate::RedbDatabase::migrate_legacy_encoding).
+#[derive(Debug)]
+pub(crate) struct LegacyBytesTy;
+
+impl Value for LegacyBytesTy {
+    type AsBytes<'a> = <Vec<u8> as Value>::AsBytes<'a>;
+
+    type SelfType<'a> = <Vec<u8> as Value>::SelfType<'a>;
+
+    fn type_name() -> redb::TypeName {
+        <Vec<u8>>::type_name()
+    }
+
+    fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a>
+    where
+        Self: 'a,
+        Self: 'b,
+    {
+        <Vec<u8>>::as_bytes(value)
+    }
+
+    fn from_bytes<'a>(data: &'a [u8]) -> Self::SelfType<'a>
+    where
+        Self: 'a,
+    {
+        <Vec<u8>>::from_bytes(data)
+    }
+
+    fn fixed_width() -> Option<usize> {
+        None
+    }
+}
+
+impl Key for LegacyBytesTy {
+    fn compare(data1: &[u8], data2: &[u8]) -> std::cmp::Ordering {
+        data1.cmp(data2)
+    }
+}
+
+/// A value borrowed from a redb table.
+///
+/// Keeps the page holding the value alive until it is dropped. Values of
+/// tables with the legacy encoding are copied instead.
+pub struct RedbValue<'a>(pub(crate) ValueGuard<'a>);
+
+pub(crate) enum ValueGuard<'a> {
+    Plain(AccessGuard<'a, BytesTy>),
+    Legacy(Bytes),
+}
+
+impl<'a> AsRef<[u8]> for RedbValue<'a> {
+    fn as_ref(&self) -> &[u8] {
+        match &self.0 {
+            ValueGuard::Plain(guard) => guard.value(),
+            ValueGuard::Legacy(value) => value,
+        }
+    }
+}
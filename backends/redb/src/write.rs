@@ -1,12 +1,15 @@
 use crepedb_core::backend::WriteTxn;
 use redb::{Error, TableDefinition, WriteTransaction};
 
-use crate::RedbWriteTable;
+use crate::{types::BytesTy, RedbWriteTable};
 
 /// A write transaction wrapper for redb.
 ///
 /// Implements the CrepeDB `WriteTxn` trait, providing read-write access to tables
 /// and the ability to commit changes.
+///
+/// Savepoints of redb can only be taken before a transaction writes anything,
+/// so savepoints of CrepeDB are kept in its undo log instead.
 pub struct RedbWriteTxn {
     pub inner: WriteTransaction,
 }
@@ -24,6 +27,17 @@ impl WriteTxn<Error> for RedbWriteTxn {
         Ok(RedbWriteTable { inner: table })
     }
 
+    fn remove_table(&self, table: &str) -> Result<(), Error> {
+        let definition = TableDefinition::<BytesTy, BytesTy>::new(table);
+
+        // Empty the table first, deleting it would free pages written in this
+        // transaction as if they were committed.
+        self.inner.open_table(definition)?.retain(|_, _| false)?;
+        self.inner.delete_table(definition)?;
+
+        Ok(())
+    }
+
     fn commit(self) -> Result<(), Error> {
         self.inner.commit()?;
 
@@ -1,17 +1,94 @@
+use std::cmp::Reverse;
 use std::path::Path;
 use std::sync::Arc;
 
-use crepedb_core::backend::Backend;
-use rocksdb::{Error, OptimisticTransactionDB, Options};
+use crepedb_core::{backend::Backend, consts};
+use rocksdb::{
+    BoundColumnFamily, DBPinnableSlice, DBWithThreadMode, Direction, IteratorMode, MultiThreaded,
+    OptimisticTransactionDB, OptimisticTransactionOptions, WriteBatchWithTransaction, WriteOptions,
+    DEFAULT_COLUMN_FAMILY_NAME,
+};
 
-use crate::{RocksdbReadTxn, RocksdbWriteTxn};
+use crate::{RocksdbError, RocksdbOptions, RocksdbReadTxn, RocksdbWriteTxn, TableOptions};
+
+/// The RocksDB database type used by this backend.
+///
+/// Column families are created on demand while transactions are alive, so the
+/// multi-threaded column family mode is required.
+pub type RocksdbInner = OptimisticTransactionDB<MultiThreaded>;
+
+/// The RocksDB database type of a database opened read-only.
+///
+/// RocksDB cannot open an optimistic transaction database read-only, so a
+/// plain database is opened instead.
+pub type RocksdbReadOnlyInner = DBWithThreadMode<MultiThreaded>;
+
+/// The key-value pairs of a RocksDB iterator.
+type KeyValues<'a> = Box<dyn Iterator<Item = Result<(Box<[u8]>, Box<[u8]>), rocksdb::Error>> + 'a>;
+
+/// The underlying database of a [`RocksdbDatabase`], read by read transactions.
+#[derive(Clone)]
+pub(crate) enum Db {
+    Transaction(Arc<RocksdbInner>),
+    ReadOnly(Arc<RocksdbReadOnlyInner>),
+}
+
+impl Db {
+    pub(crate) fn cf_handle(&self, name: &str) -> Option<Arc<BoundColumnFamily<'_>>> {
+        match self {
+            Self::Transaction(db) => db.cf_handle(name),
+            Self::ReadOnly(db) => db.cf_handle(name),
+        }
+    }
+
+    pub(crate) fn get_cf(
+        &self,
+        cf: &Arc<BoundColumnFamily<'_>>,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, rocksdb::Error> {
+        match self {
+            Self::Transaction(db) => db.get_cf(cf, key),
+            Self::ReadOnly(db) => db.get_cf(cf, key),
+        }
+    }
+
+    pub(crate) fn get_pinned_cf(
+        &self,
+        cf: &Arc<BoundColumnFamily<'_>>,
+        key: &[u8],
+    ) -> Result<Option<DBPinnableSlice<'_>>, rocksdb::Error> {
+        match self {
+            Self::Transaction(db) => db.get_pinned_cf(cf, key),
+            Self::ReadOnly(db) => db.get_pinned_cf(cf, key),
+        }
+    }
+
+    pub(crate) fn iterator_cf<'a>(
+        &'a self,
+        cf: &Arc<BoundColumnFamily<'_>>,
+        mode: IteratorMode<'_>,
+    ) -> KeyValues<'a> {
+        match self {
+            Self::Transaction(db) => Box::new(db.iterator_cf(cf, mode)),
+            Self::ReadOnly(db) => Box::new(db.iterator_cf(cf, mode)),
+        }
+    }
+}
+
+/// Number of keys moved per write batch by [`RocksdbDatabase::migrate_prefix_layout`].
+pub const MIGRATE_BATCH_SIZE: usize = 10_000;
 
 /// A CrepeDB backend implementation using RocksDB.
 ///
 /// This struct wraps a RocksDB `OptimisticTransactionDB` and implements the CrepeDB `Backend` trait,
 /// allowing RocksDB to be used as the storage engine for CrepeDB.
+///
+/// Every CrepeDB table is mapped to a column family with the same name. Column
+/// families are created the first time a table is opened in a write transaction.
 pub struct RocksdbDatabase {
-    inner: Arc<OptimisticTransactionDB>,
+    pub(crate) inner: Db,
+    pub(crate) tables: Arc<TableOptions>,
+    pub(crate) write_options: WriteOptions,
 }
 
 impl RocksdbDatabase {
@@ -32,35 +109,245 @@ impl RocksdbDatabase {
     /// ```ignore
     /// let db = RocksdbDatabase::open_or_create("mydb")?;
     /// ```
-    pub fn open_or_create(path: impl AsRef<Path>) -> Result<Self, Error> {
-        let mut opts = Options::default();
-        opts.create_if_missing(true);
+    pub fn open_or_create(path: impl AsRef<Path>) -> Result<Self, RocksdbError> {
+        RocksdbOptions::new().open(path)
+    }
 
-        let db = OptimisticTransactionDB::open(&opts, path)?;
-        Ok(Self {
-            inner: Arc::new(db),
-        })
+    /// Create an in-memory RocksDB database.
+    ///
+    /// The database lives in RocksDB's in-memory `Env` and will be lost when dropped.
+    /// This is useful for testing or temporary storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let db = RocksdbDatabase::memory()?;
+    /// ```
+    pub fn memory() -> Result<Self, RocksdbError> {
+        RocksdbOptions::new().memory()
+    }
+
+    /// Open or create a persistent RocksDB database with per-table column family options.
+    ///
+    /// Existing column families are reopened with the options from `tables`, and
+    /// column families created later use them as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory path where the database should be stored
+    /// * `tables` - The column family options for CrepeDB tables
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut opts = Options::default();
+    /// opts.set_write_buffer_size(256 << 20);
+    ///
+    /// let tables = TableOptions::default().with_table("accounts", opts);
+    /// let db = RocksdbDatabase::open_with_table_options("mydb", tables)?;
+    /// ```
+    pub fn open_with_table_options(
+        path: impl AsRef<Path>,
+        tables: TableOptions,
+    ) -> Result<Self, RocksdbError> {
+        RocksdbOptions::new().with_table_options(tables).open(path)
+    }
+
+    /// Get a reference to the underlying RocksDB database, `None` if it was
+    /// opened read-only.
+    pub fn inner(&self) -> Option<&RocksdbInner> {
+        match &self.inner {
+            Db::Transaction(db) => Some(db),
+            Db::ReadOnly(_) => None,
+        }
+    }
+
+    /// The underlying database, or an error if it was opened read-only.
+    fn writable(&self) -> Result<&Arc<RocksdbInner>, RocksdbError> {
+        match &self.inner {
+            Db::Transaction(db) => Ok(db),
+            Db::ReadOnly(_) => Err(RocksdbError::ReadOnly),
+        }
+    }
+
+    /// Drop a table by dropping its column family.
+    ///
+    /// This removes all data of the table without scanning it. The table must
+    /// also be removed from CrepeDB's meta table by the caller if it is a user table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the column family does not exist or cannot be dropped.
+    pub fn drop_table(&self, table: &str) -> Result<(), RocksdbError> {
+        Ok(self.writable()?.drop_cf(table)?)
     }
+
+    /// Migrate data written by the prefix layout into per-table column families.
+    ///
+    /// Older versions of this backend stored every table in the default column
+    /// family with keys prefixed by `name:`. This moves each such key into the
+    /// column family of its table and removes it from the default column family.
+    ///
+    /// Table names are taken from the internal tables and the entries of the meta
+    /// table. Because the prefix layout is ambiguous when one table name is a
+    /// prefix of another followed by `:`, keys are assigned to the longest matching
+    /// table name. Keys that do not belong to any known table are left untouched.
+    ///
+    /// Keys are moved in batches of [`MIGRATE_BATCH_SIZE`], and each batch
+    /// writes a key to its column family and removes it from the default one
+    /// atomically. The entries of the meta table that were moved already are
+    /// read as well, so an interrupted migration is finished by running it
+    /// again. Migration is idempotent and can be run on every open.
+    ///
+    /// # Returns
+    ///
+    /// The number of keys that were moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading, creating column families or writing fails.
+    pub fn migrate_prefix_layout(&self) -> Result<usize, RocksdbError> {
+        let db = self.writable()?;
+
+        let meta_prefix = table_prefix(consts::META_TABLE);
+
+        let mut names: Vec<String> = consts::INTERNAL_TABLES
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        for item in db.iterator(IteratorMode::From(&meta_prefix, Direction::Forward)) {
+            let (key, _) = item?;
+
+            if !key.starts_with(&meta_prefix) {
+                break;
+            }
+
+            names.push(String::from_utf8_lossy(&key[meta_prefix.len()..]).into_owned());
+        }
+
+        // Tables whose meta entries were moved by an interrupted migration.
+        if let Some(meta) = db.cf_handle(consts::META_TABLE) {
+            for item in db.iterator_cf(&meta, IteratorMode::Start) {
+                let (key, _) = item?;
+                names.push(String::from_utf8_lossy(&key).into_owned());
+            }
+        }
+
+        names.sort();
+        names.dedup();
+
+        // Longest name first, so `a:b` wins over `a` for the key `a:b:c`.
+        names.sort_by_key(|name| Reverse(name.len()));
+
+        let cfs = names
+            .iter()
+            .map(|name| Ok((table_prefix(name), ensure_table(db, &self.tables, name)?)))
+            .collect::<Result<Vec<_>, RocksdbError>>()?;
+
+        let default = db
+            .cf_handle(DEFAULT_COLUMN_FAMILY_NAME)
+            .expect("default column family always exists");
+
+        let mut moved = 0;
+        let mut batch = WriteBatchWithTransaction::<true>::default();
+
+        for item in db.iterator_cf(&default, IteratorMode::Start) {
+            let (key, value) = item?;
+
+            let Some((prefix, cf)) = cfs.iter().find(|(prefix, _)| key.starts_with(prefix)) else {
+                continue;
+            };
+
+            batch.put_cf(cf, &key[prefix.len()..], &value);
+            batch.delete_cf(&default, &key);
+
+            moved += 1;
+
+            if moved % MIGRATE_BATCH_SIZE == 0 {
+                db.write(std::mem::take(&mut batch))?;
+            }
+        }
+
+        if !batch.is_empty() {
+            db.write(batch)?;
+        }
+
+        log::info!("Migrated {moved} keys from prefix layout to column families");
+
+        Ok(moved)
+    }
+}
+
+/// Build the key prefix of a table in the prefix layout: `name:`.
+fn table_prefix(name: &str) -> Vec<u8> {
+    let mut prefix = Vec::with_capacity(name.len() + 1);
+    prefix.extend_from_slice(name.as_bytes());
+    prefix.push(b':');
+    prefix
+}
+
+/// Get the column family of a table, creating it if it does not exist.
+///
+/// Fails if the column family is dropped again before its handle is taken.
+pub(crate) fn ensure_table<'a>(
+    db: &'a RocksdbInner,
+    tables: &TableOptions,
+    name: &str,
+) -> Result<Arc<BoundColumnFamily<'a>>, RocksdbError> {
+    if let Some(cf) = db.cf_handle(name) {
+        return Ok(cf);
+    }
+
+    match db.create_cf(name, tables.get(name)) {
+        Ok(()) => {}
+        // Another transaction may have created it concurrently.
+        Err(_) if db.cf_handle(name).is_some() => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    db.cf_handle(name)
+        .ok_or_else(|| RocksdbError::TableDropped(name.to_string()))
 }
 
 impl Backend for RocksdbDatabase {
-    type Error = Error;
+    type Error = RocksdbError;
 
     type ReadTxn<'a> = RocksdbReadTxn;
 
     type WriteTxn<'a> = RocksdbWriteTxn<'a>;
 
+    fn is_conflict(error: &Self::Error) -> bool {
+        error.is_conflict()
+    }
+
     fn read_txn(&self) -> Result<Self::ReadTxn<'_>, Self::Error> {
         Ok(RocksdbReadTxn {
-            db: Arc::clone(&self.inner),
+            db: self.inner.clone(),
         })
     }
 
     fn write_txn(&self) -> Result<Self::WriteTxn<'_>, Self::Error> {
-        let txn = self.inner.transaction();
+        let db = self.writable()?;
+
+        // Validate against the state at the start of the transaction, so that
+        // a write committed between a read and the commit is a conflict.
+        let mut options = OptimisticTransactionOptions::default();
+        options.set_snapshot(true);
+
+        let txn = db.transaction_opt(&self.write_options, &options);
         Ok(RocksdbWriteTxn {
             inner: txn,
-            db: Arc::clone(&self.inner),
+            db: Arc::clone(db),
+            tables: Arc::clone(&self.tables),
         })
     }
 }
@@ -0,0 +1,42 @@
+use std::fmt::{self, Display};
+
+use rocksdb::ErrorKind;
+
+/// Errors returned by the RocksDB backend.
+#[derive(Debug)]
+pub enum RocksdbError {
+    /// Error returned by RocksDB.
+    Rocksdb(rocksdb::Error),
+    /// The column family of a table was dropped while the table was opened.
+    TableDropped(String),
+    /// The database was opened read-only.
+    ReadOnly,
+}
+
+impl RocksdbError {
+    /// Whether the error is a conflict with a concurrent write transaction.
+    pub fn is_conflict(&self) -> bool {
+        match self {
+            Self::Rocksdb(e) => matches!(e.kind(), ErrorKind::Busy | ErrorKind::TryAgain),
+            _ => false,
+        }
+    }
+}
+
+impl Display for RocksdbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rocksdb(e) => write!(f, "{e}"),
+            Self::TableDropped(name) => write!(f, "Table dropped while opened: {name:?}"),
+            Self::ReadOnly => write!(f, "Database is read-only"),
+        }
+    }
+}
+
+impl std::error::Error for RocksdbError {}
+
+impl From<rocksdb::Error> for RocksdbError {
+    fn from(e: rocksdb::Error) -> Self {
+        Self::Rocksdb(e)
+    }
+}
@@ -13,6 +13,9 @@
 //! - **Persistent Storage**: Data is stored on disk
 //! - **In-Memory Mode**: Support for temporary in-memory databases
 //! - **High Performance**: Optimized for fast reads and writes
+//! - **Column Family per Table**: Each table has its own column family and options
+//! - **Configurable**: Block cache size, compression, sync writes and read-only open via
+//!   [`RocksdbOptions`]
 //!
 //! ## Example
 //!
@@ -23,11 +26,31 @@
 //! // Open a persistent database
 //! let backend = RocksdbDatabase::open_or_create("mydb")?;
 //! let db = CrepeDB::new(backend);
+//!
+//! // Or create an in-memory database
+//! let backend = RocksdbDatabase::memory()?;
+//! let db = CrepeDB::new(backend);
+//! ```
+//!
+//! ## Migrating from the prefix layout
+//!
+//! Older versions stored all tables in the default column family with keys
+//! prefixed by `table:`. Such databases can be converted in place:
+//!
+//! ```ignore
+//! let backend = RocksdbDatabase::open_or_create("mydb")?;
+//! backend.migrate_prefix_layout()?;
 //! ```
 
 mod db;
 pub use db::*;
 
+mod error;
+pub use error::*;
+
+mod options;
+pub use options::*;
+
 mod read;
 pub use read::*;
 
@@ -42,128 +65,167 @@ pub use range::*;
 
 #[cfg(test)]
 mod tests {
-    use crate::RocksdbDatabase;
+    use crate::{DBCompressionType, RocksdbDatabase, RocksdbOptions};
     use std::path::PathBuf;
 
-    fn create_temp_db() -> (RocksdbDatabase, PathBuf) {
+    fn temp_path() -> PathBuf {
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_nanos();
-        let temp_dir = std::env::temp_dir().join(format!(
+        std::env::temp_dir().join(format!(
             "crepedb-rocksdb-test-{}-{}",
             std::process::id(),
             timestamp
-        ));
-        let backend = RocksdbDatabase::open_or_create(&temp_dir).unwrap();
-        (backend, temp_dir)
+        ))
     }
 
-    #[test]
-    fn test_db_10() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let (backend, temp_dir) = create_temp_db();
-
-        let result = crepedb_core::tests::test_db_10(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
+    crepedb_backend_tests::backend_tests!(RocksdbDatabase::memory().unwrap());
 
     #[test]
-    fn test_read() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let (backend, temp_dir) = create_temp_db();
-
-        let result = crepedb_core::read_tests::test_read(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
+    fn test_table_name_prefix() {
+        use crepedb_core::backend::{Backend, Range, ReadTable, WriteTable, WriteTxn};
 
-        result.unwrap();
-    }
+        let backend = RocksdbDatabase::memory().unwrap();
 
-    #[test]
-    fn test_snapshot_isolation() {
-        let _ = env_logger::builder().is_test(true).try_init();
+        let txn = backend.write_txn().unwrap();
+        {
+            let mut a = txn.open_table("a").unwrap();
+            a.set(b"b:1", &[1]).unwrap();
 
-        let (backend, temp_dir) = create_temp_db();
+            let mut ab = txn.open_table("a:b").unwrap();
+            ab.set(b"1", &[2]).unwrap();
+        }
+        txn.commit().unwrap();
 
-        let result = crepedb_core::read_tests::test_snapshot_isolation(backend);
+        let txn = backend.write_txn().unwrap();
+        {
+            let a = txn.open_table("a").unwrap();
+            assert_eq!(a.get(b"1").unwrap(), None);
 
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
+            let ab = txn.open_table("a:b").unwrap();
+            assert_eq!(ab.get(b"1").unwrap(), Some(vec![2]));
 
-        result.unwrap();
+            let mut range = ab.range(&[], Some(&[0xff])).unwrap();
+            assert_eq!(range.back().unwrap(), Some((b"1".to_vec(), vec![2])));
+            assert_eq!(range.back().unwrap(), None);
+        }
     }
 
     #[test]
-    fn test_multiple_keys() {
-        let _ = env_logger::builder().is_test(true).try_init();
+    fn test_migrate_prefix_layout() {
+        use crepedb_core::backend::{Backend, ReadTable, ReadTxn};
 
-        let (backend, temp_dir) = create_temp_db();
+        let backend = RocksdbDatabase::memory().unwrap();
 
-        let result = crepedb_core::read_tests::test_multiple_keys(backend);
+        let db = backend.inner().unwrap();
+        db.put(b"__crepe_meta:t", [2]).unwrap();
+        db.put(b"t:k", b"v").unwrap();
+        db.put(b"unknown", b"x").unwrap();
 
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
+        assert_eq!(backend.migrate_prefix_layout().unwrap(), 2);
+        assert_eq!(backend.migrate_prefix_layout().unwrap(), 0);
 
-        result.unwrap();
-    }
+        let txn = backend.read_txn().unwrap();
+        let t = txn.open_table("t").unwrap();
+        assert_eq!(t.get(b"k").unwrap(), Some(b"v".to_vec()));
 
-    #[test]
-    fn test_error_handling() {
-        let _ = env_logger::builder().is_test(true).try_init();
+        let meta = txn.open_table("__crepe_meta").unwrap();
+        assert_eq!(meta.get(b"t").unwrap(), Some(vec![2]));
 
-        let (backend, temp_dir) = create_temp_db();
+        assert_eq!(db.get(b"t:k").unwrap(), None);
+        assert_eq!(db.get(b"unknown").unwrap(), Some(b"x".to_vec()));
+        drop(txn);
 
-        let result = crepedb_core::read_tests::test_error_handling(backend);
+        // A migration interrupted after the meta entry of `u` was moved.
+        db.put(b"u:k", b"w").unwrap();
+        let meta = db.cf_handle("__crepe_meta").unwrap();
+        db.put_cf(&meta, b"u", [2]).unwrap();
 
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
+        assert_eq!(backend.migrate_prefix_layout().unwrap(), 1);
 
-        result.unwrap();
+        let txn = backend.read_txn().unwrap();
+        let u = txn.open_table("u").unwrap();
+        assert_eq!(u.get(b"k").unwrap(), Some(b"w".to_vec()));
+        assert_eq!(db.get(b"u:k").unwrap(), None);
     }
 
     #[test]
-    fn test_multiple_tables() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let (backend, temp_dir) = create_temp_db();
-
-        let result = crepedb_core::read_tests::test_multiple_tables(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
+    fn test_concurrent_forks() {
+        use crepedb_core::CrepeDB;
+
+        let db = CrepeDB::new(RocksdbDatabase::memory().unwrap());
+
+        let txn = db.write(None).unwrap();
+        txn.create_versioned_table("t").unwrap();
+        let root = txn.commit().unwrap();
+
+        // Forks in the same lane allocate the same snapshot ID.
+        let a = db.write_in_lane(Some(root.clone()), 1).unwrap();
+        let b = db.write_in_lane(Some(root.clone()), 1).unwrap();
+        a.open_table("t").unwrap().set(vec![1], vec![1]).unwrap();
+        b.open_table("t").unwrap().set(vec![1], vec![2]).unwrap();
+        let a = a.commit().unwrap();
+        let e = b.commit().unwrap_err();
+        assert!(db.is_conflict(&e));
+
+        let txn = db.read(Some(a)).unwrap();
+        let table = txn.open_table("t").unwrap();
+        assert_eq!(table.get(vec![1]).unwrap(), Some(vec![1]));
+
+        // Writers sharing a lane retry until every snapshot is committed.
+        let snapshots = std::thread::scope(|s| {
+            let writers: Vec<_> = (0..2u8)
+                .map(|i| {
+                    let db = &db;
+                    let root = root.clone();
+
+                    s.spawn(move || {
+                        (0..8u8)
+                            .map(|j| {
+                                db.write_with_retry(root.clone(), 2, |txn| {
+                                    txn.open_table("t")?.set(vec![i], vec![j])
+                                })
+                                .unwrap()
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            writers
+                .into_iter()
+                .map(|w| w.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        for (i, snapshots) in snapshots.into_iter().enumerate() {
+            for (j, snapshot) in snapshots.into_iter().enumerate() {
+                let txn = db.read(Some(snapshot)).unwrap();
+                let table = txn.open_table("t").unwrap();
+                assert_eq!(table.get(vec![i as u8]).unwrap(), Some(vec![j as u8]));
+            }
+        }
+
+        let report = db.verify().unwrap();
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.snapshots, 18);
     }
 
     #[test]
-    fn test_basic_table_type() {
+    fn test_options() {
         let _ = env_logger::builder().is_test(true).try_init();
 
-        let (backend, temp_dir) = create_temp_db();
-
-        let result = crepedb_core::read_tests::test_basic_table_type(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_edge_cases() {
-        let _ = env_logger::builder().is_test(true).try_init();
+        let temp_dir = temp_path();
 
-        let (backend, temp_dir) = create_temp_db();
+        let backend = RocksdbOptions::new()
+            .with_cache_size(8 << 20)
+            .with_compression(DBCompressionType::None)
+            .with_sync(true)
+            .open(&temp_dir)
+            .unwrap();
 
-        let result = crepedb_core::read_tests::test_edge_cases(backend);
+        let result = crepedb_backend_tests::suite::test_read(backend);
 
         // Clean up
         let _ = std::fs::remove_dir_all(temp_dir);
@@ -172,100 +234,43 @@ mod tests {
     }
 
     #[test]
-    fn test_version_chain() {
-        let _ = env_logger::builder().is_test(true).try_init();
+    fn test_options_read_only() {
+        use crepedb_core::backend::{Backend, ReadTable, ReadTxn, WriteTable, WriteTxn};
 
-        let (backend, temp_dir) = create_temp_db();
+        use crate::RocksdbError;
 
-        let result = crepedb_core::read_tests::test_version_chain(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_delete_operations() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let (backend, temp_dir) = create_temp_db();
-
-        let result = crepedb_core::read_tests::test_delete_operations(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_root_snapshot() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let (backend, temp_dir) = create_temp_db();
-
-        let result = crepedb_core::read_tests::test_root_snapshot(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_transaction_lifecycle() {
         let _ = env_logger::builder().is_test(true).try_init();
 
-        let (backend, temp_dir) = create_temp_db();
-
-        let result = crepedb_core::read_tests::test_transaction_lifecycle(backend);
+        let temp_dir = temp_path();
+
+        let result = RocksdbOptions::new()
+            .with_read_only(true)
+            .open(&temp_dir)
+            .err();
+        assert!(result.is_some());
+
+        let backend = RocksdbOptions::new().open(&temp_dir).unwrap();
+        let txn = backend.write_txn().unwrap();
+        txn.open_table("t").unwrap().set(b"k", b"v").unwrap();
+        txn.commit().unwrap();
+
+        // A read-only open does not take the lock, so it can share the directory.
+        let read_only = RocksdbOptions::new()
+            .with_read_only(true)
+            .open(&temp_dir)
+            .unwrap();
+        let value = read_only
+            .read_txn()
+            .and_then(|txn| txn.open_table("t")?.get(b"k"));
+        let write_err = read_only.write_txn().err();
+        let inner = read_only.inner().is_none();
+        drop((backend, read_only));
 
         // Clean up
         let _ = std::fs::remove_dir_all(temp_dir);
 
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_mixed_operations() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let (backend, temp_dir) = create_temp_db();
-
-        let result = crepedb_core::read_tests::test_mixed_operations(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_parent_child_visibility() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let (backend, temp_dir) = create_temp_db();
-
-        let result = crepedb_core::read_tests::test_parent_child_visibility(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
-    }
-
-    #[test]
-    fn test_complex_branching() {
-        let _ = env_logger::builder().is_test(true).try_init();
-
-        let (backend, temp_dir) = create_temp_db();
-
-        let result = crepedb_core::read_tests::test_complex_branching(backend);
-
-        // Clean up
-        let _ = std::fs::remove_dir_all(temp_dir);
-
-        result.unwrap();
+        assert_eq!(value.unwrap(), Some(b"v".to_vec()));
+        assert!(matches!(write_err, Some(RocksdbError::ReadOnly)));
+        assert!(inner);
     }
 }
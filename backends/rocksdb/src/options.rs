@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use rocksdb::{
+    BlockBasedOptions, Cache, ColumnFamilyDescriptor, Env, Options, WriteOptions,
+    DEFAULT_COLUMN_FAMILY_NAME,
+};
+
+pub use rocksdb::DBCompressionType;
+
+use crate::{db::Db, RocksdbDatabase, RocksdbError, RocksdbInner, RocksdbReadOnlyInner};
+
+/// Column family options for CrepeDB tables.
+///
+/// Each CrepeDB table, including the internal `__crepe_*` tables, is stored in
+/// its own RocksDB column family. The options for a column family are taken from
+/// the per-table entry if one is set, otherwise the default options are used.
+#[derive(Clone, Default)]
+pub struct TableOptions {
+    default: Options,
+    tables: HashMap<String, Options>,
+}
+
+impl TableOptions {
+    /// Create table options using `default` for every table.
+    pub fn new(default: Options) -> Self {
+        Self {
+            default,
+            tables: HashMap::new(),
+        }
+    }
+
+    /// Set the column family options of a single table.
+    pub fn with_table(mut self, table: &str, options: Options) -> Self {
+        self.tables.insert(table.to_string(), options);
+        self
+    }
+
+    /// Get the column family options used by a table.
+    pub fn get(&self, table: &str) -> &Options {
+        self.tables.get(table).unwrap_or(&self.default)
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut Options> {
+        std::iter::once(&mut self.default).chain(self.tables.values_mut())
+    }
+}
+
+/// Options used to open a [`RocksdbDatabase`].
+///
+/// The cache size and compression apply to every column family and override
+/// the corresponding settings of the [`TableOptions`]. Compression algorithms
+/// other than [`DBCompressionType::None`] require the matching cargo feature
+/// (`snappy`, `lz4`, `zstd`, `zlib` or `bzip2`).
+///
+/// A database opened read-only rejects write transactions, migration and
+/// dropping tables.
+///
+/// # Example
+///
+/// ```ignore
+/// let db = RocksdbOptions::new()
+///     .with_cache_size(512 << 20)
+///     .with_compression(DBCompressionType::Lz4)
+///     .with_sync(true)
+///     .open("mydb")?;
+/// ```
+#[derive(Clone, Default)]
+pub struct RocksdbOptions {
+    cache_size: Option<usize>,
+    compression: Option<DBCompressionType>,
+    sync: bool,
+    read_only: bool,
+    tables: TableOptions,
+}
+
+impl RocksdbOptions {
+    /// Create options with RocksDB's default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the size of the block cache in bytes, shared by all tables.
+    pub fn with_cache_size(mut self, bytes: usize) -> Self {
+        self.cache_size = Some(bytes);
+        self
+    }
+
+    /// Set the compression algorithm of all tables.
+    pub fn with_compression(mut self, compression: DBCompressionType) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Sync the write-ahead log to disk on every commit.
+    ///
+    /// Without sync, commits survive a process crash but may be lost on a
+    /// machine crash.
+    pub fn with_sync(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Open an existing database without creating it and reject write transactions.
+    ///
+    /// The database is opened with RocksDB's read-only mode, so it does not
+    /// take the lock of the database directory and sees the data as of the
+    /// open.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Set the column family options of the tables.
+    pub fn with_table_options(mut self, tables: TableOptions) -> Self {
+        self.tables = tables;
+        self
+    }
+
+    /// Open or create a persistent RocksDB database at the specified path.
+    ///
+    /// Existing column families are reopened with the table options, and
+    /// column families created later use them as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory path where the database should be stored
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened, or if it
+    /// does not exist when opened read-only.
+    pub fn open(self, path: impl AsRef<Path>) -> Result<RocksdbDatabase, RocksdbError> {
+        self.open_with_env(path.as_ref(), None)
+    }
+
+    /// Create an in-memory RocksDB database.
+    ///
+    /// All files are kept in RocksDB's in-memory `Env`, so the data is lost
+    /// when the database is dropped. This is useful for testing or temporary storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the in-memory environment or the database cannot be created.
+    pub fn memory(self) -> Result<RocksdbDatabase, RocksdbError> {
+        // Each in-memory `Env` has its own file system, so a fixed path is fine.
+        self.open_with_env(Path::new("/crepedb"), Some(Env::mem_env()?))
+    }
+
+    fn open_with_env(self, path: &Path, env: Option<Env>) -> Result<RocksdbDatabase, RocksdbError> {
+        let mut tables = self.tables;
+
+        if let Some(bytes) = self.cache_size {
+            let cache = Cache::new_lru_cache(bytes);
+
+            let mut block = BlockBasedOptions::default();
+            block.set_block_cache(&cache);
+
+            tables
+                .iter_mut()
+                .for_each(|o| o.set_block_based_table_factory(&block));
+        }
+
+        if let Some(compression) = self.compression {
+            tables
+                .iter_mut()
+                .for_each(|o| o.set_compression_type(compression));
+        }
+
+        let mut opts = Options::default();
+        opts.create_if_missing(!self.read_only);
+        opts.create_missing_column_families(!self.read_only);
+
+        if let Some(env) = &env {
+            opts.set_env(env);
+        }
+
+        // A new database, including every in-memory one, only has the default
+        // column family. Listing fails for those, so it is only done for
+        // existing databases and its errors are returned.
+        let names = if env.is_none() && path.join("CURRENT").exists() {
+            RocksdbInner::list_cf(&opts, path)?
+        } else {
+            vec![DEFAULT_COLUMN_FAMILY_NAME.to_string()]
+        };
+
+        let inner = if self.read_only {
+            let db = RocksdbReadOnlyInner::open_cf_for_read_only(&opts, path, names, false)?;
+            Db::ReadOnly(Arc::new(db))
+        } else {
+            let descriptors = names.into_iter().map(|name| {
+                let options = tables.get(&name).clone();
+                ColumnFamilyDescriptor::new(name, options)
+            });
+
+            let db = RocksdbInner::open_cf_descriptors(&opts, path, descriptors)?;
+            Db::Transaction(Arc::new(db))
+        };
+
+        let mut write_options = WriteOptions::default();
+        write_options.set_sync(self.sync);
+
+        Ok(RocksdbDatabase {
+            inner,
+            tables: Arc::new(tables),
+            write_options,
+        })
+    }
+}
@@ -1,30 +1,37 @@
-use std::sync::Arc;
+use rocksdb::IteratorMode;
 
-use rocksdb::{Error, IteratorMode, OptimisticTransactionDB};
+use crate::{db::Db, RocksdbError};
 
 /// A range iterator wrapper for RocksDB.
 ///
-/// Implements the CrepeDB `Range` trait for RocksDB's iterator.
+/// Implements the CrepeDB `Range` trait for RocksDB's iterator over a table's column family.
 pub struct RocksdbRange {
-    pub(crate) db: Arc<OptimisticTransactionDB>,
+    pub(crate) db: Db,
+    pub(crate) name: String,
     pub(crate) begin: Vec<u8>,
-    pub(crate) end: Vec<u8>,
-    pub(crate) prefix_len: usize,
+    pub(crate) end: Option<Vec<u8>>,
     pub(crate) current: Option<usize>,
 }
 
-impl crepedb_core::backend::Range<Error> for RocksdbRange {
+impl crepedb_core::backend::Range<RocksdbError> for RocksdbRange {
     fn back(
         &mut self,
-    ) -> Result<Option<(crepedb_core::types::Bytes, crepedb_core::types::Bytes)>, Error> {
+    ) -> Result<Option<(crepedb_core::types::Bytes, crepedb_core::types::Bytes)>, RocksdbError>
+    {
+        // A table without column family has never been written.
+        let Some(cf) = self.db.cf_handle(&self.name) else {
+            return Ok(None);
+        };
+
         // Initialize iterator on first call
         if self.current.is_none() {
             self.current = Some(0);
         }
 
-        let iter = self
-            .db
-            .iterator(IteratorMode::From(&self.begin, rocksdb::Direction::Forward));
+        let iter = self.db.iterator_cf(
+            &cf,
+            IteratorMode::From(&self.begin, rocksdb::Direction::Forward),
+        );
 
         // Skip to current position
         let skip_count = self.current.unwrap();
@@ -34,21 +41,14 @@ impl crepedb_core::backend::Range<Error> for RocksdbRange {
             let (key, value) = result?;
 
             // Check if key is within range
-            if key.as_ref() >= self.end.as_slice() {
+            if self.end.as_deref().is_some_and(|end| key.as_ref() >= end) {
                 return Ok(None);
             }
 
             // Increment position for next call
             self.current = Some(skip_count + 1);
 
-            // Strip the table prefix from the key
-            let stripped_key = if key.len() > self.prefix_len {
-                key[self.prefix_len..].to_vec()
-            } else {
-                return Ok(None);
-            };
-
-            Ok(Some((stripped_key, value.to_vec())))
+            Ok(Some((key.to_vec(), value.to_vec())))
         } else {
             Ok(None)
         }
@@ -1,26 +1,23 @@
-use std::sync::Arc;
-
 use crepedb_core::backend::ReadTxn;
-use rocksdb::{Error, OptimisticTransactionDB};
 
-use crate::RocksdbReadTable;
+use crate::{db::Db, RocksdbError, RocksdbReadTable};
 
 /// A read transaction wrapper for RocksDB.
 ///
 /// Implements the CrepeDB `ReadTxn` trait, providing read-only access to tables.
 pub struct RocksdbReadTxn {
-    pub(crate) db: Arc<OptimisticTransactionDB>,
+    pub(crate) db: Db,
 }
 
-impl ReadTxn<Error> for RocksdbReadTxn {
+impl ReadTxn<RocksdbError> for RocksdbReadTxn {
     type Table<'b>
         = RocksdbReadTable
     where
         Self: 'b;
 
-    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, Error> {
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, RocksdbError> {
         Ok(RocksdbReadTable {
-            db: Arc::clone(&self.db),
+            db: self.db.clone(),
             name: table.to_string(),
         })
     }
@@ -45,7 +42,7 @@ mod tests {
         ));
         let backend = RocksdbDatabase::open_or_create(&temp_dir).unwrap();
 
-        let result = crepedb_core::read_tests::test_read(backend);
+        let result = crepedb_backend_tests::suite::test_read(backend);
 
         // Clean up
         let _ = std::fs::remove_dir_all(temp_dir);
@@ -4,56 +4,57 @@ use crepedb_core::{
     backend::{ReadTable, WriteTable},
     types::Bytes,
 };
-use rocksdb::{Error, OptimisticTransactionDB, Transaction};
+use rocksdb::{BoundColumnFamily, DBPinnableSlice, Transaction};
 
-use crate::RocksdbRange;
+use crate::{db::Db, RocksdbError, RocksdbInner, RocksdbRange};
 
 /// A read-only table wrapper for RocksDB.
 ///
-/// Implements the CrepeDB `ReadTable` trait for RocksDB.
+/// Implements the CrepeDB `ReadTable` trait for RocksDB. The table is backed by
+/// the column family with the same name.
 pub struct RocksdbReadTable {
-    pub(crate) db: Arc<OptimisticTransactionDB>,
+    pub(crate) db: Db,
     pub(crate) name: String,
 }
 
-impl RocksdbReadTable {
-    fn make_key(&self, key: &[u8]) -> Vec<u8> {
-        let mut full_key = Vec::with_capacity(self.name.len() + 1 + key.len());
-        full_key.extend_from_slice(self.name.as_bytes());
-        full_key.push(b':');
-        full_key.extend_from_slice(key);
-        full_key
-    }
-}
-
-impl ReadTable<Error> for RocksdbReadTable {
+impl ReadTable<RocksdbError> for RocksdbReadTable {
     type Range<'c>
         = RocksdbRange
     where
         Self: 'c;
 
+    type Value<'c>
+        = DBPinnableSlice<'c>
+    where
+        Self: 'c;
+
     fn name(&self) -> &str {
         &self.name
     }
 
-    fn get(&self, key: Bytes) -> Result<Option<Bytes>, Error> {
-        let full_key = self.make_key(&key);
-        if let Some(value) = self.db.get(&full_key)? {
-            Ok(Some(value))
-        } else {
-            Ok(None)
-        }
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, RocksdbError> {
+        // A table without column family has never been written.
+        let Some(cf) = self.db.cf_handle(&self.name) else {
+            return Ok(None);
+        };
+
+        Ok(self.db.get_cf(&cf, key)?)
     }
 
-    fn range(&self, begin: Bytes, end: Bytes) -> Result<Self::Range<'_>, Error> {
-        let begin_key = self.make_key(&begin);
-        let end_key = self.make_key(&end);
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, RocksdbError> {
+        let Some(cf) = self.db.cf_handle(&self.name) else {
+            return Ok(None);
+        };
+
+        Ok(self.db.get_pinned_cf(&cf, key)?)
+    }
 
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, RocksdbError> {
         Ok(RocksdbRange {
-            db: Arc::clone(&self.db),
-            begin: begin_key,
-            end: end_key,
-            prefix_len: self.name.len() + 1,
+            db: self.db.clone(),
+            name: self.name.clone(),
+            begin: begin.to_vec(),
+            end: end.map(<[u8]>::to_vec),
             current: None,
         })
     }
@@ -61,66 +62,60 @@ impl ReadTable<Error> for RocksdbReadTable {
 
 /// A writable table wrapper for RocksDB.
 ///
-/// Implements both the CrepeDB `ReadTable` and `WriteTable` traits.
+/// Implements both the CrepeDB `ReadTable` and `WriteTable` traits. The column
+/// family of the table is created when the table is opened, and its handle is
+/// kept, so a table dropped meanwhile fails the transaction instead of
+/// panicking. Keys read through the table are validated on commit.
 pub struct RocksdbWriteTable<'a> {
-    pub(crate) txn: &'a Transaction<'a, OptimisticTransactionDB>,
-    pub(crate) db: Arc<OptimisticTransactionDB>,
+    pub(crate) txn: &'a Transaction<'a, RocksdbInner>,
+    pub(crate) db: Arc<RocksdbInner>,
+    pub(crate) cf: Arc<BoundColumnFamily<'a>>,
     pub(crate) name: String,
 }
 
-impl<'a> RocksdbWriteTable<'a> {
-    fn make_key(&self, key: &[u8]) -> Vec<u8> {
-        let mut full_key = Vec::with_capacity(self.name.len() + 1 + key.len());
-        full_key.extend_from_slice(self.name.as_bytes());
-        full_key.push(b':');
-        full_key.extend_from_slice(key);
-        full_key
-    }
-}
-
-impl<'a> ReadTable<Error> for RocksdbWriteTable<'a> {
+impl<'a> ReadTable<RocksdbError> for RocksdbWriteTable<'a> {
     type Range<'c>
         = RocksdbRange
     where
         Self: 'c;
 
+    type Value<'c>
+        = DBPinnableSlice<'c>
+    where
+        Self: 'c;
+
     fn name(&self) -> &str {
         &self.name
     }
 
-    fn get(&self, key: Bytes) -> Result<Option<Bytes>, Error> {
-        let full_key = self.make_key(&key);
-        if let Some(value) = self.txn.get(&full_key)? {
-            Ok(Some(value))
-        } else {
-            Ok(None)
-        }
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, RocksdbError> {
+        // Track the key, so that a concurrent write to it fails the commit.
+        Ok(self.txn.get_for_update_cf(&self.cf, key, true)?)
     }
 
-    fn range(&self, begin: Bytes, end: Bytes) -> Result<Self::Range<'_>, Error> {
-        let begin_key = self.make_key(&begin);
-        let end_key = self.make_key(&end);
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, RocksdbError> {
+        Ok(self.txn.get_pinned_for_update_cf(&self.cf, key, true)?)
+    }
 
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, RocksdbError> {
         Ok(RocksdbRange {
-            db: Arc::clone(&self.db),
-            begin: begin_key,
-            end: end_key,
-            prefix_len: self.name.len() + 1,
+            db: Db::Transaction(Arc::clone(&self.db)),
+            name: self.name.clone(),
+            begin: begin.to_vec(),
+            end: end.map(<[u8]>::to_vec),
             current: None,
         })
     }
 }
 
-impl<'a> WriteTable<Error> for RocksdbWriteTable<'a> {
-    fn set(&mut self, key: Bytes, value: Bytes) -> Result<(), Error> {
-        let full_key = self.make_key(&key);
-        self.txn.put(&full_key, &value)?;
+impl<'a> WriteTable<RocksdbError> for RocksdbWriteTable<'a> {
+    fn set(&mut self, key: &[u8], value: &[u8]) -> Result<(), RocksdbError> {
+        self.txn.put_cf(&self.cf, key, value)?;
         Ok(())
     }
 
-    fn del(&mut self, key: Bytes) -> Result<(), Error> {
-        let full_key = self.make_key(&key);
-        self.txn.delete(&full_key)?;
+    fn del(&mut self, key: &[u8]) -> Result<(), RocksdbError> {
+        self.txn.delete_cf(&self.cf, key)?;
         Ok(())
     }
 }
@@ -1,34 +1,47 @@
 use std::sync::Arc;
 
 use crepedb_core::backend::WriteTxn;
-use rocksdb::{Error, OptimisticTransactionDB, Transaction};
+use rocksdb::Transaction;
 
-use crate::RocksdbWriteTable;
+use crate::{db::ensure_table, RocksdbError, RocksdbInner, RocksdbWriteTable, TableOptions};
 
 /// A write transaction wrapper for RocksDB.
 ///
 /// Implements the CrepeDB `WriteTxn` trait, providing read-write access to tables
 /// and the ability to commit changes.
 pub struct RocksdbWriteTxn<'db> {
-    pub(crate) inner: Transaction<'db, OptimisticTransactionDB>,
-    pub(crate) db: Arc<OptimisticTransactionDB>,
+    pub(crate) inner: Transaction<'db, RocksdbInner>,
+    pub(crate) db: Arc<RocksdbInner>,
+    pub(crate) tables: Arc<TableOptions>,
 }
 
-impl<'db> WriteTxn<Error> for RocksdbWriteTxn<'db> {
+impl<'db> WriteTxn<RocksdbError> for RocksdbWriteTxn<'db> {
     type Table<'a>
         = RocksdbWriteTable<'a>
     where
         Self: 'a;
 
-    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, Error> {
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, RocksdbError> {
+        let cf = ensure_table(&self.db, &self.tables, table)?;
+
         Ok(RocksdbWriteTable {
             txn: &self.inner,
             db: Arc::clone(&self.db),
+            cf,
             name: table.to_string(),
         })
     }
 
-    fn commit(self) -> Result<(), Error> {
+    fn set_savepoint(&self) -> Result<bool, RocksdbError> {
+        self.inner.set_savepoint();
+        Ok(true)
+    }
+
+    fn rollback_to_savepoint(&self) -> Result<(), RocksdbError> {
+        Ok(self.inner.rollback_to_savepoint()?)
+    }
+
+    fn commit(self) -> Result<(), RocksdbError> {
         self.inner.commit()?;
         Ok(())
     }
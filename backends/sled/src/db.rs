@@ -0,0 +1,127 @@
+use std::{
+    path::Path,
+    sync::{Mutex, PoisonError},
+};
+
+use crepedb_core::backend::Backend;
+use sled::{Config, Db, Error};
+
+use crate::{SledReadTxn, SledWriteTxn};
+
+/// A CrepeDB backend implementation using sled.
+///
+/// This struct wraps a sled `Db` and implements the CrepeDB `Backend` trait,
+/// allowing sled to be used as the storage engine for CrepeDB.
+///
+/// Every CrepeDB table is mapped to a sled `Tree` with the same name. Write
+/// transactions buffer their changes and apply them to all trees in a single
+/// sled transaction on commit. They are serialized by a lock held until they
+/// are committed or dropped, as their reads are not validated at commit. sled
+/// has no read snapshots, so read transactions see changes committed after
+/// they started.
+pub struct SledDatabase {
+    inner: Db,
+    writer: Mutex<()>,
+}
+
+impl SledDatabase {
+    /// Open or create a persistent sled database at the specified path.
+    ///
+    /// If the database doesn't exist, it will be created. If it exists, it will be opened.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory path where the database should be stored
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let db = SledDatabase::open_or_create("mydb")?;
+    /// ```
+    pub fn open_or_create(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let db = sled::open(path)?;
+        Ok(Self::new(db))
+    }
+
+    /// Create a temporary sled database.
+    ///
+    /// The database is removed when dropped.
+    /// This is useful for testing or temporary storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let db = SledDatabase::memory()?;
+    /// ```
+    pub fn memory() -> Result<Self, Error> {
+        let db = Config::new().temporary(true).open()?;
+        Ok(Self::new(db))
+    }
+
+    /// Open a database from a sled configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let config = sled::Config::new().path("mydb").cache_capacity(256 << 20);
+    /// let db = SledDatabase::open_with_config(&config)?;
+    /// ```
+    pub fn open_with_config(config: &Config) -> Result<Self, Error> {
+        let db = config.open()?;
+        Ok(Self::new(db))
+    }
+
+    fn new(inner: Db) -> Self {
+        Self {
+            inner,
+            writer: Mutex::default(),
+        }
+    }
+
+    /// Get a reference to the underlying sled database.
+    pub fn inner(&self) -> &Db {
+        &self.inner
+    }
+
+    /// Consume the database and return the underlying sled database.
+    pub fn into_inner(self) -> Db {
+        self.inner
+    }
+}
+
+impl Backend for SledDatabase {
+    type Error = Error;
+
+    type ReadTxn<'a> = SledReadTxn;
+
+    type WriteTxn<'a> = SledWriteTxn<'a>;
+
+    fn read_txn(&self) -> Result<Self::ReadTxn<'_>, Self::Error> {
+        Ok(SledReadTxn {
+            db: self.inner.clone(),
+        })
+    }
+
+    fn write_txn(&self) -> Result<Self::WriteTxn<'_>, Self::Error> {
+        // The lock guards no data, so a panic while holding it is harmless.
+        let guard = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+
+        Ok(SledWriteTxn {
+            _guard: guard,
+            db: self.inner.clone(),
+            tables: Default::default(),
+        })
+    }
+}
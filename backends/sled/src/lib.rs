@@ -0,0 +1,153 @@
+//! # CrepeDB Sled Backend
+//!
+//! This crate provides a [sled](https://github.com/spacejam/sled) backend implementation for CrepeDB.
+//!
+//! Sled is a pure-Rust embedded database built on a lock-free B+ tree.
+//! This backend allows CrepeDB to use sled as its underlying storage engine.
+//!
+//! ## Features
+//!
+//! - **Pure Rust**: No native libraries required
+//! - **Embedded**: No separate server process required
+//! - **Persistent Storage**: Data is stored on disk
+//! - **Temporary Mode**: Support for temporary databases removed on drop
+//! - **Tree per Table**: Each table is stored in its own sled `Tree`
+//!
+//! ## Example
+//!
+//! ```ignore
+//! use crepedb::{CrepeDB, SnapshotId, TableType};
+//! use crepedb_sled::SledDatabase;
+//!
+//! // Open a persistent database
+//! let backend = SledDatabase::open_or_create("mydb")?;
+//! let db = CrepeDB::new(backend);
+//!
+//! // Or create a temporary database
+//! let backend = SledDatabase::memory()?;
+//! let db = CrepeDB::new(backend);
+//! ```
+
+mod db;
+pub use db::*;
+
+mod read;
+pub use read::*;
+
+mod write;
+pub use write::*;
+
+mod table;
+pub use table::*;
+
+mod range;
+pub use range::*;
+
+#[cfg(test)]
+mod tests {
+    use crate::SledDatabase;
+
+    crepedb_backend_tests::backend_tests!(SledDatabase::memory().unwrap());
+
+    #[test]
+    fn test_write_table_sees_changes() {
+        use crepedb_core::backend::{Backend, Range, ReadTable, WriteTable, WriteTxn};
+
+        let backend = SledDatabase::memory().unwrap();
+
+        let txn = backend.write_txn().unwrap();
+        {
+            let mut t = txn.open_table("t").unwrap();
+            t.set(&[1], &[1]).unwrap();
+            t.set(&[3], &[3]).unwrap();
+        }
+        txn.commit().unwrap();
+
+        let txn = backend.write_txn().unwrap();
+        {
+            let mut t = txn.open_table("t").unwrap();
+            t.set(&[2], &[2]).unwrap();
+            t.set(&[3], &[4]).unwrap();
+            t.del(&[1]).unwrap();
+
+            assert_eq!(t.get(&[1]).unwrap(), None);
+            assert_eq!(t.get(&[3]).unwrap(), Some(vec![4]));
+
+            let mut range = t.range(&[], Some(&[0xff])).unwrap();
+            assert_eq!(range.back().unwrap(), Some((vec![2], vec![2])));
+            assert_eq!(range.back().unwrap(), Some((vec![3], vec![4])));
+            assert_eq!(range.back().unwrap(), None);
+        }
+
+        // Uncommitted changes are not visible to readers.
+        let read = backend.read_txn().unwrap();
+        let t = crepedb_core::backend::ReadTxn::open_table(&read, "t").unwrap();
+        assert_eq!(t.get(&[1]).unwrap(), Some(vec![1]));
+
+        txn.commit().unwrap();
+
+        assert_eq!(t.get(&[1]).unwrap(), None);
+        assert_eq!(t.get(&[3]).unwrap(), Some(vec![4]));
+    }
+
+    #[test]
+    fn test_concurrent_writers() {
+        use crepedb_core::CrepeDB;
+
+        let db = CrepeDB::new(SledDatabase::memory().unwrap());
+
+        let txn = db.write(None).unwrap();
+        txn.create_versioned_table("t").unwrap();
+        let root = txn.commit().unwrap();
+
+        // Every writer allocates its snapshot IDs from the same counter.
+        let snapshots: Vec<_> = std::thread::scope(|s| {
+            let writers: Vec<_> = (0..4u8)
+                .map(|i| {
+                    let (db, root) = (&db, root.clone());
+                    s.spawn(move || {
+                        (0..10u8)
+                            .map(|j| {
+                                let txn = db.write(Some(root.clone())).unwrap();
+                                txn.open_table("t").unwrap().set([i], [j]).unwrap();
+                                (txn.commit().unwrap(), i, j)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            writers
+                .into_iter()
+                .flat_map(|w| w.join().unwrap())
+                .collect()
+        });
+
+        for (snapshot, i, j) in &snapshots {
+            let txn = db.read(Some(snapshot.clone())).unwrap();
+            assert_eq!(
+                txn.open_table("t").unwrap().get([*i]).unwrap(),
+                Some(vec![*j])
+            );
+        }
+
+        let mut ids: Vec<_> = snapshots.iter().map(|(s, _, _)| s.to_bytes()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 40);
+    }
+
+    #[test]
+    fn test_read_does_not_create_trees() {
+        use crepedb_core::backend::{Backend, Range, ReadTable, ReadTxn};
+
+        let backend = SledDatabase::memory().unwrap();
+
+        let txn = backend.read_txn().unwrap();
+        let t = txn.open_table("t").unwrap();
+        assert_eq!(t.get(&[1]).unwrap(), None);
+        assert_eq!(t.range(&[], Some(&[0xff])).unwrap().back().unwrap(), None);
+
+        assert!(!backend.inner().tree_names().iter().any(|name| name == b"t"));
+    }
+}
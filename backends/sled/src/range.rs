@@ -0,0 +1,78 @@
+use std::{cmp::Ordering, collections::VecDeque};
+
+use crepedb_core::types::Bytes;
+use sled::{Error, Iter, Tree};
+
+/// A range iterator wrapper for sled.
+///
+/// Implements the CrepeDB `Range` trait by merging the uncommitted changes of a
+/// write transaction into the iteration over a sled `Tree`.
+pub struct SledRange {
+    inner: Option<Iter>,
+    peeked: Option<(Bytes, Bytes)>,
+    pending: VecDeque<(Bytes, Option<Bytes>)>,
+}
+
+impl SledRange {
+    pub(crate) fn new(
+        tree: Option<&Tree>,
+        begin: &[u8],
+        end: Option<&[u8]>,
+        pending: VecDeque<(Bytes, Option<Bytes>)>,
+    ) -> Self {
+        // sled panics on inverted bounds.
+        let inner = tree.and_then(|tree| match end {
+            Some(end) if begin >= end => None,
+            Some(end) => Some(tree.range(begin..end)),
+            None => Some(tree.range(begin..)),
+        });
+
+        Self {
+            inner,
+            peeked: None,
+            pending,
+        }
+    }
+
+    fn fill_peeked(&mut self) -> Result<(), Error> {
+        if self.peeked.is_none() {
+            if let Some(iter) = &mut self.inner {
+                if let Some(r) = iter.next() {
+                    let (k, v) = r?;
+                    self.peeked = Some((k.to_vec(), v.to_vec()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl crepedb_core::backend::Range<Error> for SledRange {
+    fn back(&mut self) -> Result<Option<(Bytes, Bytes)>, Error> {
+        loop {
+            self.fill_peeked()?;
+
+            let order = match (&self.peeked, self.pending.front()) {
+                (None, None) => return Ok(None),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((k, _)), Some((p, _))) => k.cmp(p),
+            };
+
+            if order == Ordering::Less {
+                return Ok(self.peeked.take());
+            }
+
+            if order == Ordering::Equal {
+                self.peeked = None;
+            }
+
+            let (key, value) = self.pending.pop_front().expect("checked above");
+
+            if let Some(value) = value {
+                return Ok(Some((key, value)));
+            }
+        }
+    }
+}
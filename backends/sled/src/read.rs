@@ -0,0 +1,35 @@
+use crepedb_core::backend::ReadTxn;
+use sled::{Db, Error};
+
+use crate::SledReadTable;
+
+/// A read transaction wrapper for sled.
+///
+/// Implements the CrepeDB `ReadTxn` trait, providing read-only access to tables.
+pub struct SledReadTxn {
+    pub(crate) db: Db,
+}
+
+impl ReadTxn<Error> for SledReadTxn {
+    type Table<'a> = SledReadTable;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, Error> {
+        // Opening a tree creates it, so a table which was never written is
+        // read as empty instead.
+        let exists = self
+            .db
+            .tree_names()
+            .iter()
+            .any(|name| name == table.as_bytes());
+        let tree = if exists {
+            Some(self.db.open_tree(table)?)
+        } else {
+            None
+        };
+
+        Ok(SledReadTable {
+            tree,
+            name: table.to_string(),
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use std::{cell::RefCell, collections::BTreeMap, ops::Bound};
+
+use crepedb_core::{
+    backend::{ReadTable, WriteTable},
+    types::Bytes,
+};
+use sled::{Error, IVec, Tree};
+
+use crate::{write::Changes, SledRange};
+
+/// A read-only table wrapper for sled.
+///
+/// Implements the CrepeDB `ReadTable` trait for a sled `Tree`, `None` for a
+/// table which does not exist.
+pub struct SledReadTable {
+    pub(crate) tree: Option<Tree>,
+    pub(crate) name: String,
+}
+
+impl ReadTable<Error> for SledReadTable {
+    type Range<'a> = SledRange;
+
+    type Value<'a> = IVec;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, Error> {
+        Ok(self.get_ref(key)?.map(|v| v.to_vec()))
+    }
+
+    // Values are reference counted and shared with sled's cache.
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, Error> {
+        match &self.tree {
+            Some(tree) => tree.get(key),
+            None => Ok(None),
+        }
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, Error> {
+        Ok(SledRange::new(
+            self.tree.as_ref(),
+            begin,
+            end,
+            Default::default(),
+        ))
+    }
+}
+
+/// A writable table wrapper for sled.
+///
+/// Implements both the CrepeDB `ReadTable` and `WriteTable` traits. Reads see
+/// the uncommitted changes of the write transaction.
+pub struct SledWriteTable<'a> {
+    pub(crate) tree: Tree,
+    pub(crate) tables: &'a RefCell<BTreeMap<String, (Tree, Changes)>>,
+    pub(crate) name: String,
+}
+
+impl<'a> SledWriteTable<'a> {
+    fn change(&mut self, key: &[u8], value: Option<&[u8]>) {
+        let mut tables = self.tables.borrow_mut();
+        let (_, changes) = tables.get_mut(&self.name).expect("inserted in open_table");
+
+        changes.insert(key.to_vec(), value.map(<[u8]>::to_vec));
+    }
+}
+
+impl<'a> ReadTable<Error> for SledWriteTable<'a> {
+    type Range<'c>
+        = SledRange
+    where
+        Self: 'c;
+
+    type Value<'c>
+        = IVec
+    where
+        Self: 'c;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, Error> {
+        let tables = self.tables.borrow();
+        let (_, changes) = tables.get(&self.name).expect("inserted in open_table");
+
+        if let Some(value) = changes.get(key) {
+            return Ok(value.clone());
+        }
+
+        Ok(self.tree.get(key)?.map(|v| v.to_vec()))
+    }
+
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, Error> {
+        let tables = self.tables.borrow();
+        let (_, changes) = tables.get(&self.name).expect("inserted in open_table");
+
+        // Staged changes are owned by the transaction and copied.
+        if let Some(value) = changes.get(key) {
+            return Ok(value.as_deref().map(IVec::from));
+        }
+
+        self.tree.get(key)
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, Error> {
+        let tables = self.tables.borrow();
+        let (_, changes) = tables.get(&self.name).expect("inserted in open_table");
+
+        let pending = match end {
+            Some(end) if begin >= end => Default::default(),
+            _ => changes
+                .range::<[u8], _>((
+                    Bound::Included(begin),
+                    end.map_or(Bound::Unbounded, Bound::Excluded),
+                ))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        };
+
+        Ok(SledRange::new(Some(&self.tree), begin, end, pending))
+    }
+}
+
+impl<'a> WriteTable<Error> for SledWriteTable<'a> {
+    fn set(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.change(key, Some(value));
+        Ok(())
+    }
+
+    fn del(&mut self, key: &[u8]) -> Result<(), Error> {
+        self.change(key, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use std::{cell::RefCell, collections::BTreeMap, sync::MutexGuard};
+
+use crepedb_core::{backend::WriteTxn, types::Bytes};
+use sled::{
+    transaction::{ConflictableTransactionError, TransactionError, Transactional},
+    Batch, Db, Error, Tree,
+};
+
+use crate::SledWriteTable;
+
+/// Uncommitted changes of a table, `None` marks a deleted key.
+pub(crate) type Changes = BTreeMap<Bytes, Option<Bytes>>;
+
+/// A write transaction wrapper for sled.
+///
+/// Implements the CrepeDB `WriteTxn` trait, providing read-write access to tables
+/// and the ability to commit changes. Changes are kept in memory until commit,
+/// and other write transactions wait until this one is committed or dropped.
+pub struct SledWriteTxn<'db> {
+    pub(crate) _guard: MutexGuard<'db, ()>,
+    pub(crate) db: Db,
+    pub(crate) tables: RefCell<BTreeMap<String, (Tree, Changes)>>,
+}
+
+impl WriteTxn<Error> for SledWriteTxn<'_> {
+    type Table<'a>
+        = SledWriteTable<'a>
+    where
+        Self: 'a;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, Error> {
+        let mut tables = self.tables.borrow_mut();
+
+        let tree = match tables.get(table) {
+            Some((tree, _)) => tree.clone(),
+            None => {
+                let tree = self.db.open_tree(table)?;
+                tables.insert(table.to_string(), (tree.clone(), Changes::new()));
+                tree
+            }
+        };
+
+        Ok(SledWriteTable {
+            tree,
+            tables: &self.tables,
+            name: table.to_string(),
+        })
+    }
+
+    fn commit(self) -> Result<(), Error> {
+        let mut trees = Vec::new();
+        let mut batches = Vec::new();
+
+        for (tree, changes) in self.tables.into_inner().into_values() {
+            if changes.is_empty() {
+                continue;
+            }
+
+            let mut batch = Batch::default();
+
+            for (key, value) in changes {
+                match value {
+                    Some(value) => batch.insert(key, value),
+                    None => batch.remove(key),
+                }
+            }
+
+            trees.push(tree);
+            batches.push(batch);
+        }
+
+        if trees.is_empty() {
+            return Ok(());
+        }
+
+        trees[..]
+            .transaction(|txs| {
+                for (tx, batch) in txs.iter().zip(&batches) {
+                    tx.apply_batch(batch)?;
+                }
+
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })
+            .map_err(|e| match e {
+                TransactionError::Abort(()) => unreachable!("transaction is never aborted"),
+                TransactionError::Storage(e) => e,
+            })?;
+
+        self.db.flush()?;
+
+        Ok(())
+    }
+}
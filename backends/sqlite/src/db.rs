@@ -0,0 +1,158 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crepedb_core::backend::Backend;
+use rusqlite::{Connection, Error};
+
+use crate::{SqliteReadTxn, SqliteWriteTxn};
+
+/// How long a transaction waits for the lock held by another connection.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A CrepeDB backend implementation using SQLite.
+///
+/// Every CrepeDB table is mapped to a SQLite table with the same name and the
+/// columns `k BLOB PRIMARY KEY, v BLOB`. The database uses write-ahead logging,
+/// so read transactions see a consistent snapshot while a write is in progress.
+///
+/// Each transaction opens its own connection to the database file. Write
+/// transactions start with `BEGIN IMMEDIATE`, so concurrent writers wait for
+/// each other instead of failing on commit.
+pub struct SqliteDatabase {
+    path: PathBuf,
+    temporary: bool,
+}
+
+impl Drop for SqliteDatabase {
+    fn drop(&mut self) {
+        if !self.temporary {
+            return;
+        }
+
+        for suffix in ["", "-wal", "-shm"] {
+            let mut path = self.path.clone().into_os_string();
+            path.push(suffix);
+
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    log::warn!("Failed to remove {}: {e}", Path::new(&path).display());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl SqliteDatabase {
+    /// Open or create a persistent SQLite database at the specified path.
+    ///
+    /// If the database doesn't exist, it will be created. If it exists, it will be opened.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file path where the database should be stored
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created or opened.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let db = SqliteDatabase::open_or_create("mydb.sqlite")?;
+    /// ```
+    pub fn open_or_create(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+
+        let conn = Connection::open(&path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+
+        Ok(Self {
+            path,
+            temporary: false,
+        })
+    }
+
+    /// Create a temporary SQLite database.
+    ///
+    /// The database is stored in a new file in the system temporary directory,
+    /// which is removed together with its journal when the database is dropped.
+    /// This is useful for testing or temporary storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let db = SqliteDatabase::temporary()?;
+    /// ```
+    pub fn temporary() -> Result<Self, Error> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        let path = std::env::temp_dir().join(format!(
+            "crepedb-sqlite-{}-{}-{}.sqlite",
+            std::process::id(),
+            nanos,
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let mut db = Self::open_or_create(path)?;
+        db.temporary = true;
+
+        Ok(db)
+    }
+
+    /// Get the path of the database file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Open a new connection to the database.
+    ///
+    /// This can be used to inspect or maintain the database with plain SQL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened.
+    pub fn connection(&self) -> Result<Connection, Error> {
+        let conn = Connection::open(&self.path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        Ok(conn)
+    }
+}
+
+impl Backend for SqliteDatabase {
+    type Error = Error;
+
+    type ReadTxn<'a> = SqliteReadTxn;
+
+    type WriteTxn<'a> = SqliteWriteTxn;
+
+    fn read_txn(&self) -> Result<Self::ReadTxn<'_>, Self::Error> {
+        let conn = self.connection()?;
+
+        conn.execute_batch("BEGIN")?;
+        // The snapshot of a deferred transaction is taken on the first read.
+        conn.query_row("SELECT count(*) FROM sqlite_schema", [], |_| Ok(()))?;
+
+        Ok(SqliteReadTxn { conn })
+    }
+
+    fn write_txn(&self) -> Result<Self::WriteTxn<'_>, Self::Error> {
+        let conn = self.connection()?;
+
+        conn.execute_batch("BEGIN IMMEDIATE")?;
+
+        Ok(SqliteWriteTxn { conn })
+    }
+}
@@ -0,0 +1,98 @@
+//! # CrepeDB SQLite Backend
+//!
+//! This crate provides a [SQLite](https://sqlite.org/) backend implementation for CrepeDB.
+//!
+//! SQLite is a small, fast, self-contained, single-file SQL database engine.
+//! This backend allows CrepeDB to use SQLite as its underlying storage engine,
+//! so databases can be inspected with standard SQLite tools.
+//!
+//! ## Features
+//!
+//! - **ACID Transactions**: Full transactional support via SQLite
+//! - **Single File**: The whole database is one portable file
+//! - **Inspectable**: Each table is a plain SQL table with `k` and `v` BLOB columns
+//! - **Temporary Mode**: Support for temporary databases removed on drop
+//!
+//! ## Example
+//!
+//! ```ignore
+//! use crepedb::{CrepeDB, SnapshotId, TableType};
+//! use crepedb_sqlite::SqliteDatabase;
+//!
+//! // Open a persistent database
+//! let backend = SqliteDatabase::open_or_create("mydb.sqlite")?;
+//! let db = CrepeDB::new(backend);
+//!
+//! // Or create a temporary database
+//! let backend = SqliteDatabase::temporary()?;
+//! let db = CrepeDB::new(backend);
+//! ```
+
+mod db;
+pub use db::*;
+
+mod read;
+pub use read::*;
+
+mod write;
+pub use write::*;
+
+mod table;
+pub use table::*;
+
+mod range;
+pub use range::*;
+
+#[cfg(test)]
+mod tests {
+    use crate::SqliteDatabase;
+
+    crepedb_backend_tests::backend_tests!(SqliteDatabase::temporary().unwrap());
+
+    #[test]
+    fn test_range_pages() {
+        use crepedb_core::backend::{Backend, Range, ReadTable, ReadTxn, WriteTable, WriteTxn};
+
+        let backend = SqliteDatabase::temporary().unwrap();
+
+        let txn = backend.write_txn().unwrap();
+        {
+            let mut t = txn.open_table("a \"quoted\" table").unwrap();
+            for i in 0..1000u16 {
+                t.set(&i.to_be_bytes(), &[1]).unwrap();
+            }
+        }
+        txn.commit().unwrap();
+
+        let txn = backend.read_txn().unwrap();
+        let t = txn.open_table("a \"quoted\" table").unwrap();
+
+        let mut range = t
+            .range(&10u16.to_be_bytes(), Some(&900u16.to_be_bytes()))
+            .unwrap();
+        let mut count = 0u16;
+        while let Some((k, _)) = range.back().unwrap() {
+            assert_eq!(k, (10 + count).to_be_bytes().to_vec());
+            count += 1;
+        }
+        assert_eq!(count, 890);
+
+        let missing = txn.open_table("missing").unwrap();
+        assert_eq!(missing.get(&[1]).unwrap(), None);
+        assert_eq!(
+            missing.range(&[], Some(&[0xff])).unwrap().back().unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_temporary_cleanup() {
+        let backend = SqliteDatabase::temporary().unwrap();
+        let path = backend.path().to_path_buf();
+
+        assert!(path.exists());
+
+        crepedb_backend_tests::suite::test_read(backend).unwrap();
+        assert!(!path.exists());
+    }
+}
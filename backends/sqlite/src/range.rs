@@ -0,0 +1,78 @@
+use std::collections::VecDeque;
+
+use crepedb_core::types::Bytes;
+use rusqlite::{Connection, Error};
+
+/// Number of rows fetched by each query of a range.
+const PAGE_SIZE: usize = 256;
+
+/// A range iterator wrapper for SQLite.
+///
+/// Implements the CrepeDB `Range` trait by fetching pages of rows with
+/// `WHERE k >= ? AND k < ? ORDER BY k` queries, which use the primary key index.
+/// Ranges without an end leave out the `k < ?` condition.
+pub struct SqliteRange<'a> {
+    conn: &'a Connection,
+    ident: Option<String>,
+    /// Key to continue from, and whether it was already returned.
+    next: Option<(Bytes, bool)>,
+    end: Option<Bytes>,
+    buffer: VecDeque<(Bytes, Bytes)>,
+}
+
+impl<'a> SqliteRange<'a> {
+    pub(crate) fn new(
+        conn: &'a Connection,
+        ident: Option<&str>,
+        begin: &[u8],
+        end: Option<&[u8]>,
+    ) -> Self {
+        Self {
+            conn,
+            ident: ident.map(|s| s.to_string()),
+            next: Some((begin.to_vec(), false)),
+            end: end.map(<[u8]>::to_vec),
+            buffer: VecDeque::new(),
+        }
+    }
+
+    fn fetch(&mut self) -> Result<(), Error> {
+        let (Some(ident), Some((from, returned))) = (&self.ident, self.next.take()) else {
+            return Ok(());
+        };
+
+        let op = if returned { ">" } else { ">=" };
+        let end = if self.end.is_some() { "AND k < ?2" } else { "" };
+        let sql =
+            format!("SELECT k, v FROM {ident} WHERE k {op} ?1 {end} ORDER BY k LIMIT {PAGE_SIZE}");
+
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let row = |row: &rusqlite::Row| Ok((row.get(0)?, row.get(1)?));
+
+        let rows: Vec<(Bytes, Bytes)> = match &self.end {
+            Some(end) => stmt
+                .query_map((from, end), row)?
+                .collect::<Result<_, _>>()?,
+            None => stmt.query_map([from], row)?.collect::<Result<_, _>>()?,
+        };
+
+        self.buffer.extend(rows);
+
+        if self.buffer.len() == PAGE_SIZE {
+            let (last, _) = self.buffer.back().expect("page is full");
+            self.next = Some((last.clone(), true));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> crepedb_core::backend::Range<Error> for SqliteRange<'a> {
+    fn back(&mut self) -> Result<Option<(Bytes, Bytes)>, Error> {
+        if self.buffer.is_empty() {
+            self.fetch()?;
+        }
+
+        Ok(self.buffer.pop_front())
+    }
+}
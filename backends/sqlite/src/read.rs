@@ -0,0 +1,29 @@
+use crepedb_core::backend::ReadTxn;
+use rusqlite::{Connection, Error};
+
+use crate::{table::quote, SqliteReadTable};
+
+/// A read transaction wrapper for SQLite.
+///
+/// Implements the CrepeDB `ReadTxn` trait, providing read-only access to tables.
+/// The transaction is rolled back when dropped.
+pub struct SqliteReadTxn {
+    pub(crate) conn: Connection,
+}
+
+impl ReadTxn<Error> for SqliteReadTxn {
+    type Table<'a> = SqliteReadTable<'a>;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, Error> {
+        let exists = self
+            .conn
+            .prepare_cached("SELECT 1 FROM sqlite_schema WHERE type = 'table' AND name = ?1")?
+            .exists([table])?;
+
+        Ok(SqliteReadTable {
+            conn: &self.conn,
+            ident: exists.then(|| quote(table)),
+            name: table.to_string(),
+        })
+    }
+}
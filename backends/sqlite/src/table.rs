@@ -0,0 +1,123 @@
+use crepedb_core::{
+    backend::{ReadTable, WriteTable},
+    types::Bytes,
+};
+use rusqlite::{Connection, Error, OptionalExtension};
+
+use crate::SqliteRange;
+
+/// Quote a table name as a SQLite identifier.
+pub(crate) fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn get(conn: &Connection, ident: &str, key: &[u8]) -> Result<Option<Bytes>, Error> {
+    conn.prepare_cached(&format!("SELECT v FROM {ident} WHERE k = ?1"))?
+        .query_row([key], |row| row.get(0))
+        .optional()
+}
+
+/// A read-only table wrapper for SQLite.
+///
+/// Implements the CrepeDB `ReadTable` trait. A table that does not exist is
+/// read as an empty table.
+pub struct SqliteReadTable<'a> {
+    pub(crate) conn: &'a Connection,
+    pub(crate) ident: Option<String>,
+    pub(crate) name: String,
+}
+
+impl<'a> ReadTable<Error> for SqliteReadTable<'a> {
+    type Range<'c>
+        = SqliteRange<'c>
+    where
+        Self: 'c;
+
+    type Value<'c>
+        = Bytes
+    where
+        Self: 'c;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, Error> {
+        match &self.ident {
+            Some(ident) => get(self.conn, ident, key),
+            None => Ok(None),
+        }
+    }
+
+    // SQLite copies values out of its pages on read.
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, Error> {
+        self.get(key)
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, Error> {
+        Ok(SqliteRange::new(
+            self.conn,
+            self.ident.as_deref(),
+            begin,
+            end,
+        ))
+    }
+}
+
+/// A writable table wrapper for SQLite.
+///
+/// Implements both the CrepeDB `ReadTable` and `WriteTable` traits.
+pub struct SqliteWriteTable<'a> {
+    pub(crate) conn: &'a Connection,
+    pub(crate) ident: String,
+    pub(crate) name: String,
+}
+
+impl<'a> ReadTable<Error> for SqliteWriteTable<'a> {
+    type Range<'c>
+        = SqliteRange<'c>
+    where
+        Self: 'c;
+
+    type Value<'c>
+        = Bytes
+    where
+        Self: 'c;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, Error> {
+        get(self.conn, &self.ident, key)
+    }
+
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, Error> {
+        self.get(key)
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, Error> {
+        Ok(SqliteRange::new(self.conn, Some(&self.ident), begin, end))
+    }
+}
+
+impl<'a> WriteTable<Error> for SqliteWriteTable<'a> {
+    fn set(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        let sql = format!(
+            "INSERT INTO {} (k, v) VALUES (?1, ?2) ON CONFLICT (k) DO UPDATE SET v = excluded.v",
+            self.ident
+        );
+
+        self.conn.prepare_cached(&sql)?.execute((key, value))?;
+
+        Ok(())
+    }
+
+    fn del(&mut self, key: &[u8]) -> Result<(), Error> {
+        let sql = format!("DELETE FROM {} WHERE k = ?1", self.ident);
+
+        self.conn.prepare_cached(&sql)?.execute([key])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use crepedb_core::backend::WriteTxn;
+use rusqlite::{Connection, Error};
+
+use crate::{table::quote, SqliteWriteTable};
+
+/// A write transaction wrapper for SQLite.
+///
+/// Implements the CrepeDB `WriteTxn` trait, providing read-write access to tables
+/// and the ability to commit changes. The transaction is rolled back when dropped
+/// without commit. Savepoints map to the savepoints of SQLite, which also undo
+/// tables created after them.
+pub struct SqliteWriteTxn {
+    pub(crate) conn: Connection,
+}
+
+impl WriteTxn<Error> for SqliteWriteTxn {
+    type Table<'a>
+        = SqliteWriteTable<'a>
+    where
+        Self: 'a;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, Error> {
+        let ident = quote(table);
+
+        self.conn.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS {ident} (k BLOB PRIMARY KEY, v BLOB NOT NULL) WITHOUT ROWID"
+        ))?;
+
+        Ok(SqliteWriteTable {
+            conn: &self.conn,
+            ident,
+            name: table.to_string(),
+        })
+    }
+
+    fn set_savepoint(&self) -> Result<bool, Error> {
+        self.conn.execute_batch("SAVEPOINT crepe")?;
+        Ok(true)
+    }
+
+    fn rollback_to_savepoint(&self) -> Result<(), Error> {
+        // Savepoints with the same name nest, both statements apply to the
+        // most recent one.
+        self.conn
+            .execute_batch("ROLLBACK TO crepe; RELEASE crepe")?;
+        Ok(())
+    }
+
+    fn commit(self) -> Result<(), Error> {
+        self.conn.execute_batch("COMMIT")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+//! Allocations and time of reads and writes with borrowed keys.
+//!
+//! Run with `cargo bench -p crepedb-core --features memory`. The allocations
+//! of a single call are printed before each benchmark.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crepedb_core::{memory::MemoryBackend, types::SnapshotId, CrepeDB};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// Counts the allocations of the process.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const KEYS: u32 = 1000;
+
+/// Count the allocations of `f`.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// A database with `KEYS` keys in a versioned and a basic table, written
+/// over a chain of snapshots.
+fn setup() -> (CrepeDB<MemoryBackend>, SnapshotId) {
+    let db = CrepeDB::new(MemoryBackend::new());
+
+    let wtxn = db.write(None).unwrap();
+    wtxn.create_versioned_table("v").unwrap();
+    wtxn.create_basic_table("b").unwrap();
+    let mut snapshot = wtxn.commit().unwrap();
+
+    for chunk in 0..10 {
+        let wtxn = db.write(Some(snapshot)).unwrap();
+        {
+            let mut v = wtxn.open_table("v").unwrap();
+            let mut b = wtxn.open_table("b").unwrap();
+
+            for i in (chunk..KEYS).step_by(10) {
+                v.set(i.to_be_bytes(), [chunk as u8; 32]).unwrap();
+                b.set(i.to_be_bytes(), [chunk as u8; 32]).unwrap();
+            }
+        }
+        snapshot = wtxn.commit().unwrap();
+    }
+
+    (db, snapshot)
+}
+
+fn bench_get(c: &mut Criterion) {
+    let (db, snapshot) = setup();
+    let rtxn = db.read(Some(snapshot)).unwrap();
+
+    for name in ["v", "b"] {
+        let table = rtxn.open_table(name).unwrap();
+        let key = 500u32.to_be_bytes();
+
+        println!(
+            "get {name}: {} allocations, get_ref {name}: {} allocations",
+            allocations(|| {
+                black_box(table.get(key).unwrap());
+            }),
+            allocations(|| {
+                black_box(table.get_ref(key).unwrap());
+            }),
+        );
+
+        c.bench_function(&format!("get {name}"), |b| {
+            let mut i = 0u32;
+            b.iter(|| {
+                i = (i + 1) % KEYS;
+                black_box(table.get(i.to_be_bytes()).unwrap())
+            })
+        });
+
+        c.bench_function(&format!("get_ref {name}"), |b| {
+            let mut i = 0u32;
+            b.iter(|| {
+                i = (i + 1) % KEYS;
+                black_box(table.get_ref(i.to_be_bytes()).unwrap().map(|v| v.len()))
+            })
+        });
+    }
+}
+
+fn bench_set(c: &mut Criterion) {
+    let (db, snapshot) = setup();
+    let wtxn = db.write(Some(snapshot)).unwrap();
+
+    for name in ["v", "b"] {
+        let mut table = wtxn.open_table(name).unwrap();
+        let value = [7u8; 32];
+
+        // The memory backend copies a table on its first write.
+        table.set(KEYS.to_be_bytes(), value).unwrap();
+
+        println!(
+            "set {name}: {} allocations",
+            allocations(|| table.set(KEYS.to_be_bytes(), value).unwrap()),
+        );
+
+        c.bench_function(&format!("set {name}"), |b| {
+            let mut i = 0u32;
+            b.iter(|| {
+                i = (i + 1) % KEYS;
+                table.set(i.to_be_bytes(), value).unwrap()
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench_get, bench_set);
+criterion_main!(benches);
@@ -4,7 +4,12 @@
 //! to be used with CrepeDB. The backend abstraction allows CrepeDB to work with
 //! different underlying storage engines.
 
-use core::fmt::{Debug, Display};
+use core::{
+    any::Any,
+    fmt::{Debug, Display},
+};
+
+use alloc::vec::Vec;
 
 use crate::types::Bytes;
 
@@ -31,14 +36,35 @@ pub trait Backend: Sized + 'static {
     ///
     /// Write transactions allow modifications to the database.
     fn write_txn(&self) -> Result<Self::WriteTxn<'_>, Self::Error>;
+
+    /// Whether `error` is a conflict with a concurrent write transaction.
+    ///
+    /// A write transaction failing with a conflict can be retried. The default
+    /// returns `false`, which fits backends that serialise write transactions.
+    fn is_conflict(error: &Self::Error) -> bool {
+        let _ = error;
+        false
+    }
 }
 
 /// Trait for backend error types.
 ///
-/// Backend errors must be debuggable, displayable, and have a static lifetime.
-pub trait BackendError: Debug + Display + 'static {}
+/// Backend errors must be debuggable, displayable, thread-safe and have a
+/// static lifetime, so that [`Error`](crate::Error) can be sent to other
+/// threads.
+pub trait BackendError: Debug + Display + Send + Sync + 'static {
+    /// Get the error as [`Any`], to downcast it to the concrete error type.
+    fn as_any(&self) -> &dyn Any;
+}
 
-impl<T> BackendError for T where T: Debug + Display + 'static {}
+impl<T> BackendError for T
+where
+    T: Debug + Display + Send + Sync + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
 
 /// Trait for read transactions.
 ///
@@ -73,6 +99,45 @@ pub trait WriteTxn<E> {
     /// * `table` - The name of the table to open
     fn open_table(&self, table: &str) -> Result<Self::Table<'_>, E>;
 
+    /// Set a savepoint in the transaction.
+    ///
+    /// Returns `false` if the backend has no native savepoints, CrepeDB then
+    /// keeps an undo log of the changes itself. The default returns `false`.
+    fn set_savepoint(&self) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    /// Undo every change since the most recent savepoint and remove it.
+    ///
+    /// Only called after [`set_savepoint`](Self::set_savepoint) returned
+    /// `true`.
+    fn rollback_to_savepoint(&self) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Remove a table created in this transaction, with every row in it.
+    ///
+    /// Called when a rollback to a savepoint kept by CrepeDB undoes the
+    /// creation of the table, after every table of the transaction was
+    /// closed. The default deletes every row, which fits backends where an
+    /// empty table cannot be told apart from a missing one.
+    fn remove_table(&self, table: &str) -> Result<(), E> {
+        let mut table = self.open_table(table)?;
+
+        let mut keys = Vec::new();
+        let mut range = table.range(&[], None)?;
+        while let Some(key) = range.back_key()? {
+            keys.push(key);
+        }
+        drop(range);
+
+        for key in keys {
+            table.del(&key)?;
+        }
+
+        Ok(())
+    }
+
     /// Commit the write transaction.
     ///
     /// This persists all changes made during the transaction.
@@ -88,6 +153,14 @@ pub trait ReadTable<E> {
     where
         Self: 'a;
 
+    /// The value type returned by [`get_ref`](Self::get_ref).
+    ///
+    /// Backends which can lend stored bytes return a guard or slice borrowing
+    /// them, the others return [`Bytes`].
+    type Value<'a>: AsRef<[u8]>
+    where
+        Self: 'a;
+
     /// Get the value associated with a key.
     ///
     /// Returns `None` if the key does not exist.
@@ -95,15 +168,28 @@ pub trait ReadTable<E> {
     /// # Arguments
     ///
     /// * `key` - The key to look up
-    fn get(&self, key: Bytes) -> Result<Option<Bytes>, E>;
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, E>;
+
+    /// Get the value associated with a key without copying it, if the backend
+    /// can lend it.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to look up
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, E>;
 
     /// Create a range iterator over keys.
     ///
+    /// The bounds are only borrowed for the call, backends copy them if the
+    /// iterator needs them later.
+    ///
     /// # Arguments
     ///
     /// * `begin` - The inclusive start of the range
-    /// * `end` - The exclusive end of the range
-    fn range(&self, begin: Bytes, end: Bytes) -> Result<Self::Range<'_>, E>;
+    /// * `end` - The exclusive end of the range, `None` for no end
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, E>;
 
     /// Get the name of the table.
     fn name(&self) -> &str;
@@ -121,14 +207,14 @@ pub trait WriteTable<E>: ReadTable<E> {
     ///
     /// * `key` - The key to set
     /// * `value` - The value to associate with the key
-    fn set(&mut self, key: Bytes, value: Bytes) -> Result<(), E>;
+    fn set(&mut self, key: &[u8], value: &[u8]) -> Result<(), E>;
 
     /// Delete a key from the table.
     ///
     /// # Arguments
     ///
     /// * `key` - The key to delete
-    fn del(&mut self, key: Bytes) -> Result<(), E>;
+    fn del(&mut self, key: &[u8]) -> Result<(), E>;
 }
 
 /// Trait for range iterators.
@@ -139,4 +225,12 @@ pub trait Range<E> {
     ///
     /// Returns `None` when the iteration is complete.
     fn back(&mut self) -> Result<Option<(Bytes, Bytes)>, E>;
+
+    /// Get the key of the next key-value pair in the range.
+    ///
+    /// Backends which can skip copying the value override it, the default
+    /// calls [`back`](Self::back).
+    fn back_key(&mut self) -> Result<Option<Bytes>, E> {
+        Ok(self.back()?.map(|(key, _)| key))
+    }
 }
@@ -0,0 +1,269 @@
+//! Codecs between typed keys and values and their stored bytes.
+
+use alloc::string::{String, ToString};
+
+use crate::{types::Bytes, Error, Result};
+
+/// Encode values of type `T` to bytes and decode them back.
+///
+/// Codecs are zero-sized marker types, they are only used as type
+/// parameters of [`TableDef`](crate::TableDef) and
+/// [`TypedTable`](crate::TypedTable).
+pub trait Codec<T> {
+    /// Encode a value to bytes.
+    fn encode(value: &T) -> Result<Bytes>;
+
+    /// Decode a value from bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not a valid encoding of `T`.
+    fn decode(bytes: &[u8]) -> Result<T>;
+}
+
+/// The codecs of the keys and values of a typed table.
+///
+/// Implemented by every built-in codec for the keys and values it can
+/// encode, and by `(KC, VC)` pairs to encode keys with `KC` and values with
+/// `VC`.
+pub trait TableCodec<K, V> {
+    /// The codec of keys.
+    type Key: Codec<K>;
+
+    /// The codec of values.
+    type Value: Codec<V>;
+}
+
+impl<K, V, KC, VC> TableCodec<K, V> for (KC, VC)
+where
+    KC: Codec<K>,
+    VC: Codec<V>,
+{
+    type Key = KC;
+    type Value = VC;
+}
+
+macro_rules! table_codec {
+    ($($codec:ty),*) => {
+        $(
+            impl<K, V> TableCodec<K, V> for $codec
+            where
+                $codec: Codec<K> + Codec<V>,
+            {
+                type Key = $codec;
+                type Value = $codec;
+            }
+        )*
+    };
+}
+
+table_codec!(Raw, BigEndian, Fixed);
+
+#[cfg(feature = "bincode")]
+table_codec!(Bincode);
+
+#[cfg(feature = "borsh")]
+table_codec!(Borsh);
+
+/// Store bytes and strings as they are.
+///
+/// The stored order is the order of `Bytes` and `String`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Raw;
+
+impl Codec<Bytes> for Raw {
+    fn encode(value: &Bytes) -> Result<Bytes> {
+        Ok(value.clone())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Bytes> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Codec<String> for Raw {
+    fn encode(value: &String) -> Result<Bytes> {
+        Ok(value.as_bytes().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<String> {
+        String::from_utf8(bytes.to_vec()).map_err(|e| Error::CodecError(e.to_string()))
+    }
+}
+
+/// Store integers as fixed-size big-endian bytes.
+///
+/// The sign bit of signed integers is flipped, so the stored order is the
+/// numeric order. Use it for keys of ordered ranges.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BigEndian;
+
+macro_rules! big_endian {
+    ($($t:ty => $sign:expr),*) => {
+        $(
+            impl Codec<$t> for BigEndian {
+                fn encode(value: &$t) -> Result<Bytes> {
+                    let mut bytes = value.to_be_bytes();
+                    bytes[0] ^= $sign;
+
+                    Ok(bytes.to_vec())
+                }
+
+                fn decode(bytes: &[u8]) -> Result<$t> {
+                    let mut bytes: [u8; core::mem::size_of::<$t>()] = bytes
+                        .try_into()
+                        .map_err(|_| Error::WrongBytesLength(bytes.len()))?;
+                    bytes[0] ^= $sign;
+
+                    Ok(<$t>::from_be_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+big_endian!(
+    u8 => 0, u16 => 0, u32 => 0, u64 => 0, u128 => 0,
+    i8 => 0x80, i16 => 0x80, i32 => 0x80, i64 => 0x80, i128 => 0x80
+);
+
+/// Store fixed-size byte arrays, such as hashes and addresses, as they are.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Fixed;
+
+impl<const N: usize> Codec<[u8; N]> for Fixed {
+    fn encode(value: &[u8; N]) -> Result<Bytes> {
+        Ok(value.to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<[u8; N]> {
+        bytes
+            .try_into()
+            .map_err(|_| Error::WrongBytesLength(bytes.len()))
+    }
+}
+
+/// Store `serde` types encoded by `bincode` with its standard config.
+///
+/// The stored order is not the order of the values, use [`BigEndian`] or
+/// [`Fixed`] for keys of ordered ranges.
+#[cfg(feature = "bincode")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bincode;
+
+#[cfg(feature = "bincode")]
+impl<T> Codec<T> for Bincode
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn encode(value: &T) -> Result<Bytes> {
+        bincode::serde::encode_to_vec(value, bincode::config::standard())
+            .map_err(|e| Error::CodecError(e.to_string()))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T> {
+        let (value, len) = bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+            .map_err(|e| Error::CodecError(e.to_string()))?;
+
+        if len != bytes.len() {
+            return Err(Error::WrongBytesLength(bytes.len()));
+        }
+
+        Ok(value)
+    }
+}
+
+/// Store types encoded by `borsh`.
+///
+/// The stored order is not the order of the values, use [`BigEndian`] or
+/// [`Fixed`] for keys of ordered ranges.
+#[cfg(feature = "borsh")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Borsh;
+
+#[cfg(feature = "borsh")]
+impl<T> Codec<T> for Borsh
+where
+    T: borsh::BorshSerialize + borsh::BorshDeserialize,
+{
+    fn encode(value: &T) -> Result<Bytes> {
+        borsh::to_vec(value).map_err(|e| Error::CodecError(e.to_string()))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T> {
+        borsh::from_slice(bytes).map_err(|e| Error::CodecError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec, vec::Vec};
+
+    use super::*;
+
+    fn roundtrip<C: Codec<T>, T: PartialEq + core::fmt::Debug>(values: Vec<T>) -> Vec<Bytes> {
+        values
+            .iter()
+            .map(|value| {
+                let bytes = C::encode(value).unwrap();
+                assert_eq!(&C::decode(&bytes).unwrap(), value);
+                bytes
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_big_endian_order() {
+        let bytes = roundtrip::<BigEndian, i64>(vec![i64::MIN, -300, -1, 0, 1, 300, i64::MAX]);
+        assert!(bytes.windows(2).all(|w| w[0] < w[1]));
+
+        let bytes = roundtrip::<BigEndian, u16>(vec![0, 1, 255, 256, u16::MAX]);
+        assert!(bytes.windows(2).all(|w| w[0] < w[1]));
+
+        let bytes = roundtrip::<BigEndian, i8>(vec![i8::MIN, -1, 0, i8::MAX]);
+        assert!(bytes.windows(2).all(|w| w[0] < w[1]));
+
+        assert!(matches!(
+            <BigEndian as Codec<u32>>::decode(&[1, 2, 3]),
+            Err(Error::WrongBytesLength(3))
+        ));
+    }
+
+    #[test]
+    fn test_raw_and_fixed() {
+        roundtrip::<Raw, Bytes>(vec![vec![], vec![1, 2, 3]]);
+        roundtrip::<Raw, String>(vec![String::new(), "key".into()]);
+        roundtrip::<Fixed, [u8; 4]>(vec![[0; 4], [1, 2, 3, 4]]);
+
+        assert!(matches!(
+            <Raw as Codec<String>>::decode(&[0xff]),
+            Err(Error::CodecError(_))
+        ));
+        assert!(matches!(
+            <Fixed as Codec<[u8; 4]>>::decode(&[1, 2]),
+            Err(Error::WrongBytesLength(2))
+        ));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode() {
+        roundtrip::<Bincode, (u32, String, Option<Vec<u8>>)>(vec![
+            (0, String::new(), None),
+            (7, "value".into(), Some(vec![1, 2])),
+        ]);
+
+        assert!(<Bincode as Codec<(u32, String)>>::decode(&[1, 9]).is_err());
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh() {
+        roundtrip::<Borsh, (u32, String, Option<Vec<u8>>)>(vec![
+            (0, String::new(), None),
+            (7, "value".into(), Some(vec![1, 2])),
+        ]);
+
+        assert!(<Borsh as Codec<(u32, String)>>::decode(&[1, 9]).is_err());
+    }
+}
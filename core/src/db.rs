@@ -1,25 +1,47 @@
-use core::marker::PhantomData;
+use core::{cell::RefCell, marker::PhantomData};
 
 use crate::{
     backend::Backend,
-    types::{SnapshotId, Version},
+    types::{Hash, SnapshotId, Version},
     utils, Error, ReadTxn, Result, WriteTxn,
 };
 
 /// Information about a database snapshot.
 ///
-/// Contains the version number and the parent snapshot ID for a given snapshot.
+/// Contains the version number, the parent snapshot ID, the state root and the
+/// state hash for a given snapshot.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct SnapshotInfo {
     /// The version number of the snapshot.
     pub version: Version,
     /// The parent snapshot ID (the previous snapshot in the lineage).
     pub parent_snapshot_id: SnapshotId,
+    /// The Merkle root of the versioned tables at the snapshot.
+    ///
+    /// `None` if the snapshot was committed without state roots, see
+    /// `CrepeDB::with_state_root`.
+    pub state_root: Option<Hash>,
+    /// The order-independent hash of the entries of the versioned tables at
+    /// the snapshot.
+    ///
+    /// `None` if the snapshot was committed without state hashes, see
+    /// `CrepeDB::with_state_hash`.
+    pub state_hash: Option<Hash>,
 }
 
+/// Default number of retries of [`CrepeDB::write_with_retry`].
+pub const DEFAULT_MAX_RETRIES: usize = 16;
+
 /// Versioned and forkable Database
 pub struct CrepeDB<B> {
     pub(crate) backend: B,
+    pub(crate) max_retries: usize,
+
+    #[cfg(feature = "merkle")]
+    pub(crate) state_root: bool,
+
+    #[cfg(feature = "state-hash")]
+    pub(crate) state_hash: bool,
 }
 
 impl<B> CrepeDB<B>
@@ -28,7 +50,47 @@ where
 {
     /// Create database using backend.
     pub fn new(backend: B) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            max_retries: DEFAULT_MAX_RETRIES,
+            #[cfg(feature = "merkle")]
+            state_root: false,
+            #[cfg(feature = "state-hash")]
+            state_hash: false,
+        }
+    }
+
+    /// Set how often [`write_with_retry`](Self::write_with_retry) retries a
+    /// snapshot after a conflict, [`DEFAULT_MAX_RETRIES`] by default.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set whether the root snapshot is created with a state root, `false` by
+    /// default.
+    ///
+    /// A snapshot gets a [state root](SnapshotInfo::state_root) when its
+    /// parent has one, so this only needs to be set when creating the root.
+    /// A snapshot committed without the `merkle` feature has no state root,
+    /// and neither do its descendants.
+    #[cfg(feature = "merkle")]
+    pub fn with_state_root(mut self, state_root: bool) -> Self {
+        self.state_root = state_root;
+        self
+    }
+
+    /// Set whether the root snapshot is created with a state hash, `false` by
+    /// default.
+    ///
+    /// A snapshot gets a [state hash](SnapshotInfo::state_hash) when its
+    /// parent has one, so this only needs to be set when creating the root.
+    /// A snapshot committed without the `state-hash` feature has no state
+    /// hash, and neither do its descendants.
+    #[cfg(feature = "state-hash")]
+    pub fn with_state_hash(mut self, state_hash: bool) -> Self {
+        self.state_hash = state_hash;
+        self
     }
 
     /// Get a reference to the underlying backend.
@@ -58,27 +120,58 @@ where
     }
 
     /// Create a transaction to write data.
+    ///
+    /// The new snapshot ID is allocated in lane 0, see
+    /// [`write_in_lane`](Self::write_in_lane).
     pub fn write(
         &self,
         snapshot_id: Option<SnapshotId>,
+    ) -> Result<WriteTxn<B::WriteTxn<'_>, B::Error>> {
+        self.write_in_lane(snapshot_id, 0)
+    }
+
+    /// Create a transaction to write data, allocating the new snapshot ID in
+    /// `lane`.
+    ///
+    /// Every lane has its own range of snapshot IDs and its own counter, so
+    /// write transactions in different lanes do not write a shared row unless
+    /// they write the same keys of basic tables. Backends with optimistic
+    /// transactions can then commit snapshots on different forks concurrently
+    /// when every writer uses its own lane. The root is always allocated in
+    /// lane 0.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::LaneExhausted`] if all IDs of the lane are allocated.
+    pub fn write_in_lane(
+        &self,
+        snapshot_id: Option<SnapshotId>,
+        lane: u16,
     ) -> Result<WriteTxn<B::WriteTxn<'_>, B::Error>> {
         let txn = self.backend.write_txn().map_err(Error::backend)?;
 
         if let Some(snapshot_id) = snapshot_id {
             let snapshot = utils::snapshot_writer(&txn)?;
 
-            let (version, parent_snapshot_id) = snapshot.read(&snapshot_id)?;
+            let info = snapshot.read_info(&snapshot_id)?;
 
-            let new_snapshot_id = snapshot.read_next_snapshot_id()?;
+            let new_snapshot_id = snapshot.read_next_snapshot_id(lane)?;
 
             drop(snapshot);
 
             Ok(WriteTxn {
                 txn,
-                version: (version.0 + 1).into(),
+                version: (info.version.0 + 1).into(),
                 new_snapshot_id,
-                parent_snapshot_id: Some(parent_snapshot_id),
+                parent_snapshot_id: Some(info.parent_snapshot_id),
                 snapshot_id,
+                savepoints: RefCell::default(),
+                #[cfg(feature = "merkle")]
+                written: info.state_root.map(|_| RefCell::default()),
+                #[cfg(feature = "state-hash")]
+                state_hash: info.state_hash.map(RefCell::new),
+                #[cfg(feature = "state-hash")]
+                parent_ancestors: RefCell::default(),
                 marker: PhantomData,
             })
         } else {
@@ -99,14 +192,70 @@ where
                 new_snapshot_id: SnapshotId::root(),
                 parent_snapshot_id: None,
                 snapshot_id,
+                savepoints: RefCell::default(),
+                #[cfg(feature = "merkle")]
+                written: self.state_root.then(RefCell::default),
+                #[cfg(feature = "state-hash")]
+                state_hash: self.state_hash.then(RefCell::default),
+                #[cfg(feature = "state-hash")]
+                parent_ancestors: RefCell::default(),
                 marker: PhantomData,
             })
         }
     }
 
+    /// Build a snapshot on `parent` with `f` and commit it, retrying on
+    /// conflicts.
+    ///
+    /// `f` is called with a write transaction in `lane`, see
+    /// [`write_in_lane`](Self::write_in_lane). If `f` or the commit fails
+    /// with a conflict with a concurrent write transaction, as reported by
+    /// [`Backend::is_conflict`], the transaction is dropped and `f` is called
+    /// again with a new one, up to the configured number of retries.
+    ///
+    /// # Returns
+    ///
+    /// The snapshot ID of the newly created snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error which is not a conflict, or the last conflict
+    /// if all retries failed.
+    pub fn write_with_retry<F>(&self, parent: SnapshotId, lane: u16, mut f: F) -> Result<SnapshotId>
+    where
+        F: FnMut(&WriteTxn<B::WriteTxn<'_>, B::Error>) -> Result<()>,
+    {
+        let mut retries = 0;
+
+        loop {
+            let txn = self.write_in_lane(Some(parent.clone()), lane)?;
+
+            let res = match f(&txn) {
+                Ok(()) => txn.commit(),
+                Err(e) => Err(e),
+            };
+
+            match res {
+                Err(e) if retries < self.max_retries && self.is_conflict(&e) => {
+                    retries += 1;
+                    log::debug!("Retry snapshot on {parent:?} after conflict: {e:?}");
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Whether `error` is a conflict with a concurrent write transaction.
+    pub fn is_conflict(&self, error: &Error) -> bool {
+        error
+            .backend_error::<B::Error>()
+            .is_some_and(B::is_conflict)
+    }
+
     /// Get snapshot information by snapshot ID.
     ///
-    /// Returns the version and parent snapshot ID for the given snapshot.
+    /// Returns the version, parent snapshot ID and state root for the given
+    /// snapshot.
     ///
     /// # Arguments
     ///
@@ -117,6 +266,8 @@ where
     /// A `SnapshotInfo` containing:
     /// - `version`: The version number of the snapshot
     /// - `parent_snapshot_id`: The parent snapshot ID (the previous snapshot)
+    /// - `state_root`: The Merkle root of the versioned tables, if maintained
+    /// - `state_hash`: The hash of the versioned tables, if maintained
     ///
     /// # Errors
     ///
@@ -124,10 +275,134 @@ where
     pub fn get_snapshot_info(&self, snapshot_id: SnapshotId) -> Result<SnapshotInfo> {
         let txn = self.backend.read_txn().map_err(Error::backend)?;
         let snapshot = utils::snapshot_reader(&txn)?;
-        let (version, parent_snapshot_id) = snapshot.read(&snapshot_id)?;
-        Ok(SnapshotInfo {
-            version,
-            parent_snapshot_id,
-        })
+        snapshot.read_info(&snapshot_id)
+    }
+
+    /// Get the state hash of a snapshot.
+    ///
+    /// The state hash only depends on the entries of the versioned tables at
+    /// the snapshot, so two snapshots with equal entries have equal hashes
+    /// however they were written. It is cheaper to maintain than a state
+    /// root, but there are no proofs against it.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot_id` - The snapshot ID to query
+    ///
+    /// # Returns
+    ///
+    /// The state hash, `None` if the snapshot was committed without state
+    /// hashes, see `CrepeDB::with_state_hash`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot ID doesn't exist or if there's a backend error.
+    pub fn state_hash(&self, snapshot_id: SnapshotId) -> Result<Option<Hash>> {
+        Ok(self.get_snapshot_info(snapshot_id)?.state_hash)
+    }
+}
+
+#[doc(hidden)]
+#[cfg(feature = "tests")]
+pub mod lane_tests {
+    use alloc::vec;
+
+    use crate::{
+        backend::{Backend, WriteTable, WriteTxn},
+        types::SnapshotId,
+        utils::{self, consts},
+        CrepeDB, Error, Result,
+    };
+
+    pub fn test_lanes(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+
+        let txn = db.write_in_lane(None, 7)?;
+        txn.create_versioned_table("t")?;
+        let root = txn.commit()?;
+
+        // The root is always allocated in lane 0.
+        assert_eq!(root, SnapshotId::root());
+
+        let mut tips = vec![];
+        for lane in [0, 1, 2, u16::MAX] {
+            let mut tip = root.clone();
+
+            for i in 0..3u8 {
+                let txn = db.write_in_lane(Some(tip.clone()), lane)?;
+                txn.open_table("t")?.set(vec![0], vec![lane as u8, i])?;
+                tip = txn.commit()?;
+
+                assert_eq!(tip.lane(), lane);
+            }
+
+            tips.push(tip);
+        }
+
+        // Every lane counts from its own start.
+        assert_eq!(tips[0], SnapshotId(3));
+        assert_eq!(tips[1], SnapshotId((1 << 48) + 2));
+        assert_eq!(tips[3], SnapshotId((u64::MAX << 48) + 2));
+
+        // Forks continue across lanes.
+        let fork = db.write_with_retry(tips[1].clone(), 2, |txn| {
+            txn.open_table("t")?.set(vec![1], vec![1])?;
+            Ok(())
+        })?;
+        assert_eq!(fork, SnapshotId((2 << 48) + 3));
+        assert_eq!(db.get_snapshot_info(fork.clone())?.version.0, 4);
+
+        let txn = db.read(Some(fork))?;
+        let table = txn.open_table("t")?;
+        assert_eq!(table.get(vec![0])?, Some(vec![1, 2]));
+        assert_eq!(table.get(vec![1])?, Some(vec![1]));
+
+        for (lane, tip) in [0, 1, 2, u16::MAX].into_iter().zip(&tips) {
+            let txn = db.read(Some(tip.clone()))?;
+            let table = txn.open_table("t")?;
+            assert_eq!(table.get(vec![0])?, Some(vec![lane as u8, 2]));
+            assert_eq!(table.get(vec![1])?, None);
+        }
+
+        let report = db.verify()?;
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.snapshots, 14);
+
+        // Errors other than conflicts are returned at once.
+        let mut calls = 0;
+        let res = db.write_with_retry(root.clone(), 0, |_| {
+            calls += 1;
+            Err(Error::MissingTable)
+        });
+        assert!(matches!(res, Err(Error::MissingTable)));
+        assert!(!db.is_conflict(&res.unwrap_err()));
+        assert_eq!(calls, 1);
+
+        // Exhaust lane 5 and the last lane.
+        let txn = db.backend().write_txn().map_err(Error::backend)?;
+        {
+            let mut table = txn
+                .open_table(consts::SNAPSHOT_TABLE)
+                .map_err(Error::backend)?;
+
+            table
+                .set(&utils::next_key(5), &(6u64 << 48).to_be_bytes())
+                .map_err(Error::backend)?;
+            table
+                .set(&utils::next_key(u16::MAX), &u64::MAX.to_be_bytes())
+                .map_err(Error::backend)?;
+        }
+        txn.commit().map_err(Error::backend)?;
+
+        assert!(matches!(
+            db.write_in_lane(Some(root.clone()), 5),
+            Err(Error::LaneExhausted(5))
+        ));
+        assert!(matches!(
+            db.write_in_lane(Some(root), u16::MAX),
+            Err(Error::LaneExhausted(u16::MAX))
+        ));
+
+        Ok(())
     }
 }
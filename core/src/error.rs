@@ -1,8 +1,11 @@
 //! Error types for CrepeDB operations.
 
-use alloc::boxed::Box;
+use alloc::{boxed::Box, string::String};
 
-use crate::{backend::BackendError, types::SnapshotId};
+use crate::{
+    backend::BackendError,
+    types::{SnapshotId, TableType},
+};
 
 /// Errors that can occur during CrepeDB operations.
 #[derive(Debug)]
@@ -34,6 +37,36 @@ pub enum Error {
     /// An unknown data operation type was encountered.
     UnexpectedDataOpType(u8),
 
+    /// All snapshot IDs of a lane are allocated.
+    LaneExhausted(u16),
+
+    /// The savepoint was already released.
+    InvalidSavepoint,
+
+    /// A typed key or value could not be encoded or decoded.
+    CodecError(String),
+
+    /// A typed table was opened with a definition of another table type, the
+    /// type of the stored table.
+    WrongTableType(TableType),
+
+    /// A node of a Merkle tree is missing or does not match its hash.
+    InvalidMerkleNode,
+
+    /// The snapshot has no state root to prove values against.
+    MissingStateRoot,
+
+    /// The snapshot has no state hash to check a delta against.
+    MissingStateHash,
+
+    /// An archive is malformed, of an unsupported version, or does not match
+    /// its checksum.
+    InvalidArchive(&'static str),
+
+    /// Reading or writing an archive failed.
+    #[cfg(feature = "export")]
+    Io(std::io::Error),
+
     /// An error from the underlying storage backend.
     BackendError(Box<dyn BackendError>),
 }
@@ -47,6 +80,15 @@ impl Error {
     pub fn backend(e: impl BackendError + 'static) -> Self {
         Self::BackendError(Box::new(e))
     }
+
+    /// Get the wrapped backend error if it has type `E`.
+    pub fn backend_error<E: BackendError>(&self) -> Option<&E> {
+        match self {
+            // Deref the box, it is a `BackendError` itself.
+            Self::BackendError(e) => (**e).as_any().downcast_ref(),
+            _ => None,
+        }
+    }
 }
 
 /// Result type alias for CrepeDB operations.
@@ -0,0 +1,1154 @@
+//! Export and import of the state of snapshots.
+//!
+//! An archive holds the entries of tables at a snapshot, without their
+//! history. Versioned tables are read at the snapshot, basic tables hold their
+//! current entries. A delta holds the changes of the versioned tables from one
+//! snapshot to another, with the state hashes of both. Integers are big-endian
+//! and byte strings are prefixed by their length as a `u32`:
+//!
+//! ```text
+//! archive = "CREPEARC" | format version (u16) | kind (u8, 0) | record* | end
+//! delta   = "CREPEARC" | format version (u16) | kind (u8, 1)
+//!         | base state hash (32) | target state hash (32) | record* | end
+//! table   = 0x01 | table type (u8, 1 basic, 2 versioned) | name
+//! set     = 0x02 | key | value
+//! del     = 0x03 | key
+//! end     = 0x00 | number of records (u64) | SHA-256 of the bytes before
+//! ```
+//!
+//! The entries of a table follow its record in increasing key order. Only
+//! deltas have deletes.
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    string::String,
+    vec::Vec,
+};
+use core::cmp::Ordering;
+use std::io::{self, Read, Write};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    backend::{
+        Backend, BackendError, Range, ReadTable as BackendReadTable, ReadTxn as BackendReadTxn,
+        WriteTxn as BackendWriteTxn,
+    },
+    types::{Bytes, DataOp, Hash, SnapshotId, TableType, Version},
+    utils::{consts, scan},
+    CrepeDB, Error, ReadTable, Result, WriteTxn,
+};
+
+/// Magic bytes at the start of archives.
+pub const ARCHIVE_MAGIC: [u8; 8] = *b"CREPEARC";
+
+/// Version of the archive format.
+pub const ARCHIVE_VERSION: u16 = 1;
+
+/// Kind of archives with the state of a snapshot.
+const KIND_STATE: u8 = 0;
+
+/// Kind of archives with the changes between two snapshots.
+const KIND_DELTA: u8 = 1;
+
+const RECORD_END: u8 = 0;
+const RECORD_TABLE: u8 = 1;
+const RECORD_SET: u8 = 2;
+const RECORD_DEL: u8 = 3;
+
+/// Writes records and the checksum of an archive.
+struct ArchiveWriter<W> {
+    writer: W,
+    hasher: Sha256,
+    records: u64,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    fn new(writer: W, kind: u8) -> Result<Self> {
+        let mut archive = Self {
+            writer,
+            hasher: Sha256::new(),
+            records: 0,
+        };
+
+        archive.write(&ARCHIVE_MAGIC)?;
+        archive.write(&ARCHIVE_VERSION.to_be_bytes())?;
+        archive.write(&[kind])?;
+
+        Ok(archive)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.hasher.update(bytes);
+        self.writer.write_all(bytes).map_err(Error::Io)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        let len = u32::try_from(bytes.len())
+            .map_err(|_| Error::InvalidArchive("byte string longer than 4 GiB"))?;
+
+        self.write(&len.to_be_bytes())?;
+        self.write(bytes)
+    }
+
+    fn table(&mut self, name: &str, ty: &TableType) -> Result<()> {
+        self.records += 1;
+        self.write(&[RECORD_TABLE, ty.to_byte()])?;
+        self.write_bytes(name.as_bytes())
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.records += 1;
+        self.write(&[RECORD_SET])?;
+        self.write_bytes(key)?;
+        self.write_bytes(value)
+    }
+
+    fn del(&mut self, key: &[u8]) -> Result<()> {
+        self.records += 1;
+        self.write(&[RECORD_DEL])?;
+        self.write_bytes(key)
+    }
+
+    fn finish(mut self) -> Result<()> {
+        self.write(&[RECORD_END])?;
+        let records = self.records;
+        self.write(&records.to_be_bytes())?;
+
+        let checksum = self.hasher.finalize();
+        self.writer.write_all(&checksum).map_err(Error::Io)?;
+        self.writer.flush().map_err(Error::Io)
+    }
+}
+
+/// A record read from an archive.
+enum Record {
+    Table(String, TableType),
+    Set(Bytes, Bytes),
+    Del(Bytes),
+    End,
+}
+
+/// Reads records of an archive, checking its checksum at the end.
+struct ArchiveReader<R> {
+    reader: R,
+    hasher: Sha256,
+    records: u64,
+}
+
+impl<R: Read> ArchiveReader<R> {
+    fn new(reader: R, kind: u8) -> Result<Self> {
+        let mut archive = Self {
+            reader,
+            hasher: Sha256::new(),
+            records: 0,
+        };
+
+        if archive.read::<8>()? != ARCHIVE_MAGIC {
+            return Err(Error::InvalidArchive("not an archive"));
+        }
+
+        if u16::from_be_bytes(archive.read()?) != ARCHIVE_VERSION {
+            return Err(Error::InvalidArchive("unsupported format version"));
+        }
+
+        if archive.read::<1>()? != [kind] {
+            return Err(Error::InvalidArchive("unexpected archive kind"));
+        }
+
+        Ok(archive)
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut bytes = [0; N];
+        self.reader.read_exact(&mut bytes).map_err(Error::Io)?;
+        self.hasher.update(bytes);
+
+        Ok(bytes)
+    }
+
+    fn read_bytes(&mut self) -> Result<Bytes> {
+        let len = u32::from_be_bytes(self.read()?) as u64;
+
+        // Read through `take`, so a corrupted length does not allocate more
+        // than the archive holds.
+        let mut bytes = Vec::new();
+        (&mut self.reader)
+            .take(len)
+            .read_to_end(&mut bytes)
+            .map_err(Error::Io)?;
+
+        if bytes.len() as u64 != len {
+            return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+
+        self.hasher.update(&bytes);
+
+        Ok(bytes)
+    }
+
+    fn record(&mut self) -> Result<Record> {
+        let [tag] = self.read()?;
+
+        match tag {
+            RECORD_TABLE => {
+                let [ty] = self.read()?;
+                let name = String::from_utf8(self.read_bytes()?)
+                    .map_err(|_| Error::InvalidArchive("table name is not UTF-8"))?;
+
+                self.records += 1;
+                Ok(Record::Table(name, TableType::from_byte(ty)?))
+            }
+            RECORD_SET => {
+                let key = self.read_bytes()?;
+                let value = self.read_bytes()?;
+
+                self.records += 1;
+                Ok(Record::Set(key, value))
+            }
+            RECORD_DEL => {
+                let key = self.read_bytes()?;
+
+                self.records += 1;
+                Ok(Record::Del(key))
+            }
+            RECORD_END => {
+                let records = u64::from_be_bytes(self.read()?);
+                let expected = self.hasher.clone().finalize();
+
+                let mut checksum = [0; 32];
+                self.reader.read_exact(&mut checksum).map_err(Error::Io)?;
+
+                if checksum[..] != expected[..] {
+                    return Err(Error::InvalidArchive("checksum mismatch"));
+                }
+
+                if records != self.records {
+                    return Err(Error::InvalidArchive("wrong number of records"));
+                }
+
+                Ok(Record::End)
+            }
+            _ => Err(Error::InvalidArchive("unknown record")),
+        }
+    }
+}
+
+/// Number of entries of a table read or written at once by an import.
+const PAGE_SIZE: usize = 1024;
+
+/// A table being imported, replacing its entries at the parent snapshot.
+///
+/// Entries are written, and entries at the parent snapshot read, in pages.
+/// The table is only opened to write a page, as some backends can not open it
+/// for reading at the same time.
+struct TableImport<'a, T, E> {
+    txn: &'a WriteTxn<T, E>,
+    name: String,
+    ty: TableType,
+
+    /// Entries to write, `None` for deletes.
+    writes: VecDeque<(Bytes, Option<Bytes>)>,
+
+    /// Entries at the parent snapshot read and not reached yet.
+    parent: VecDeque<(Bytes, Bytes)>,
+
+    /// Key to read the next entries at the parent snapshot from, `None` once
+    /// they are all read.
+    next_parent: Option<Bytes>,
+
+    /// The last imported key.
+    last: Option<Bytes>,
+}
+
+impl<T, E> TableImport<'_, T, E>
+where
+    T: BackendWriteTxn<E>,
+    E: BackendError,
+{
+    fn check_order(&self, key: &Bytes) -> Result<()> {
+        if self.last.as_ref().is_some_and(|last| last >= key) {
+            return Err(Error::InvalidArchive("keys out of order"));
+        }
+
+        Ok(())
+    }
+
+    fn set(&mut self, key: Bytes, value: Bytes) -> Result<()> {
+        self.write(key, Some(value))
+    }
+
+    fn del(&mut self, key: Bytes) -> Result<()> {
+        self.write(key, None)
+    }
+
+    fn write(&mut self, key: Bytes, value: Option<Bytes>) -> Result<()> {
+        self.check_order(&key)?;
+
+        self.last = Some(key.clone());
+        self.writes.push_back((key, value));
+
+        if self.writes.len() >= PAGE_SIZE {
+            self.flush(false)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the next page of entries at the parent snapshot.
+    fn read_parent(&mut self) -> Result<()> {
+        let Some(from) = self.next_parent.take() else {
+            return Ok(());
+        };
+
+        let Some(table) = self.txn.read_parent(&self.name, self.ty.clone())? else {
+            return Ok(());
+        };
+
+        let page = table
+            .scan(from, None)?
+            .take(PAGE_SIZE)
+            .collect::<Result<Vec<_>>>()?;
+
+        if page.len() == PAGE_SIZE {
+            let (last, _) = page.last().expect("page is full");
+
+            // The smallest key after the last one.
+            let mut next = last.clone();
+            next.push(0);
+            self.next_parent = Some(next);
+        }
+
+        self.parent.extend(page);
+
+        Ok(())
+    }
+
+    /// Write the buffered entries, and delete the entries at the parent
+    /// snapshot before them. All remaining entries at the parent snapshot are
+    /// deleted if `all`.
+    fn flush(&mut self, all: bool) -> Result<()> {
+        loop {
+            if self.parent.is_empty() {
+                self.read_parent()?;
+            }
+
+            let mut table = self.txn.open_table(&self.name)?;
+
+            // Delete the parent's entries before each write, and keep its
+            // entry of the key if the value is the same.
+            while let Some((key, value)) = self.writes.front() {
+                let order = match self.parent.front() {
+                    Some((k, _)) => k.cmp(key),
+                    // More entries at the parent snapshot can come before.
+                    None if self.next_parent.is_some() => break,
+                    None => Ordering::Greater,
+                };
+
+                match order {
+                    Ordering::Less => {
+                        let (k, _) = self.parent.pop_front().expect("checked above");
+                        table.del(&k)?;
+                        continue;
+                    }
+                    Ordering::Equal => {
+                        let (_, v) = self.parent.pop_front().expect("checked above");
+
+                        if value.as_ref() == Some(&v) {
+                            self.writes.pop_front();
+                            continue;
+                        }
+                    }
+                    Ordering::Greater => {}
+                }
+
+                let (key, value) = self.writes.pop_front().expect("checked above");
+
+                match value {
+                    Some(value) => table.set(&key, &value)?,
+                    None => table.del(&key)?,
+                }
+            }
+
+            if self.writes.is_empty() && all {
+                for (key, _) in self.parent.drain(..) {
+                    table.del(&key)?;
+                }
+            }
+
+            let done = if all {
+                self.next_parent.is_none() && self.parent.is_empty()
+            } else {
+                self.writes.is_empty() || self.next_parent.is_none()
+            };
+
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    fn finish(mut self) -> Result<()> {
+        self.flush(true)
+    }
+}
+
+/// The table the entries being read belong to.
+fn current<T>(table: &mut Option<T>) -> Result<&mut T> {
+    table
+        .as_mut()
+        .ok_or(Error::InvalidArchive("entry outside of a table"))
+}
+
+/// Call `f` with the changes of a versioned table from its state in `from`
+/// to its state in `to`, in increasing key order, with `None` for deletes.
+///
+/// If the snapshot of `from` is an ancestor of the snapshot of `to`, only the
+/// keys of the rows written between them are compared. Otherwise both states
+/// are scanned side by side.
+fn changes<T, E>(
+    from: &ReadTable<T, E>,
+    to: &ReadTable<T, E>,
+    f: impl FnMut(Bytes, Option<Bytes>) -> Result<()>,
+) -> Result<()>
+where
+    T: BackendReadTable<E>,
+    E: BackendError,
+{
+    let related = from.view.version <= to.view.version
+        && to.view.ancestor(from.view.version.clone())?.as_ref() == Some(&from.view.snapshot_id);
+
+    if related {
+        written_changes(from, to, f)
+    } else {
+        diff(from.scan([], None)?, to.scan([], None)?, f)
+    }
+}
+
+/// Changes of the keys written after the snapshot of `from` up to the
+/// snapshot of `to`, its descendant.
+fn written_changes<T, E>(
+    from: &ReadTable<T, E>,
+    to: &ReadTable<T, E>,
+    mut f: impl FnMut(Bytes, Option<Bytes>) -> Result<()>,
+) -> Result<()>
+where
+    T: BackendReadTable<E>,
+    E: BackendError,
+{
+    let mut ancestors = BTreeMap::new();
+    let mut written = BTreeMap::new();
+
+    let mut range = to.table.range(&[], None).map_err(Error::backend)?;
+
+    while let Some((row, value)) = range.back().map_err(Error::backend)? {
+        let Some(key_len) = row.len().checked_sub(16) else {
+            continue;
+        };
+
+        let version = Version::from_bytes(&row[key_len..key_len + 8])?;
+
+        if version <= from.view.version || version > to.view.version {
+            continue;
+        }
+
+        let sss = SnapshotId::from_bytes(&row[key_len + 8..])?;
+
+        let ancestor = match ancestors.get(&version) {
+            Some(ancestor) => ancestor,
+            None => {
+                let ancestor = to.view.ancestor(version.clone())?;
+                ancestors.entry(version).or_insert(ancestor)
+            }
+        };
+
+        // Entries of a key are ordered by version, so the last one written
+        // by an ancestor is the value at `to`.
+        if ancestor.as_ref() == Some(&sss) {
+            written.insert(row[..key_len].to_vec(), value);
+        }
+    }
+
+    for (key, value) in written {
+        let value: Option<Bytes> = DataOp::from_bytes(value)?.into();
+
+        if from.get(&key)? != value {
+            f(key, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Changes from the entries `before` to the entries `after`, both in
+/// increasing key order.
+fn diff(
+    before: impl Iterator<Item = Result<(Bytes, Bytes)>>,
+    after: impl Iterator<Item = Result<(Bytes, Bytes)>>,
+    mut f: impl FnMut(Bytes, Option<Bytes>) -> Result<()>,
+) -> Result<()> {
+    let mut before = before.peekable();
+    let mut after = after.peekable();
+
+    loop {
+        let order = match (before.peek(), after.peek()) {
+            (Some(Err(_)), _) => return Err(before.next().unwrap().unwrap_err()),
+            (_, Some(Err(_))) => return Err(after.next().unwrap().unwrap_err()),
+            (Some(Ok((old, _))), Some(Ok((new, _)))) => old.cmp(new),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return Ok(()),
+        };
+
+        match order {
+            Ordering::Less => {
+                let (key, _) = before.next().unwrap()?;
+                f(key, None)?;
+            }
+            Ordering::Greater => {
+                let (key, value) = after.next().unwrap()?;
+                f(key, Some(value))?;
+            }
+            Ordering::Equal => {
+                let (_, old) = before.next().unwrap()?;
+                let (key, new) = after.next().unwrap()?;
+
+                if old != new {
+                    f(key, Some(new))?;
+                }
+            }
+        }
+    }
+}
+
+/// Create or check `name` in `txn` and open it for importing, replacing its
+/// entries at the parent snapshot if `replace`.
+fn import_table<'a, T, E>(
+    txn: &'a WriteTxn<T, E>,
+    name: &str,
+    ty: TableType,
+    replace: bool,
+) -> Result<TableImport<'a, T, E>>
+where
+    T: BackendWriteTxn<E>,
+    E: BackendError,
+{
+    let existing = match crate::utils::meta_reader_by_write(&txn.txn)?.read_type(name) {
+        Ok(existing) => Some(existing),
+        Err(Error::MissingTable) => None,
+        Err(e) => return Err(e),
+    };
+
+    let next_parent = match existing {
+        Some(existing) if existing != ty => {
+            return Err(Error::InvalidArchive("table type does not match"))
+        }
+        Some(_) => replace.then(Vec::new),
+        None => {
+            match ty {
+                TableType::Basic => txn.create_basic_table(name)?,
+                TableType::Versioned => txn.create_versioned_table(name)?,
+            }
+            None
+        }
+    };
+
+    Ok(TableImport {
+        txn,
+        name: name.into(),
+        ty,
+        writes: VecDeque::new(),
+        parent: VecDeque::new(),
+        next_parent,
+        last: None,
+    })
+}
+
+impl<B> CrepeDB<B>
+where
+    B: Backend,
+{
+    /// Export the entries of all tables at `snapshot_id` to an archive.
+    ///
+    /// See [`export_tables`](Self::export_tables).
+    pub fn export(&self, snapshot_id: SnapshotId, writer: impl Write) -> Result<()> {
+        let tables = self.tables()?;
+        let tables: Vec<&str> = tables.iter().map(|(name, _)| name.as_str()).collect();
+
+        self.export_tables(snapshot_id, &tables, writer)
+    }
+
+    /// Export the entries of `tables` at `snapshot_id` to an archive.
+    ///
+    /// Versioned tables are exported as they are at the snapshot and basic
+    /// tables with their current entries. The history of the snapshot is not
+    /// exported.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot or a table does not exist, or writing
+    /// fails.
+    pub fn export_tables(
+        &self,
+        snapshot_id: SnapshotId,
+        tables: &[&str],
+        writer: impl Write,
+    ) -> Result<()> {
+        let txn = self.read(Some(snapshot_id))?;
+
+        let mut archive = ArchiveWriter::new(writer, KIND_STATE)?;
+
+        for name in tables {
+            let table = txn.open_table(name)?;
+            archive.table(name, &table.table_type)?;
+
+            for entry in table.scan([], None)? {
+                let (key, value) = entry?;
+                archive.set(&key, &value)?;
+            }
+        }
+
+        archive.finish()
+    }
+
+    /// Import an archive as a new snapshot, the root if `parent` is `None`.
+    ///
+    /// Missing tables are created. The tables of the archive hold exactly its
+    /// entries at the new snapshot, other tables are left as they are at
+    /// `parent`. Nothing is written unless the whole archive is valid.
+    ///
+    /// # Returns
+    ///
+    /// The snapshot ID of the new snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidArchive`] if the archive is malformed, does not
+    /// match its checksum, or a table exists with another type.
+    pub fn import(&self, parent: Option<SnapshotId>, reader: impl Read) -> Result<SnapshotId> {
+        let mut archive = ArchiveReader::new(reader, KIND_STATE)?;
+
+        let txn = self.write(parent)?;
+
+        {
+            let mut imported = BTreeSet::new();
+            let mut table = None;
+
+            loop {
+                match archive.record()? {
+                    Record::Table(name, ty) => {
+                        if let Some(table) = table.take() {
+                            TableImport::finish(table)?;
+                        }
+
+                        if !imported.insert(name.clone()) {
+                            return Err(Error::InvalidArchive("duplicate table"));
+                        }
+
+                        table = Some(import_table(&txn, &name, ty, true)?);
+                    }
+                    Record::Set(key, value) => current(&mut table)?.set(key, value)?,
+                    Record::Del(_) => {
+                        return Err(Error::InvalidArchive("delete in a state archive"))
+                    }
+                    Record::End => break,
+                }
+            }
+
+            if let Some(table) = table {
+                table.finish()?;
+            }
+        }
+
+        txn.commit()
+    }
+
+    /// Export the changes of the versioned tables from snapshot `from` to
+    /// snapshot `to` as a delta.
+    ///
+    /// The snapshots do not need to be related. Applying the delta to a
+    /// snapshot with the state of `from` gives the state of `to`. If `from`
+    /// is an ancestor of `to`, only the keys written between them are read at
+    /// both snapshots, other snapshots are compared entry by entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MissingStateHash`] if a snapshot has no state hash,
+    /// see `CrepeDB::with_state_hash`.
+    pub fn export_delta(&self, from: SnapshotId, to: SnapshotId, writer: impl Write) -> Result<()> {
+        let base = self
+            .state_hash(from.clone())?
+            .ok_or(Error::MissingStateHash)?;
+        let target = self
+            .state_hash(to.clone())?
+            .ok_or(Error::MissingStateHash)?;
+
+        let from = self.read(Some(from))?;
+        let to = self.read(Some(to))?;
+
+        let mut archive = ArchiveWriter::new(writer, KIND_DELTA)?;
+        archive.write(&base)?;
+        archive.write(&target)?;
+
+        for (name, ty) in self.tables()? {
+            if ty != TableType::Versioned {
+                continue;
+            }
+
+            let before = from.open_table(&name)?;
+            let after = to.open_table(&name)?;
+
+            let mut written = false;
+            changes(&before, &after, |key, value| {
+                if !written {
+                    archive.table(&name, &ty)?;
+                    written = true;
+                }
+
+                match value {
+                    Some(value) => archive.set(&key, &value),
+                    None => archive.del(&key),
+                }
+            })?;
+        }
+
+        archive.finish()
+    }
+
+    /// Apply a delta as a new child snapshot of `base`.
+    ///
+    /// Missing tables are created. The delta is only committed if the state
+    /// hash of `base` is the base state hash of the delta and the state hash
+    /// of the new snapshot is its target state hash.
+    ///
+    /// # Returns
+    ///
+    /// The snapshot ID of the new snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MissingStateHash`] if `base` has no state hash, or
+    /// [`Error::InvalidArchive`] if the delta is malformed or the state hashes
+    /// do not match.
+    pub fn import_delta(&self, base: SnapshotId, reader: impl Read) -> Result<SnapshotId> {
+        let mut archive = ArchiveReader::new(reader, KIND_DELTA)?;
+        let base_hash: Hash = archive.read()?;
+        let target: Hash = archive.read()?;
+
+        if self
+            .state_hash(base.clone())?
+            .ok_or(Error::MissingStateHash)?
+            != base_hash
+        {
+            return Err(Error::InvalidArchive("base state hash mismatch"));
+        }
+
+        let txn = self.write(Some(base))?;
+
+        {
+            let mut imported = BTreeSet::new();
+            let mut table = None;
+
+            loop {
+                match archive.record()? {
+                    Record::Table(name, ty) => {
+                        if ty != TableType::Versioned {
+                            return Err(Error::InvalidArchive("basic table in a delta"));
+                        }
+
+                        if !imported.insert(name.clone()) {
+                            return Err(Error::InvalidArchive("duplicate table"));
+                        }
+
+                        if let Some(table) = table.take() {
+                            TableImport::finish(table)?;
+                        }
+
+                        table = Some(import_table(&txn, &name, ty, false)?);
+                    }
+                    Record::Set(key, value) => current(&mut table)?.set(key, value)?,
+                    Record::Del(key) => current(&mut table)?.del(key)?,
+                    Record::End => break,
+                }
+            }
+
+            if let Some(table) = table {
+                table.finish()?;
+            }
+        }
+
+        if txn.state_hash.as_ref().map(|hash| *hash.borrow()) != Some(target) {
+            return Err(Error::InvalidArchive("state hash mismatch"));
+        }
+
+        txn.commit()
+    }
+
+    /// Names and types of all tables, in increasing order of names.
+    fn tables(&self) -> Result<Vec<(String, TableType)>> {
+        let txn = self.backend.read_txn().map_err(Error::backend)?;
+        let meta = txn.open_table(consts::META_TABLE).map_err(Error::backend)?;
+
+        let mut rows = Vec::new();
+        scan(&meta, |key, value| rows.push((key, value)))?;
+
+        rows.into_iter()
+            .map(|(key, value)| {
+                let byte = value.first().ok_or(Error::WrongBytesLength(1))?;
+                Ok((
+                    String::from_utf8_lossy(&key).into_owned(),
+                    TableType::from_byte(*byte)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+#[doc(hidden)]
+#[cfg(feature = "tests")]
+pub mod export_tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::memory::MemoryBackend;
+
+    /// A table type and its entries.
+    type Dump = (TableType, Vec<(Bytes, Bytes)>);
+
+    /// Entries of `tables` at `snapshot`, with the types of the tables.
+    fn dump<B: Backend>(
+        db: &CrepeDB<B>,
+        snapshot: &SnapshotId,
+        tables: &[&str],
+    ) -> Result<Vec<Dump>> {
+        let txn = db.read(Some(snapshot.clone()))?;
+
+        tables
+            .iter()
+            .map(|name| {
+                let table = txn.open_table(name)?;
+                let entries = table.scan([], None)?.collect::<Result<_>>()?;
+                Ok((table.table_type.clone(), entries))
+            })
+            .collect()
+    }
+
+    /// Test exporting snapshots and importing them into other databases
+    pub fn test_export(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+        let tables = ["a", "b", "c", "e"];
+
+        let txn = db.write(None)?;
+        txn.create_versioned_table("a")?;
+        txn.create_basic_table("b")?;
+        txn.create_versioned_table("c")?;
+        txn.create_versioned_table("e")?;
+        {
+            let mut a = txn.open_table("a")?;
+            for i in 0..20u8 {
+                a.set([i], [i, i])?;
+            }
+            a.set([0xff], [0])?;
+            // Keys past any fixed scan end.
+            a.set([0xff; 1100], [1])?;
+            txn.open_table("b")?.set([1], [1])?;
+            txn.open_table("b")?.set([0xff; 1100], [2])?;
+            txn.open_table("c")?.set([1], [1])?;
+            txn.open_table("e")?.del([0])?;
+        }
+        let root = txn.commit()?;
+
+        let txn = db.write(Some(root.clone()))?;
+        {
+            let mut a = txn.open_table("a")?;
+            a.del([3])?;
+            a.set([4], [])?;
+            a.set([30], [30])?;
+            txn.open_table("c")?.del([1])?;
+        }
+        let s1 = txn.commit()?;
+
+        // Another fork is not exported.
+        let txn = db.write(Some(root.clone()))?;
+        txn.open_table("a")?.set([40], [40])?;
+        let fork = txn.commit()?;
+
+        let mut archive = Vec::new();
+        db.export(s1.clone(), &mut archive)?;
+
+        // Import as the root of another database.
+        let other = CrepeDB::new(MemoryBackend::new());
+        let imported = other.import(None, &archive[..])?;
+        assert_eq!(dump(&other, &imported, &tables)?, dump(&db, &s1, &tables)?);
+        assert_eq!(
+            other
+                .read(Some(imported.clone()))?
+                .open_table("a")?
+                .get([0xff; 1100])?,
+            Some(vec![1])
+        );
+
+        // Export the imported snapshot again.
+        let mut again = Vec::new();
+        other.export(imported, &mut again)?;
+        assert_eq!(again, archive);
+
+        // Selected tables.
+        let mut archive = Vec::new();
+        db.export_tables(s1.clone(), &["c", "a"], &mut archive)?;
+
+        let other = CrepeDB::new(MemoryBackend::new());
+        let imported = other.import(None, &archive[..])?;
+        assert_eq!(
+            dump(&other, &imported, &["c", "a"])?,
+            dump(&db, &s1, &["c", "a"])?
+        );
+        assert!(matches!(
+            other.read(Some(imported))?.open_table("b"),
+            Err(Error::MissingTable)
+        ));
+
+        // Import as a child of the fork, replacing its state.
+        let mut archive = Vec::new();
+        db.export_tables(s1.clone(), &["a", "c"], &mut archive)?;
+
+        let child = db.import(Some(fork.clone()), &archive[..])?;
+        assert_eq!(
+            dump(&db, &child, &["a", "c"])?,
+            dump(&db, &s1, &["a", "c"])?
+        );
+        assert_eq!(db.read(Some(child))?.open_table("a")?.get([40])?, None);
+        assert_eq!(
+            db.read(Some(fork.clone()))?.open_table("a")?.get([40])?,
+            Some(vec![40])
+        );
+
+        // Import over a parent with more entries than a page, keeping, changing
+        // and deleting some of them.
+        let txn = db.write(Some(fork.clone()))?;
+        {
+            let mut c = txn.open_table("c")?;
+            for i in 0..3000u32 {
+                c.set(i.to_be_bytes(), [0])?;
+            }
+        }
+        let big = txn.commit()?;
+
+        let txn = db.write(Some(big.clone()))?;
+        {
+            let mut c = txn.open_table("c")?;
+            for i in 0..3000u32 {
+                match i % 3 {
+                    0 => c.set(i.to_be_bytes(), [1])?,
+                    1 => c.del(i.to_be_bytes())?,
+                    _ => {}
+                }
+            }
+            c.set([0xff; 1100], [2])?;
+        }
+        let expected = txn.commit()?;
+
+        let mut replacing = Vec::new();
+        db.export_tables(expected.clone(), &["c"], &mut replacing)?;
+
+        let child = db.import(Some(big), &replacing[..])?;
+        assert_eq!(dump(&db, &child, &["c"])?, dump(&db, &expected, &["c"])?);
+
+        let report = db.verify()?;
+        assert!(report.is_ok(), "{:?}", report.issues);
+
+        // Corrupted, truncated and unsupported archives are rejected and
+        // create no snapshot.
+        let other = CrepeDB::new(MemoryBackend::new());
+
+        let mut corrupted = archive.clone();
+        corrupted[20] ^= 1;
+        assert!(other.import(None, &corrupted[..]).is_err());
+
+        let truncated = &archive[..archive.len() - 1];
+        assert!(matches!(other.import(None, truncated), Err(Error::Io(_))));
+
+        let mut unsupported = archive.clone();
+        unsupported[9] = 2;
+        assert!(matches!(
+            other.import(None, &unsupported[..]),
+            Err(Error::InvalidArchive("unsupported format version"))
+        ));
+
+        // Tables of another type are rejected. The root can still be created.
+        let txn = other.write(None)?;
+        txn.create_basic_table("a")?;
+        txn.open_table("a")?.set([0], [0])?;
+        let root = txn.commit()?;
+
+        assert!(matches!(
+            other.import(Some(root), &archive[..]),
+            Err(Error::InvalidArchive("table type does not match"))
+        ));
+
+        Ok(())
+    }
+
+    /// Test exporting deltas and applying them to other databases
+    pub fn test_delta(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend).with_state_hash(true);
+        let tables = ["a", "c", "d"];
+
+        let txn = db.write(None)?;
+        txn.create_versioned_table("a")?;
+        txn.create_basic_table("b")?;
+        txn.create_versioned_table("c")?;
+        txn.create_versioned_table("d")?;
+        {
+            let mut a = txn.open_table("a")?;
+            for i in 0..20u8 {
+                a.set([i], [i])?;
+            }
+            txn.open_table("b")?.set([0], [0])?;
+            txn.open_table("c")?.set([0], [0])?;
+            txn.open_table("d")?.del([0])?;
+        }
+        let root = txn.commit()?;
+
+        let txn = db.write(Some(root.clone()))?;
+        {
+            let mut a = txn.open_table("a")?;
+            a.del([1])?;
+            a.set([2], [2, 2])?;
+            a.set([3], [3])?;
+            a.set([30], [30])?;
+            txn.open_table("b")?.set([1], [1])?;
+            txn.open_table("c")?.del([0])?;
+            txn.open_table("d")?.set([5], [])?;
+        }
+        let s1 = txn.commit()?;
+
+        // A follower bootstrapped from the root catches up with the delta.
+        let mut archive = Vec::new();
+        db.export(root.clone(), &mut archive)?;
+
+        let other = CrepeDB::new(MemoryBackend::new()).with_state_hash(true);
+        let base = other.import(None, &archive[..])?;
+        assert_eq!(
+            other.state_hash(base.clone())?,
+            db.state_hash(root.clone())?
+        );
+
+        let mut delta = Vec::new();
+        db.export_delta(root.clone(), s1.clone(), &mut delta)?;
+
+        let caught_up = other.import_delta(base.clone(), &delta[..])?;
+        assert_eq!(
+            other.state_hash(caught_up.clone())?,
+            db.state_hash(s1.clone())?
+        );
+        assert_eq!(dump(&other, &caught_up, &tables)?, dump(&db, &s1, &tables)?);
+
+        // Deltas also go back to an ancestor.
+        let mut back = Vec::new();
+        db.export_delta(s1.clone(), root.clone(), &mut back)?;
+
+        let reverted = other.import_delta(caught_up.clone(), &back[..])?;
+        assert_eq!(
+            other.state_hash(reverted.clone())?,
+            db.state_hash(root.clone())?
+        );
+        assert_eq!(
+            dump(&other, &reverted, &tables)?,
+            dump(&db, &root, &tables)?
+        );
+
+        // No changes give an empty delta.
+        let mut empty = Vec::new();
+        db.export_delta(s1.clone(), s1.clone(), &mut empty)?;
+        let same = other.import_delta(caught_up.clone(), &empty[..])?;
+        assert_eq!(other.state_hash(same)?, db.state_hash(s1.clone())?);
+
+        // Deltas over several snapshots, with a key written back to its value,
+        // and between snapshots of different forks.
+        let txn = db.write(Some(s1.clone()))?;
+        {
+            let mut a = txn.open_table("a")?;
+            a.set([2], [2])?;
+            a.set([40], [40])?;
+            txn.open_table("d")?.del([5])?;
+        }
+        let s2 = txn.commit()?;
+
+        let txn = db.write(Some(s1.clone()))?;
+        txn.open_table("a")?.set([50], [50])?;
+        let sibling = txn.commit()?;
+
+        let txn = db.write(Some(s2.clone()))?;
+        {
+            let mut a = txn.open_table("a")?;
+            a.set([40], [41])?;
+            a.del([3])?;
+        }
+        let s3 = txn.commit()?;
+
+        for (from, to) in [
+            (root.clone(), s3.clone()),
+            (sibling.clone(), s3),
+            (s2, sibling),
+        ] {
+            let mut delta = Vec::new();
+            db.export_delta(from.clone(), to.clone(), &mut delta)?;
+
+            let applied = db.import_delta(from, &delta[..])?;
+            assert_eq!(dump(&db, &applied, &tables)?, dump(&db, &to, &tables)?);
+        }
+
+        // A delta only applies to a snapshot with its base state.
+        assert!(matches!(
+            other.import_delta(caught_up.clone(), &delta[..]),
+            Err(Error::InvalidArchive("base state hash mismatch"))
+        ));
+
+        // A delta which does not give its target state is not committed.
+        let base_hash = db.state_hash(root.clone())?.unwrap();
+
+        let mut wrong = Vec::new();
+        let mut writer = ArchiveWriter::new(&mut wrong, KIND_DELTA)?;
+        writer.write(&base_hash)?;
+        writer.write(&db.state_hash(s1.clone())?.unwrap())?;
+        writer.table("a", &TableType::Versioned)?;
+        writer.set(&[2], &[2, 2])?;
+        writer.finish()?;
+
+        assert!(matches!(
+            other.import_delta(base.clone(), &wrong[..]),
+            Err(Error::InvalidArchive("state hash mismatch"))
+        ));
+
+        // Corrupted deltas and state archives are rejected.
+        let mut corrupted = delta.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 1;
+        assert!(matches!(
+            other.import_delta(base.clone(), &corrupted[..]),
+            Err(Error::InvalidArchive("checksum mismatch"))
+        ));
+        assert!(matches!(
+            other.import_delta(base.clone(), &archive[..]),
+            Err(Error::InvalidArchive("unexpected archive kind"))
+        ));
+        assert!(matches!(
+            other.import(None, &delta[..]),
+            Err(Error::InvalidArchive("unexpected archive kind"))
+        ));
+
+        let report = other.verify()?;
+        assert!(report.is_ok(), "{:?}", report.issues);
+
+        // Deltas need state hashes.
+        let db = CrepeDB::new(MemoryBackend::new());
+        let root = db.write(None)?.commit()?;
+        assert!(matches!(
+            db.export_delta(root.clone(), root, Vec::new()),
+            Err(Error::MissingStateHash)
+        ));
+
+        Ok(())
+    }
+}
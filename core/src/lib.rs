@@ -11,6 +11,7 @@
 //! - **Snapshot Isolation**: Create and read from consistent snapshots
 //! - **Fork Support**: Create new branches from any snapshot
 //! - **Backend Abstraction**: Use different storage backends (e.g., redb)
+//! - **In-Memory Backend**: A `no_std` `BTreeMap` backend behind the `memory` feature
 //!
 //! ## Example
 //!
@@ -28,24 +29,27 @@
 //! // Write data
 //! let wtxn = db.write(Some(root))?;
 //! let mut table = wtxn.open_table("my_table")?;
-//! table.set(b"key".to_vec(), b"value".to_vec())?;
+//! table.set(b"key", b"value")?;
 //! let snapshot1 = wtxn.commit()?;
 //!
 //! // Read data
 //! let rtxn = db.read(Some(snapshot1))?;
 //! let table = rtxn.open_table("my_table")?;
-//! let value = table.get(b"key".to_vec())?;
+//! let value = table.get(b"key")?;
 //! ```
 
 #![no_std]
 
 extern crate alloc;
 
-#[cfg(feature = "tests")]
+#[cfg(any(feature = "tests", feature = "export"))]
 extern crate std;
 
 pub mod backend;
 
+#[cfg(feature = "memory")]
+pub mod memory;
+
 pub mod types;
 
 mod db;
@@ -66,7 +70,45 @@ pub use write_table::*;
 mod error;
 pub use error::*;
 
+mod verify;
+pub use verify::*;
+
+mod rebuild;
+#[cfg(feature = "tests")]
+pub use rebuild::*;
+
+mod staged;
+pub use staged::*;
+
+mod savepoint;
+pub use savepoint::*;
+
+mod codec;
+pub use codec::*;
+
+mod typed;
+pub use typed::*;
+
+#[cfg(feature = "merkle")]
+mod merkle;
+#[cfg(feature = "merkle")]
+pub use merkle::*;
+
+#[cfg(feature = "state-hash")]
+mod state_hash;
+#[cfg(all(feature = "state-hash", feature = "tests"))]
+pub use state_hash::*;
+
+#[cfg(feature = "export")]
+mod export;
+#[cfg(feature = "export")]
+pub use export::*;
+
 pub(crate) mod utils;
+// Names of the internal tables, for backends which store them specially. Not
+// part of the public API.
+#[doc(hidden)]
+pub use utils::consts;
 
 #[doc(hidden)]
 #[cfg(feature = "tests")]
@@ -0,0 +1,426 @@
+//! In-memory storage backend.
+//!
+//! [`MemoryBackend`] keeps every table in a `BTreeMap` and only needs `alloc`,
+//! so it can be used where no storage engine is available, such as embedded,
+//! WASM or zk-guest environments, and for unit tests.
+//!
+//! Transactions are copy-on-write: a transaction starts from a shared reference
+//! to the committed state and a table is cloned the first time it is modified.
+//! Committing a write transaction fails with [`MemoryError::Conflict`] if
+//! another write transaction was committed after it started.
+
+use alloc::{
+    collections::BTreeMap,
+    rc::Rc,
+    string::{String, ToString},
+};
+use core::{
+    cell::RefCell,
+    fmt::{self, Display},
+    ops::Bound,
+};
+
+use crate::{
+    backend::{Backend, Range, ReadTable, ReadTxn, WriteTable, WriteTxn},
+    types::Bytes,
+};
+
+type Table = BTreeMap<Bytes, Bytes>;
+
+type Tables = BTreeMap<String, Rc<Table>>;
+
+/// Errors returned by the in-memory backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryError {
+    /// Another write transaction was committed after this one started.
+    Conflict,
+}
+
+impl Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conflict => write!(f, "write transaction conflicts with a newer commit"),
+        }
+    }
+}
+
+/// A CrepeDB backend keeping all data in memory.
+///
+/// The data is lost when the backend is dropped. The backend is not `Sync`,
+/// transactions must be used from a single thread.
+///
+/// # Example
+///
+/// ```ignore
+/// let db = CrepeDB::new(MemoryBackend::new());
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    state: RefCell<Rc<Tables>>,
+}
+
+impl MemoryBackend {
+    /// Create an empty in-memory backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Backend for MemoryBackend {
+    type Error = MemoryError;
+
+    type ReadTxn<'a> = MemoryReadTxn;
+
+    type WriteTxn<'a> = MemoryWriteTxn<'a>;
+
+    fn is_conflict(error: &Self::Error) -> bool {
+        matches!(error, MemoryError::Conflict)
+    }
+
+    fn read_txn(&self) -> Result<Self::ReadTxn<'_>, Self::Error> {
+        Ok(MemoryReadTxn {
+            tables: self.state.borrow().clone(),
+        })
+    }
+
+    fn write_txn(&self) -> Result<Self::WriteTxn<'_>, Self::Error> {
+        let base = self.state.borrow().clone();
+
+        Ok(MemoryWriteTxn {
+            db: self,
+            tables: RefCell::new((*base).clone()),
+            base,
+        })
+    }
+}
+
+/// A read transaction of the in-memory backend.
+///
+/// Holds the committed state at the time the transaction was started.
+pub struct MemoryReadTxn {
+    tables: Rc<Tables>,
+}
+
+impl ReadTxn<MemoryError> for MemoryReadTxn {
+    type Table<'a> = MemoryReadTable;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, MemoryError> {
+        Ok(MemoryReadTable {
+            inner: self.tables.get(table).cloned().unwrap_or_default(),
+            name: table.to_string(),
+        })
+    }
+}
+
+/// A write transaction of the in-memory backend.
+///
+/// Changes are made to private copies of the modified tables and become
+/// visible to new transactions on commit.
+pub struct MemoryWriteTxn<'a> {
+    db: &'a MemoryBackend,
+    base: Rc<Tables>,
+    tables: RefCell<Tables>,
+}
+
+impl<'db> WriteTxn<MemoryError> for MemoryWriteTxn<'db> {
+    type Table<'a>
+        = MemoryWriteTable<'a>
+    where
+        Self: 'a;
+
+    fn open_table(&self, table: &str) -> Result<Self::Table<'_>, MemoryError> {
+        self.tables
+            .borrow_mut()
+            .entry(table.to_string())
+            .or_default();
+
+        Ok(MemoryWriteTable {
+            tables: &self.tables,
+            name: table.to_string(),
+        })
+    }
+
+    fn remove_table(&self, table: &str) -> Result<(), MemoryError> {
+        self.tables.borrow_mut().remove(table);
+        Ok(())
+    }
+
+    fn commit(self) -> Result<(), MemoryError> {
+        let mut state = self.db.state.borrow_mut();
+
+        if !Rc::ptr_eq(&state, &self.base) {
+            return Err(MemoryError::Conflict);
+        }
+
+        *state = Rc::new(self.tables.into_inner());
+
+        Ok(())
+    }
+}
+
+/// A table opened in a read transaction of the in-memory backend.
+pub struct MemoryReadTable {
+    inner: Rc<Table>,
+    name: String,
+}
+
+impl ReadTable<MemoryError> for MemoryReadTable {
+    type Range<'a> = MemoryRange;
+
+    type Value<'a> = &'a [u8];
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, MemoryError> {
+        Ok(self.inner.get(key).cloned())
+    }
+
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, MemoryError> {
+        Ok(self.inner.get(key).map(|v| v.as_slice()))
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, MemoryError> {
+        Ok(MemoryRange::new(
+            self.inner.clone(),
+            begin.to_vec(),
+            end.map(<[u8]>::to_vec),
+        ))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A table opened in a write transaction of the in-memory backend.
+pub struct MemoryWriteTable<'a> {
+    tables: &'a RefCell<Tables>,
+    name: String,
+}
+
+impl<'a> MemoryWriteTable<'a> {
+    fn table(&self) -> Rc<Table> {
+        self.tables
+            .borrow()
+            .get(&self.name)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl<'t> ReadTable<MemoryError> for MemoryWriteTable<'t> {
+    type Range<'a>
+        = MemoryRange
+    where
+        Self: 'a;
+
+    // The table can change while a value is borrowed, values are copied.
+    type Value<'a>
+        = Bytes
+    where
+        Self: 'a;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, MemoryError> {
+        Ok(self.table().get(key).cloned())
+    }
+
+    fn get_ref(&self, key: &[u8]) -> Result<Option<Self::Value<'_>>, MemoryError> {
+        self.get(key)
+    }
+
+    fn range(&self, begin: &[u8], end: Option<&[u8]>) -> Result<Self::Range<'_>, MemoryError> {
+        Ok(MemoryRange::new(
+            self.table(),
+            begin.to_vec(),
+            end.map(<[u8]>::to_vec),
+        ))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<'t> WriteTable<MemoryError> for MemoryWriteTable<'t> {
+    fn set(&mut self, key: &[u8], value: &[u8]) -> Result<(), MemoryError> {
+        let mut tables = self.tables.borrow_mut();
+        let table = tables.entry(self.name.clone()).or_default();
+
+        Rc::make_mut(table).insert(key.to_vec(), value.to_vec());
+
+        Ok(())
+    }
+
+    fn del(&mut self, key: &[u8]) -> Result<(), MemoryError> {
+        let mut tables = self.tables.borrow_mut();
+        let table = tables.entry(self.name.clone()).or_default();
+
+        Rc::make_mut(table).remove(key);
+
+        Ok(())
+    }
+}
+
+/// A range iterator of the in-memory backend.
+///
+/// Iterates over the table as it was when the range was created.
+pub struct MemoryRange {
+    table: Rc<Table>,
+    begin: Bound<Bytes>,
+    end: Bound<Bytes>,
+}
+
+impl MemoryRange {
+    fn new(table: Rc<Table>, begin: Bytes, end: Option<Bytes>) -> Self {
+        Self {
+            table,
+            begin: Bound::Included(begin),
+            end: end.map_or(Bound::Unbounded, Bound::Excluded),
+        }
+    }
+}
+
+impl Range<MemoryError> for MemoryRange {
+    fn back(&mut self) -> Result<Option<(Bytes, Bytes)>, MemoryError> {
+        // `BTreeMap::range` panics on inverted bounds.
+        let empty = match (&self.begin, &self.end) {
+            (Bound::Included(begin), Bound::Excluded(end)) => begin > end,
+            (Bound::Excluded(begin), Bound::Excluded(end)) => begin >= end,
+            _ => false,
+        };
+
+        if empty {
+            return Ok(None);
+        }
+
+        let bounds = (self.begin.clone(), self.end.clone());
+
+        let Some((key, value)) = self.table.range::<Bytes, _>(bounds).next() else {
+            return Ok(None);
+        };
+
+        self.begin = Bound::Excluded(key.clone());
+
+        Ok(Some((key.clone(), value.clone())))
+    }
+}
+
+#[cfg(all(test, feature = "tests"))]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_savepoint_removes_table() {
+        let db = crate::CrepeDB::new(MemoryBackend::new());
+
+        let txn = db.write(None).unwrap();
+        let savepoint = txn.savepoint().unwrap();
+        txn.create_basic_table("t").unwrap();
+        txn.open_table("t").unwrap().set(vec![1], vec![1]).unwrap();
+        assert!(txn.txn.tables.borrow().contains_key("t"));
+
+        savepoint.rollback_to().unwrap();
+        assert!(!txn.txn.tables.borrow().contains_key("t"));
+        assert!(txn.open_table("t").is_err());
+    }
+
+    #[test]
+    fn test_write_with_retry() {
+        use crate::{CrepeDB, Error};
+
+        let db = CrepeDB::new(MemoryBackend::new()).with_max_retries(2);
+
+        let txn = db.write(None).unwrap();
+        txn.create_versioned_table("t").unwrap();
+        let root = txn.commit().unwrap();
+
+        // Commit an unrelated change while the snapshot is built, so that
+        // every commit of the snapshot conflicts until `conflicts` is reached.
+        let write = |conflicts: usize| {
+            let mut calls = 0;
+
+            let res = db.write_with_retry(root.clone(), 1, |txn| {
+                txn.open_table("t")?.set(vec![1], vec![calls as u8])?;
+
+                if calls < conflicts {
+                    let other = db.backend().write_txn().map_err(Error::backend)?;
+                    other.open_table("other").map_err(Error::backend)?;
+                    other.commit().map_err(Error::backend)?;
+                }
+
+                calls += 1;
+                Ok(())
+            });
+
+            (res, calls)
+        };
+
+        let (res, calls) = write(2);
+        let snapshot = res.unwrap();
+        assert_eq!(calls, 3);
+
+        let txn = db.read(Some(snapshot)).unwrap();
+        let table = txn.open_table("t").unwrap();
+        assert_eq!(table.get(vec![1]).unwrap(), Some(vec![2]));
+
+        let (res, calls) = write(3);
+        let e = res.unwrap_err();
+        assert!(db.is_conflict(&e));
+        assert_eq!(e.backend_error(), Some(&MemoryError::Conflict));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_copy_on_write() {
+        let backend = MemoryBackend::new();
+
+        let txn = backend.write_txn().unwrap();
+        txn.open_table("t").unwrap().set(&[1], &[1]).unwrap();
+        txn.commit().unwrap();
+
+        let read = backend.read_txn().unwrap();
+
+        let txn = backend.write_txn().unwrap();
+        {
+            let mut table = txn.open_table("t").unwrap();
+            table.set(&[2], &[2]).unwrap();
+            table.del(&[1]).unwrap();
+
+            let mut range = table.range(&[], Some(&[0xff])).unwrap();
+            assert_eq!(range.back().unwrap(), Some((vec![2], vec![2])));
+            assert_eq!(range.back().unwrap(), None);
+        }
+
+        // Uncommitted changes are invisible.
+        let table = backend.read_txn().unwrap().open_table("t").unwrap();
+        assert_eq!(table.get(&[1]).unwrap(), Some(vec![1]));
+
+        txn.commit().unwrap();
+
+        // Old read transactions keep their view.
+        let table = read.open_table("t").unwrap();
+        assert_eq!(table.get(&[1]).unwrap(), Some(vec![1]));
+        assert_eq!(table.get(&[2]).unwrap(), None);
+
+        let table = backend.read_txn().unwrap().open_table("t").unwrap();
+        assert_eq!(table.get(&[1]).unwrap(), None);
+        assert_eq!(table.get(&[2]).unwrap(), Some(vec![2]));
+    }
+
+    #[test]
+    fn test_conflict() {
+        let backend = MemoryBackend::new();
+
+        let a = backend.write_txn().unwrap();
+        let b = backend.write_txn().unwrap();
+
+        a.open_table("t").unwrap().set(&[1], &[1]).unwrap();
+        b.open_table("t").unwrap().set(&[1], &[2]).unwrap();
+
+        a.commit().unwrap();
+        assert_eq!(b.commit(), Err(MemoryError::Conflict));
+
+        let table = backend.read_txn().unwrap().open_table("t").unwrap();
+        assert_eq!(table.get(&[1]).unwrap(), Some(vec![1]));
+    }
+}
@@ -0,0 +1,1006 @@
+//! Merkle trees of the versioned tables of snapshots.
+//!
+//! Every versioned table has a sparse Merkle tree of its visible entries, with
+//! the hash of a key as its path. A subtree with a single entry is stored as a
+//! leaf at the top of the subtree, so a tree of `n` entries is about
+//! `log2(n)` levels deep. The roots of the tables are the leaves of the state
+//! tree, with the hash of the table name as their path, and the root of the
+//! state tree is the state root of a snapshot.
+//!
+//! Nodes are stored in the versioned [`MERKLE_TABLE`](consts::MERKLE_TABLE)
+//! at their position, so the trees of every snapshot stay readable.
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec,
+    vec::Vec,
+};
+use core::cell::RefCell;
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    backend::{BackendError, WriteTable as BackendWriteTable, WriteTxn as BackendWriteTxn},
+    types::{Bytes, Hash},
+    utils::consts,
+    write_table::versioned_row,
+    Error, ReadTable, Result, WriteTxn,
+};
+
+/// The root of an empty tree.
+pub const EMPTY_ROOT: Hash = [0; 32];
+
+/// Tag of leaf nodes, prefixed to their hash input.
+const LEAF: u8 = 0;
+
+/// Tag of internal nodes, prefixed to their hash input.
+const INTERNAL: u8 = 1;
+
+/// Keys written to the versioned tables of a write transaction, by table.
+pub(crate) type Written = RefCell<BTreeMap<String, BTreeSet<Bytes>>>;
+
+/// Record a write of `key` to `table`.
+pub(crate) fn record(written: &Written, table: &str, key: &[u8]) {
+    let mut written = written.borrow_mut();
+
+    match written.get_mut(table) {
+        Some(keys) => {
+            keys.insert(key.to_vec());
+        }
+        None => {
+            written.insert(table.into(), BTreeSet::from([key.to_vec()]));
+        }
+    }
+}
+
+fn sha256(parts: &[&[u8]]) -> Hash {
+    let mut hasher = Sha256::new();
+
+    for part in parts {
+        hasher.update(part);
+    }
+
+    hasher.finalize().into()
+}
+
+/// Hash of a value in the leaves of the trees.
+pub(crate) fn value_hash(value: &[u8]) -> Hash {
+    sha256(&[value])
+}
+
+fn leaf_hash(key: &Hash, value: &Hash) -> Hash {
+    sha256(&[&[LEAF], key, value])
+}
+
+fn internal_hash(left: &Hash, right: &Hash) -> Hash {
+    sha256(&[&[INTERNAL], left, right])
+}
+
+/// Whether bit `i` of `path` is set, starting from the most significant bit.
+fn bit(path: &Hash, i: usize) -> bool {
+    path[i / 8] & (0x80 >> (i % 8)) != 0
+}
+
+/// `path` with bit `i` set.
+fn with_bit(path: &Hash, i: usize) -> Hash {
+    let mut path = *path;
+    path[i / 8] |= 0x80 >> (i % 8);
+    path
+}
+
+/// A node of a tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Empty,
+
+    /// The only entry of a subtree, by the hashes of its key and value.
+    Leaf {
+        key: Hash,
+        value: Hash,
+    },
+
+    /// A subtree of at least two entries, by the hashes of its children.
+    Internal {
+        left: Hash,
+        right: Hash,
+    },
+}
+
+impl Node {
+    fn hash(&self) -> Hash {
+        match self {
+            Self::Empty => EMPTY_ROOT,
+            Self::Leaf { key, value } => leaf_hash(key, value),
+            Self::Internal { left, right } => internal_hash(left, right),
+        }
+    }
+
+    fn to_bytes(&self) -> Option<Bytes> {
+        let (tag, a, b) = match self {
+            Self::Empty => return None,
+            Self::Leaf { key, value } => (LEAF, key, value),
+            Self::Internal { left, right } => (INTERNAL, left, right),
+        };
+
+        let mut bytes = Vec::with_capacity(65);
+
+        bytes.push(tag);
+        bytes.extend_from_slice(a);
+        bytes.extend_from_slice(b);
+
+        Some(bytes)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 65 {
+            return Err(Error::InvalidMerkleNode);
+        }
+
+        let a = bytes[1..33].try_into().expect("checked length");
+        let b = bytes[33..].try_into().expect("checked length");
+
+        match bytes[0] {
+            LEAF => Ok(Self::Leaf { key: a, value: b }),
+            INTERNAL => Ok(Self::Internal { left: a, right: b }),
+            _ => Err(Error::InvalidMerkleNode),
+        }
+    }
+
+    /// Decode a stored node which has hash `hash`.
+    fn checked(bytes: Option<Bytes>, hash: &Hash) -> Result<Self> {
+        let node = Self::from_bytes(&bytes.ok_or(Error::InvalidMerkleNode)?)?;
+
+        if node.hash() != *hash {
+            return Err(Error::InvalidMerkleNode);
+        }
+
+        Ok(node)
+    }
+}
+
+/// Prefix of the positions of the tree of `table`, or of the state tree.
+fn tree_prefix(table: Option<&str>) -> Bytes {
+    match table {
+        None => vec![0],
+        Some(table) => {
+            let mut prefix = vec![1];
+
+            prefix.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            prefix.extend_from_slice(table.as_bytes());
+
+            prefix
+        }
+    }
+}
+
+/// Key of the node at `depth` on `path` of the tree with positions starting
+/// with `prefix`, where `path` has the bits below `depth` cleared.
+fn position(prefix: &[u8], depth: usize, path: &Hash) -> Bytes {
+    let mut key = Vec::with_capacity(prefix.len() + 34);
+
+    key.extend_from_slice(prefix);
+    key.extend_from_slice(&(depth as u16).to_be_bytes());
+    key.extend_from_slice(path);
+
+    key
+}
+
+/// The nodes of the trees at the new snapshot of a write transaction.
+struct Nodes<T, E> {
+    table: ReadTable<T, E>,
+
+    /// Prefix of the positions of the tree being updated.
+    prefix: Bytes,
+
+    /// Reused buffer for rows.
+    row: Bytes,
+}
+
+impl<T, E> Nodes<T, E>
+where
+    T: BackendWriteTable<E>,
+    E: BackendError,
+{
+    fn position(&self, depth: usize, path: &Hash) -> Bytes {
+        position(&self.prefix, depth, path)
+    }
+
+    /// Get the node at `depth` on `path`, which has hash `hash`.
+    fn get(&self, depth: usize, path: &Hash, hash: &Hash) -> Result<Node> {
+        if *hash == EMPTY_ROOT {
+            return Ok(Node::Empty);
+        }
+
+        Node::checked(self.table.get(self.position(depth, path))?, hash)
+    }
+
+    fn put(&mut self, depth: usize, path: &Hash, node: &Node) -> Result<()> {
+        let key = self.position(depth, path);
+        let value = node.to_bytes();
+
+        let len = versioned_row(
+            &mut self.row,
+            &key,
+            &self.table.view.version,
+            &self.table.view.snapshot_id,
+            value.as_deref(),
+        );
+        let (key, value) = self.row.split_at(len);
+
+        self.table.table.set(key, value).map_err(Error::backend)
+    }
+
+    /// Apply `updates` of `(key, value)` hashes, sorted by key, to the tree
+    /// with positions starting with `prefix`, a deletion for a `None` value.
+    ///
+    /// Returns the new root.
+    fn update_tree(&mut self, prefix: Bytes, updates: &[(Hash, Option<Hash>)]) -> Result<Hash> {
+        self.prefix = prefix;
+
+        let root = match self.table.get(self.position(0, &EMPTY_ROOT))? {
+            Some(bytes) => Node::from_bytes(&bytes)?,
+            None => Node::Empty,
+        };
+
+        let new_root = self.update(0, &EMPTY_ROOT, root.clone(), updates)?;
+
+        if new_root != root {
+            self.put(0, &EMPTY_ROOT, &new_root)?;
+        }
+
+        Ok(new_root.hash())
+    }
+
+    /// Apply `updates` to `node` at `depth` on `path`, writing the changed
+    /// nodes below it.
+    ///
+    /// Returns the new node, which the caller writes.
+    fn update(
+        &mut self,
+        depth: usize,
+        path: &Hash,
+        node: Node,
+        updates: &[(Hash, Option<Hash>)],
+    ) -> Result<Node> {
+        if updates.is_empty() {
+            return Ok(node);
+        }
+
+        let (left, right) = match node {
+            Node::Empty => return self.build(depth, path, &merge(None, updates)),
+            Node::Leaf { key, value } => {
+                return self.build(depth, path, &merge(Some((key, value)), updates))
+            }
+            Node::Internal { left, right } => (left, right),
+        };
+
+        let split = updates.partition_point(|(key, _)| !bit(key, depth));
+        let right_path = with_bit(path, depth);
+
+        let new_left = self.update_child(depth + 1, path, &left, &updates[..split])?;
+        let new_right = self.update_child(depth + 1, &right_path, &right, &updates[split..])?;
+
+        let left_hash = new_left.as_ref().map_or(left, Node::hash);
+        let right_hash = new_right.as_ref().map_or(right, Node::hash);
+
+        // A subtree with at most one entry is stored as its leaf.
+        let collapsed = match (left_hash == EMPTY_ROOT, right_hash == EMPTY_ROOT) {
+            (true, true) => Some(Node::Empty),
+            (false, true) => self.as_leaf(depth + 1, path, &left, new_left.as_ref())?,
+            (true, false) => self.as_leaf(depth + 1, &right_path, &right, new_right.as_ref())?,
+            (false, false) => None,
+        };
+
+        if let Some(node) = collapsed {
+            if left != EMPTY_ROOT {
+                self.put(depth + 1, path, &Node::Empty)?;
+            }
+            if right != EMPTY_ROOT {
+                self.put(depth + 1, &right_path, &Node::Empty)?;
+            }
+
+            return Ok(node);
+        }
+
+        if let Some(node) = new_left.filter(|_| left_hash != left) {
+            self.put(depth + 1, path, &node)?;
+        }
+        if let Some(node) = new_right.filter(|_| right_hash != right) {
+            self.put(depth + 1, &right_path, &node)?;
+        }
+
+        Ok(Node::Internal {
+            left: left_hash,
+            right: right_hash,
+        })
+    }
+
+    /// Apply `updates` to the child with hash `hash`, `None` if there are no
+    /// updates.
+    fn update_child(
+        &mut self,
+        depth: usize,
+        path: &Hash,
+        hash: &Hash,
+        updates: &[(Hash, Option<Hash>)],
+    ) -> Result<Option<Node>> {
+        if updates.is_empty() {
+            return Ok(None);
+        }
+
+        let node = self.get(depth, path, hash)?;
+
+        self.update(depth, path, node, updates).map(Some)
+    }
+
+    /// The child with hash `hash`, or its new node, if it is a leaf.
+    fn as_leaf(
+        &self,
+        depth: usize,
+        path: &Hash,
+        hash: &Hash,
+        new: Option<&Node>,
+    ) -> Result<Option<Node>> {
+        let node = match new {
+            Some(node) => node.clone(),
+            None => self.get(depth, path, hash)?,
+        };
+
+        Ok(matches!(node, Node::Leaf { .. }).then_some(node))
+    }
+
+    /// Build the subtree of `leaves`, sorted by key, at `depth` on `path`,
+    /// writing the nodes below it.
+    ///
+    /// Nothing is stored below a leaf or an empty node, so there is nothing
+    /// to remove.
+    fn build(&mut self, depth: usize, path: &Hash, leaves: &[(Hash, Hash)]) -> Result<Node> {
+        match leaves {
+            [] => Ok(Node::Empty),
+            [(key, value)] => Ok(Node::Leaf {
+                key: *key,
+                value: *value,
+            }),
+            _ => {
+                let split = leaves.partition_point(|(key, _)| !bit(key, depth));
+                let right_path = with_bit(path, depth);
+
+                let left = self.build(depth + 1, path, &leaves[..split])?;
+                let right = self.build(depth + 1, &right_path, &leaves[split..])?;
+
+                if left != Node::Empty {
+                    self.put(depth + 1, path, &left)?;
+                }
+                if right != Node::Empty {
+                    self.put(depth + 1, &right_path, &right)?;
+                }
+
+                Ok(Node::Internal {
+                    left: left.hash(),
+                    right: right.hash(),
+                })
+            }
+        }
+    }
+}
+
+/// The entries of a subtree with entry `leaf` after `updates`, sorted by key.
+fn merge(leaf: Option<(Hash, Hash)>, updates: &[(Hash, Option<Hash>)]) -> Vec<(Hash, Hash)> {
+    let mut leaves: Vec<_> = updates
+        .iter()
+        .filter_map(|(key, value)| value.map(|value| (*key, value)))
+        .collect();
+
+    if let Some((key, value)) = leaf {
+        if updates.binary_search_by(|(k, _)| k.cmp(&key)).is_err() {
+            let i = leaves.partition_point(|(k, _)| *k < key);
+            leaves.insert(i, (key, value));
+        }
+    }
+
+    leaves
+}
+
+/// Update the trees of the tables with the keys in `written` and return the
+/// new state root.
+pub(crate) fn commit<T, E>(
+    txn: &WriteTxn<T, E>,
+    written: BTreeMap<String, BTreeSet<Bytes>>,
+) -> Result<Hash>
+where
+    T: BackendWriteTxn<E>,
+    E: BackendError,
+{
+    let mut tables = Vec::with_capacity(written.len());
+
+    for (table, keys) in written {
+        let reader = txn.read_versioned(&table)?;
+
+        let mut updates = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            let value = reader.get_ref(&key)?.map(|value| value_hash(&value));
+            updates.push((sha256(&[&key]), value));
+        }
+
+        updates.sort_unstable_by_key(|(key, _)| *key);
+
+        tables.push((table, updates));
+    }
+
+    let mut nodes = Nodes {
+        table: txn.read_versioned(consts::MERKLE_TABLE)?,
+        prefix: Bytes::new(),
+        row: Bytes::new(),
+    };
+
+    let mut roots = Vec::with_capacity(tables.len());
+
+    for (table, updates) in tables {
+        let root = nodes.update_tree(tree_prefix(Some(&table)), &updates)?;
+
+        roots.push((
+            sha256(&[table.as_bytes()]),
+            (root != EMPTY_ROOT).then_some(root),
+        ));
+    }
+
+    roots.sort_unstable_by_key(|(key, _)| *key);
+
+    nodes.update_tree(tree_prefix(None), &roots)
+}
+
+/// The path from the root of a tree to a key.
+///
+/// The path ends at the leaf of the key if it is in the tree, and otherwise at
+/// an empty node or at the leaf of another key sharing the bits of the path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeProof {
+    /// Hashes of the siblings of the nodes on the path, from the root down.
+    pub siblings: Vec<Hash>,
+
+    /// The hashes of the key and value of the leaf of another key ending the
+    /// path, if the key is not in the tree.
+    pub leaf: Option<(Hash, Hash)>,
+}
+
+impl TreeProof {
+    /// Compute the root of the tree with the leaf of `path` and `value`
+    /// hashes, or without `path` for a `None` value.
+    ///
+    /// Returns `None` if the proof is malformed.
+    fn root(&self, path: &Hash, value: Option<&Hash>) -> Option<Hash> {
+        let depth = self.siblings.len();
+
+        if depth > 256 {
+            return None;
+        }
+
+        let mut node = match (value, &self.leaf) {
+            (Some(value), None) => leaf_hash(path, value),
+            (None, None) => EMPTY_ROOT,
+            (None, Some((key, value))) => {
+                // The other leaf must be on the path.
+                if key == path || (0..depth).any(|i| bit(key, i) != bit(path, i)) {
+                    return None;
+                }
+
+                leaf_hash(key, value)
+            }
+            (Some(_), Some(_)) => return None,
+        };
+
+        for (i, sibling) in self.siblings.iter().enumerate().rev() {
+            node = if bit(path, i) {
+                internal_hash(sibling, &node)
+            } else {
+                internal_hash(&node, sibling)
+            };
+        }
+
+        Some(node)
+    }
+
+    fn write(&self, bytes: &mut Bytes) {
+        bytes.extend_from_slice(&(self.siblings.len() as u16).to_be_bytes());
+
+        for sibling in &self.siblings {
+            bytes.extend_from_slice(sibling);
+        }
+
+        match &self.leaf {
+            Some((key, value)) => {
+                bytes.push(1);
+                bytes.extend_from_slice(key);
+                bytes.extend_from_slice(value);
+            }
+            None => bytes.push(0),
+        }
+    }
+
+    fn read(bytes: &mut &[u8]) -> Result<Self> {
+        fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+            if bytes.len() < n {
+                return Err(Error::WrongBytesLength(n));
+            }
+
+            let (head, tail) = bytes.split_at(n);
+            *bytes = tail;
+
+            Ok(head)
+        }
+
+        fn hash(bytes: &mut &[u8]) -> Result<Hash> {
+            Ok(take(bytes, 32)?.try_into().expect("checked length"))
+        }
+
+        let len = u16::from_be_bytes(take(bytes, 2)?.try_into().expect("checked length"));
+
+        let siblings = (0..len).map(|_| hash(bytes)).collect::<Result<_>>()?;
+
+        let leaf = match take(bytes, 1)?[0] {
+            0 => None,
+            1 => Some((hash(bytes)?, hash(bytes)?)),
+            _ => return Err(Error::InvalidMerkleNode),
+        };
+
+        Ok(Self { siblings, leaf })
+    }
+}
+
+/// A proof of the value of a key of a versioned table, or of its absence,
+/// against the state root of a snapshot.
+///
+/// Verify it with [`verify_proof`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Proof {
+    /// The path to the table in the state tree.
+    pub table: TreeProof,
+
+    /// The path to the key in the tree of the table, empty if the table has
+    /// no entries.
+    pub key: TreeProof,
+}
+
+impl Proof {
+    /// Encode the proof, with each path as the number of siblings (u16), the
+    /// siblings, and a flag followed by the hashes of the other leaf.
+    pub fn to_bytes(&self) -> Bytes {
+        let mut bytes = Bytes::new();
+
+        self.table.write(&mut bytes);
+        self.key.write(&mut bytes);
+
+        bytes
+    }
+
+    /// Decode a proof encoded by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut rest = bytes;
+
+        let table = TreeProof::read(&mut rest)?;
+        let key = TreeProof::read(&mut rest)?;
+
+        if !rest.is_empty() {
+            return Err(Error::WrongBytesLength(bytes.len() - rest.len()));
+        }
+
+        Ok(Self { table, key })
+    }
+}
+
+/// Verify that `key` of versioned table `table` has `value`, or is absent for
+/// a `None` value, in the snapshot with state root `state_root`.
+///
+/// # Arguments
+///
+/// * `state_root` - The trusted state root of the snapshot
+/// * `table` - The name of the table
+/// * `key` - The key
+/// * `value` - The claimed value of the key
+/// * `proof` - The proof from [`ReadTable::get_with_proof`]
+pub fn verify_proof(
+    state_root: &Hash,
+    table: &str,
+    key: &[u8],
+    value: Option<&[u8]>,
+    proof: &Proof,
+) -> bool {
+    let value = value.map(value_hash);
+
+    let Some(table_root) = proof.key.root(&sha256(&[key]), value.as_ref()) else {
+        return false;
+    };
+
+    let table_root = (table_root != EMPTY_ROOT).then_some(table_root);
+
+    proof
+        .table
+        .root(&sha256(&[table.as_bytes()]), table_root.as_ref())
+        == Some(*state_root)
+}
+
+/// Find the path to `path` in the tree with positions starting with `prefix`
+/// and root `root`, reading nodes with `get`.
+///
+/// Returns the value hash of the leaf of `path`, if any.
+fn prove_path(
+    get: &impl Fn(&[u8]) -> Result<Option<Bytes>>,
+    prefix: &[u8],
+    root: &Hash,
+    path: &Hash,
+) -> Result<(Option<Hash>, TreeProof)> {
+    let mut proof = TreeProof::default();
+
+    let mut position_path = EMPTY_ROOT;
+    let mut node = match *root {
+        EMPTY_ROOT => Node::Empty,
+        _ => Node::checked(get(&position(prefix, 0, &position_path))?, root)?,
+    };
+
+    loop {
+        let (left, right) = match node {
+            Node::Empty => return Ok((None, proof)),
+            Node::Leaf { key, value } if key == *path => return Ok((Some(value), proof)),
+            Node::Leaf { key, value } => {
+                proof.leaf = Some((key, value));
+                return Ok((None, proof));
+            }
+            Node::Internal { left, right } => (left, right),
+        };
+
+        let depth = proof.siblings.len();
+
+        if depth == 256 {
+            return Err(Error::InvalidMerkleNode);
+        }
+
+        let child = if bit(path, depth) {
+            position_path = with_bit(&position_path, depth);
+            proof.siblings.push(left);
+            right
+        } else {
+            proof.siblings.push(right);
+            left
+        };
+
+        node = match child {
+            EMPTY_ROOT => Node::Empty,
+            _ => Node::checked(get(&position(prefix, depth + 1, &position_path))?, &child)?,
+        };
+    }
+}
+
+/// Prove the value of `key` of `table` in the snapshot with state root
+/// `state_root`, reading nodes with `get`.
+///
+/// Returns the value hash of `key`, if any.
+pub(crate) fn prove(
+    get: impl Fn(&[u8]) -> Result<Option<Bytes>>,
+    state_root: &Hash,
+    table: &str,
+    key: &[u8],
+) -> Result<(Option<Hash>, Proof)> {
+    let (table_root, table_proof) = prove_path(
+        &get,
+        &tree_prefix(None),
+        state_root,
+        &sha256(&[table.as_bytes()]),
+    )?;
+
+    let (value, key_proof) = match table_root {
+        Some(root) => prove_path(&get, &tree_prefix(Some(table)), &root, &sha256(&[key]))?,
+        None => (None, TreeProof::default()),
+    };
+
+    Ok((
+        value,
+        Proof {
+            table: table_proof,
+            key: key_proof,
+        },
+    ))
+}
+
+#[doc(hidden)]
+#[cfg(feature = "tests")]
+pub mod merkle_tests {
+    use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+    use super::*;
+    use crate::{backend::Backend, types::SnapshotId, CrepeDB};
+
+    /// Compute the root of `leaves`, sorted by key, from scratch.
+    fn reference_root(depth: usize, leaves: &[(Hash, Hash)]) -> Hash {
+        match leaves {
+            [] => EMPTY_ROOT,
+            [(key, value)] => leaf_hash(key, value),
+            _ => {
+                let split = leaves.partition_point(|(key, _)| !bit(key, depth));
+
+                internal_hash(
+                    &reference_root(depth + 1, &leaves[..split]),
+                    &reference_root(depth + 1, &leaves[split..]),
+                )
+            }
+        }
+    }
+
+    /// Compute the state root of `snapshot` from its visible entries.
+    fn expected_root<B: Backend>(
+        db: &CrepeDB<B>,
+        snapshot: &SnapshotId,
+        tables: &[&str],
+    ) -> Result<Hash> {
+        let txn = db.read(Some(snapshot.clone()))?;
+
+        let mut roots = Vec::new();
+
+        for name in tables {
+            let table = txn.open_table(name)?;
+
+            let mut leaves: Vec<_> = table
+                .range([], [0xff; 4])?
+                .into_iter()
+                .map(|(key, value)| (sha256(&[&key]), sha256(&[&value])))
+                .collect();
+            leaves.sort_unstable();
+
+            if !leaves.is_empty() {
+                roots.push((sha256(&[name.as_bytes()]), reference_root(0, &leaves)));
+            }
+        }
+
+        roots.sort_unstable();
+
+        Ok(reference_root(0, &roots))
+    }
+
+    fn state_root<B: Backend>(db: &CrepeDB<B>, snapshot: &SnapshotId) -> Result<Option<Hash>> {
+        Ok(db.get_snapshot_info(snapshot.clone())?.state_root)
+    }
+
+    /// Test state roots against roots computed from scratch
+    pub fn test_state_root(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend).with_state_root(true);
+        let tables = ["a", "b"];
+
+        let txn = db.write(None)?;
+        txn.create_versioned_table("a")?;
+        txn.create_versioned_table("b")?;
+        txn.create_basic_table("c")?;
+        let root = txn.commit()?;
+
+        assert_eq!(state_root(&db, &root)?, Some(EMPTY_ROOT));
+
+        let txn = db.write(Some(root.clone()))?;
+        {
+            let mut a = txn.open_table("a")?;
+            for i in 0..100u8 {
+                a.set([i], [i, 1])?;
+            }
+
+            let mut b = txn.open_table("b")?;
+            b.set([1], [1])?;
+            b.set([2], [2])?;
+            b.del([3])?;
+        }
+        let s1 = txn.commit()?;
+
+        let expected = expected_root(&db, &s1, &tables)?;
+        assert_eq!(state_root(&db, &s1)?, Some(expected));
+        assert_ne!(expected, EMPTY_ROOT);
+
+        // Updates, deletes, rewrites of the same value and basic tables.
+        let txn = db.write(Some(s1.clone()))?;
+        {
+            let mut a = txn.open_table("a")?;
+            for i in (0..100u8).step_by(3) {
+                a.del([i])?;
+            }
+            for i in (1..100u8).step_by(7) {
+                a.set([i], [i, 2])?;
+            }
+            a.set([2], [2, 1])?;
+            a.set([200], [200])?;
+
+            txn.open_table("c")?.set([1], [1])?;
+        }
+        let s2 = txn.commit()?;
+
+        assert_eq!(
+            state_root(&db, &s2)?,
+            Some(expected_root(&db, &s2, &tables)?)
+        );
+
+        // A fork of s1 which deletes every entry of `b`, so its leaf is
+        // removed from the state tree, and rolls back a write.
+        let txn = db.write(Some(s1.clone()))?;
+        {
+            let mut b = txn.open_table("b")?;
+            b.del([1])?;
+            b.del([2])?;
+
+            let savepoint = txn.savepoint()?;
+            txn.open_table("a")?.set([50], [0])?;
+            savepoint.rollback_to()?;
+        }
+        let s3 = txn.commit()?;
+
+        assert_eq!(
+            state_root(&db, &s3)?,
+            Some(expected_root(&db, &s3, &tables)?)
+        );
+
+        // Deleting every entry of a subtree collapses it.
+        let txn = db.write(Some(s3.clone()))?;
+        {
+            let mut a = txn.open_table("a")?;
+            for i in 0..99u8 {
+                a.del([i])?;
+            }
+        }
+        let s4 = txn.commit()?;
+
+        let expected = expected_root(&db, &s4, &tables)?;
+        assert_eq!(state_root(&db, &s4)?, Some(expected));
+
+        let txn = db.write(Some(s4.clone()))?;
+        txn.open_table("a")?.del([99])?;
+        let s5 = txn.commit()?;
+        assert_eq!(state_root(&db, &s5)?, Some(EMPTY_ROOT));
+
+        // Snapshots without changes keep the root of their parent, and the
+        // roots of old snapshots do not change.
+        let txn = db.write(Some(s2.clone()))?;
+        let s6 = txn.commit()?;
+        assert_eq!(state_root(&db, &s6)?, state_root(&db, &s2)?);
+        assert_eq!(
+            state_root(&db, &s1)?,
+            Some(expected_root(&db, &s1, &tables)?)
+        );
+
+        // Roots are kept by databases opened without the option.
+        let db = CrepeDB::new(db.into_backend());
+
+        let txn = db.write(Some(s6))?;
+        txn.open_table("b")?.set([9], [9])?;
+        let s7 = txn.commit()?;
+        assert_eq!(
+            state_root(&db, &s7)?,
+            Some(expected_root(&db, &s7, &tables)?)
+        );
+
+        let report = db.verify()?;
+        assert!(report.is_ok(), "{:?}", report.issues);
+
+        Ok(())
+    }
+
+    /// Test that snapshots of databases without state roots have none
+    pub fn test_no_state_root(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+
+        let txn = db.write(None)?;
+        txn.create_versioned_table("a")?;
+        let root = txn.commit()?;
+
+        let txn = db.write(Some(root.clone()))?;
+        txn.open_table("a")?.set(vec![1], vec![1])?;
+        let s1 = txn.commit()?;
+
+        assert_eq!(state_root(&db, &root)?, None);
+        assert_eq!(state_root(&db, &s1)?, None);
+
+        let expected: BTreeMap<_, _> = [(vec![1], vec![1])].into();
+        let txn = db.read(Some(s1))?;
+        assert_eq!(
+            txn.open_table("a")?.range([], [0xff])?,
+            expected.into_iter().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    /// Test proofs of present and absent keys against state roots
+    pub fn test_proofs(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend).with_state_root(true);
+
+        let txn = db.write(None)?;
+        txn.create_versioned_table("a")?;
+        txn.create_versioned_table("b")?;
+        txn.create_versioned_table("e")?;
+        txn.create_basic_table("c")?;
+        let root = txn.commit()?;
+
+        let txn = db.write(Some(root))?;
+        {
+            let mut a = txn.open_table("a")?;
+            for i in 0..200u8 {
+                a.set([i], [i; 3])?;
+            }
+
+            txn.open_table("b")?.set([1], [1])?;
+            txn.open_table("c")?.set([1], [1])?;
+            txn.open_table("e")?.del([1])?;
+        }
+        let s1 = txn.commit()?;
+
+        let txn = db.write(Some(s1.clone()))?;
+        {
+            let mut a = txn.open_table("a")?;
+            a.set([7], [0])?;
+            a.del([8])?;
+        }
+        let s2 = txn.commit()?;
+
+        for snapshot in [&s1, &s2] {
+            let state_root = state_root(&db, snapshot)?.unwrap();
+            let txn = db.read(Some(snapshot.clone()))?;
+
+            for name in ["a", "b", "e"] {
+                let table = txn.open_table(name)?;
+
+                for i in 0..=255u8 {
+                    let key = [i];
+                    let (value, proof) = table.get_with_proof(key)?;
+                    assert_eq!(value, table.get(key)?);
+
+                    let value = value.as_deref();
+                    assert!(verify_proof(&state_root, name, &key, value, &proof));
+
+                    let decoded = Proof::from_bytes(&proof.to_bytes())?;
+                    assert_eq!(decoded, proof);
+
+                    // Other values, keys, tables and roots are rejected.
+                    let other = match value {
+                        Some(_) => None,
+                        None => Some(&[i][..]),
+                    };
+                    assert!(!verify_proof(&state_root, name, &key, other, &proof));
+                    assert!(!verify_proof(
+                        &state_root,
+                        name,
+                        &key,
+                        Some(&[9, 9]),
+                        &proof
+                    ));
+                    assert!(!verify_proof(&EMPTY_ROOT, name, &key, value, &proof));
+
+                    // Absent keys are absent from missing tables as well.
+                    if value.is_some() {
+                        assert!(!verify_proof(&state_root, name, &[i, 0], value, &proof));
+                        assert!(!verify_proof(&state_root, "d", &key, value, &proof));
+                    }
+
+                    if let Some(sibling) = proof.key.siblings.first() {
+                        let mut tampered = proof.clone();
+                        tampered.key.siblings[0] = leaf_hash(sibling, sibling);
+                        assert!(!verify_proof(&state_root, name, &key, value, &tampered));
+                    }
+                }
+            }
+        }
+
+        // Proofs of old snapshots are unchanged by later snapshots.
+        let txn = db.read(Some(s1.clone()))?;
+        let (value, proof) = txn.open_table("a")?.get_with_proof([8])?;
+        assert_eq!(value, Some(vec![8; 3]));
+        assert!(verify_proof(
+            &state_root(&db, &s1)?.unwrap(),
+            "a",
+            &[8],
+            Some(&[8; 3]),
+            &proof
+        ));
+
+        assert!(matches!(
+            txn.open_table("c")?.get_with_proof([1]),
+            Err(Error::MissingStateRoot)
+        ));
+        assert!(Proof::from_bytes(&[0]).is_err());
+
+        Ok(())
+    }
+}
@@ -1,4 +1,11 @@
-use core::marker::PhantomData;
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{
+    cell::RefCell,
+    cmp::Ordering,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    ops::Deref,
+};
 
 use crate::{
     backend::{BackendError, Range, ReadTable as BackendReadTable},
@@ -14,13 +21,14 @@ use crate::{
 pub struct ReadTable<T, E> {
     pub(crate) table: T,
 
-    pub(crate) index: IndexTable<T, E>,
-    pub(crate) snapshot: SnapshotTable<T, E>,
-
     pub(crate) table_type: TableType,
 
-    pub(crate) snapshot_id: SnapshotId,
-    pub(crate) version: Version,
+    pub(crate) view: View<T, E>,
+
+    /// The Merkle table and the state root of a versioned table's snapshot,
+    /// if the snapshot has one.
+    #[cfg(feature = "merkle")]
+    pub(crate) merkle: Option<(T, crate::types::Hash)>,
 
     pub(crate) marker: PhantomData<E>,
 }
@@ -44,82 +52,573 @@ where
     /// * `Ok(Some(value))` if the key exists
     /// * `Ok(None)` if the key does not exist or was deleted
     /// * `Err(...)` if an error occurs
-    pub fn get(&self, key: Bytes) -> Result<Option<Bytes>> {
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Result<Option<Bytes>> {
         match self.table_type {
-            TableType::Basic => self.get_basic(key),
-            TableType::Versioned => self.get_versioned(key),
+            TableType::Basic => self.get_basic(key.as_ref()),
+            TableType::Versioned => self.get_versioned(key.as_ref()),
         }
     }
 
-    fn get_basic(&self, key: Bytes) -> Result<Option<Bytes>> {
+    fn get_basic(&self, key: &[u8]) -> Result<Option<Bytes>> {
         let res = self.table.get(key).map_err(Error::backend)?;
 
         Ok(res)
     }
 
-    fn get_versioned(&self, key: Bytes) -> Result<Option<Bytes>> {
-        let key_len = key.len();
+    fn get_versioned(&self, key: &[u8]) -> Result<Option<Bytes>> {
+        self.view.get(&self.table, key)
+    }
+
+    /// Get the value associated with a key without copying it, if the backend
+    /// can lend it.
+    ///
+    /// Same as [`get`](Self::get), but the value borrows the stored bytes
+    /// where the backend allows, such as redb's `AccessGuard`, MDBX's
+    /// `Cow<[u8]>` or RocksDB's `DBPinnableSlice`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to look up
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(value))` if the key exists
+    /// * `Ok(None)` if the key does not exist or was deleted
+    /// * `Err(...)` if an error occurs
+    pub fn get_ref(&self, key: impl AsRef<[u8]>) -> Result<Option<ValueRef<T::Value<'_>>>> {
+        let value = match self.table_type {
+            TableType::Basic => self.table.get_ref(key.as_ref()),
+            TableType::Versioned => match self.view.visible_key(&self.table, key.as_ref())? {
+                Some(key) => self.table.get_ref(&key),
+                None => return Ok(None),
+            },
+        };
+
+        let Some(value) = value.map_err(Error::backend)? else {
+            return Ok(None);
+        };
+
+        match self.table_type {
+            TableType::Basic => Ok(Some(ValueRef::new(value))),
+            TableType::Versioned => ValueRef::from_data_op(value),
+        }
+    }
+
+    /// Get the value associated with a key with a proof of the value, or of
+    /// its absence, against the state root of this table's snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to look up
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MissingStateRoot`] for a basic table or a snapshot
+    /// without a state root, and [`Error::InvalidMerkleNode`] if the tree does
+    /// not match the table.
+    #[cfg(feature = "merkle")]
+    pub fn get_with_proof(&self, key: impl AsRef<[u8]>) -> Result<(Option<Bytes>, crate::Proof)> {
+        let key = key.as_ref();
+        let (merkle, state_root) = self.merkle.as_ref().ok_or(Error::MissingStateRoot)?;
 
-        let mut begin = key.clone();
-        let mut end = key;
+        let (value_hash, proof) = crate::merkle::prove(
+            |position| self.view.get(merkle, position),
+            state_root,
+            self.table.name(),
+            key,
+        )?;
 
-        begin.extend_from_slice(&0u64.to_le_bytes());
-        begin.extend_from_slice(&SnapshotId::root().to_bytes());
+        let value = self.get_versioned(key)?;
 
-        end.extend_from_slice(&self.version.0.to_le_bytes());
-        end.extend_from_slice(&SnapshotId::preroot().to_bytes());
+        if value.as_deref().map(crate::merkle::value_hash) != value_hash {
+            return Err(Error::InvalidMerkleNode);
+        }
 
-        let mut iter = self.table.range(begin, end).map_err(Error::backend)?;
+        Ok((value, proof))
+    }
 
-        while let Some((k, v)) = iter.back().map_err(Error::backend)? {
-            let version = Version::from_bytes(&k[key_len..key_len + 8])?;
-            let sss = SnapshotId::from_bytes(&k[key_len + 8..key_len + 16])?;
+    /// Get all key-value pairs with keys in `begin..end`, ordered by key.
+    ///
+    /// For versioned tables, this returns the pairs visible at this table's
+    /// snapshot version. For basic tables, this returns the current pairs.
+    ///
+    /// The whole range is read when this is called, see [`scan`](Self::scan)
+    /// to read it as it is iterated.
+    ///
+    /// # Arguments
+    ///
+    /// * `begin` - The first key of the range
+    /// * `end` - The key after the range
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend fails or a row is malformed.
+    pub fn range(
+        &self,
+        begin: impl AsRef<[u8]>,
+        end: impl AsRef<[u8]>,
+    ) -> Result<Vec<(Bytes, Bytes)>> {
+        self.scan(begin, Some(end.as_ref()))?.collect()
+    }
+
+    /// Iterate over the key-value pairs with keys from `begin` up to `end`,
+    /// ordered by key.
+    ///
+    /// Same as [`range`](Self::range), but rows are read from the backend as
+    /// the iterator advances, and the range has no end for `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `begin` - The first key of the range
+    /// * `end` - The key after the range, `None` to read to the last key
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend fails. The iterator returns an error
+    /// if the backend fails or a row is malformed.
+    pub fn scan(&self, begin: impl AsRef<[u8]>, end: Option<&[u8]>) -> Result<Scan<'_, T, E>> {
+        let begin = begin.as_ref();
 
-            log::trace!("version: {version}, snapshot: {sss:?}, value: {v:?}");
+        let range = match end {
+            Some(end) if begin >= end => None,
+            _ => {
+                // Entries of versioned keys sort after their key, the end of
+                // their range is checked while scanning.
+                let end = match self.table_type {
+                    TableType::Basic => end,
+                    TableType::Versioned => None,
+                };
 
-            if version > self.version {
-                continue;
+                Some(self.table.range(begin, end).map_err(Error::backend)?)
             }
+        };
+
+        let mut tail = self.view.version.to_bytes().to_vec();
+        tail.extend_from_slice(&SnapshotId::preroot().to_bytes());
+
+        Ok(Scan {
+            table: self,
+            range,
+            begin: begin.to_vec(),
+            end: end.map(<[u8]>::to_vec),
+            tail,
+            peeked: None,
+            pending: BTreeMap::new(),
+            ancestors: BTreeMap::new(),
+        })
+    }
+}
+
+/// An iterator over the key-value pairs of a range of a [`ReadTable`],
+/// ordered by key.
+///
+/// Created by [`ReadTable::scan`]. Rows are read from the backend as the
+/// iterator advances.
+pub struct Scan<'a, T, E>
+where
+    T: BackendReadTable<E> + 'a,
+{
+    table: &'a ReadTable<T, E>,
+
+    /// The rows of the backend, `None` once they are all read.
+    range: Option<T::Range<'a>>,
+
+    begin: Bytes,
+    end: Option<Bytes>,
+
+    /// The version of the snapshot followed by the largest snapshot ID, which
+    /// bounds the entries of versioned keys.
+    tail: Bytes,
+
+    /// The next row of a versioned table.
+    peeked: Option<(Bytes, Bytes)>,
+
+    /// Keys of a versioned table with their visible entry so far, which rows
+    /// not read yet can still change or precede.
+    pending: BTreeMap<Bytes, Bytes>,
+
+    ancestors: BTreeMap<Version, Option<SnapshotId>>,
+}
+
+impl<'a, T, E> Scan<'a, T, E>
+where
+    T: BackendReadTable<E> + 'a,
+    E: BackendError,
+{
+    fn next_basic(&mut self) -> Result<Option<(Bytes, Bytes)>> {
+        let Some(range) = &mut self.range else {
+            return Ok(None);
+        };
 
-            let mut target_version = self.version.0;
-            let mut snapshot = self.snapshot_id.clone();
+        range.back().map_err(Error::backend)
+    }
+
+    /// Return the visible pairs of a versioned table.
+    ///
+    /// Entries of a key are ordered by version, but the entries of its
+    /// extensions can sort between them, and the entries of a key can sort
+    /// after the entries of its extensions. A key is returned once the next
+    /// row sorts after every entry of the key and of its prefixes.
+    fn next_versioned(&mut self) -> Result<Option<(Bytes, Bytes)>> {
+        loop {
+            if self.peeked.is_none() {
+                if let Some(range) = &mut self.range {
+                    self.peeked = range.back().map_err(Error::backend)?;
 
-            while target_version > version.0 {
-                let diff = target_version - version.0;
+                    // Rows after every entry of the keys before the end are
+                    // not read.
+                    let past_end = match (&self.peeked, &self.end) {
+                        (Some((row, _)), Some(end)) => after(row, end, &self.tail),
+                        _ => false,
+                    };
 
-                if diff == 1 {
-                    let (_, s) = self.snapshot.read(&snapshot)?;
-                    snapshot = s;
-                    target_version = version.0;
-                    break;
+                    if self.peeked.is_none() || past_end {
+                        self.peeked = None;
+                        self.range = None;
+                    }
                 }
+            }
 
-                let skip_i = diff.ilog2();
-                let skip = 1 << skip_i;
+            if let Some(entry) = self.pending.first_entry() {
+                let ready = match &self.peeked {
+                    Some((row, _)) => after(row, entry.key(), &self.tail),
+                    None => true,
+                };
 
-                log::trace!("Read snapshot: {snapshot:?}, target_version: {target_version}, version: {version}, skip_n: {skip_i}, skip numer is: {skip}");
+                if ready {
+                    let (key, value) = entry.remove_entry();
 
-                if let Some(snapshot_id) = self.index.read(&snapshot, skip_i)? {
-                    snapshot = snapshot_id;
-                } else {
-                    break;
+                    match DataOp::from_bytes(value)?.into() {
+                        Some(value) => return Ok(Some((key, value))),
+                        None => continue,
+                    }
                 }
+            }
+
+            let Some((row, value)) = self.peeked.take() else {
+                return Ok(None);
+            };
+
+            self.push(row, value)?;
+        }
+    }
+
+    /// Keep the entry `row` of a versioned table if it is visible.
+    fn push(&mut self, row: Bytes, value: Bytes) -> Result<()> {
+        let Some(key_len) = row.len().checked_sub(16) else {
+            return Ok(());
+        };
+
+        let key = &row[..key_len];
+
+        // Entries of shorter keys can sort after `begin`, and entries of
+        // longer keys before the end of the range.
+        if key < &self.begin[..] || self.end.as_deref().is_some_and(|end| key >= end) {
+            return Ok(());
+        }
+
+        let version = Version::from_bytes(&row[key_len..key_len + 8])?;
+        let sss = SnapshotId::from_bytes(&row[key_len + 8..])?;
+
+        if version > self.table.view.version {
+            return Ok(());
+        }
+
+        let ancestor = match self.ancestors.get(&version) {
+            Some(ancestor) => ancestor,
+            None => {
+                let ancestor = self.table.view.ancestor(version.clone())?;
+                self.ancestors.entry(version).or_insert(ancestor)
+            }
+        };
+
+        // The last entry written by an ancestor is the visible one.
+        if ancestor.as_ref() == Some(&sss) {
+            self.pending.insert(key.to_vec(), value);
+        }
+
+        Ok(())
+    }
+}
 
-                target_version -= skip;
+impl<'a, T, E> Iterator for Scan<'a, T, E>
+where
+    T: BackendReadTable<E> + 'a,
+    E: BackendError,
+{
+    type Item = Result<(Bytes, Bytes)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = match self.table.table_type {
+            TableType::Basic => self.next_basic(),
+            TableType::Versioned => self.next_versioned(),
+        };
+
+        res.transpose()
+    }
+}
+
+/// Whether the versioned row `row` sorts after every entry of `key` and of
+/// its prefixes, whose entries sort before the prefix followed by `tail`.
+fn after(row: &[u8], key: &[u8], tail: &[u8]) -> bool {
+    // `row` starts with the first `k` bytes of `key`.
+    let mut k = 0;
+
+    loop {
+        if &row[k..] <= tail {
+            return false;
+        }
+
+        if k == key.len() {
+            return true;
+        }
+
+        match row[k].cmp(&key[k]) {
+            Ordering::Greater => return true,
+            Ordering::Less => return false,
+            Ordering::Equal => k += 1,
+        }
+    }
+}
+
+/// The snapshot which versioned tables are read at.
+pub(crate) struct View<T, E> {
+    pub(crate) index: IndexTable<T, E>,
+    pub(crate) snapshot: SnapshotTable<T, E>,
+
+    pub(crate) snapshot_id: SnapshotId,
+    pub(crate) version: Version,
+
+    /// Reused buffer for the range bounds of versioned keys.
+    pub(crate) scratch: RefCell<Bytes>,
+}
+
+impl<T, E> View<T, E>
+where
+    T: BackendReadTable<E>,
+    E: BackendError,
+{
+    /// Get the value of `key` in versioned table `table` at this view's
+    /// snapshot.
+    pub(crate) fn get(
+        &self,
+        table: &impl BackendReadTable<E>,
+        key: &[u8],
+    ) -> Result<Option<Bytes>> {
+        match self.find(table, key, true)? {
+            Some((_, Some(value))) => Ok(DataOp::from_bytes(value)?.into()),
+            _ => Ok(None),
+        }
+    }
+
+    /// Find the stored key of the entry of `key` in versioned table `table`
+    /// visible at this view's snapshot version.
+    pub(crate) fn visible_key(
+        &self,
+        table: &impl BackendReadTable<E>,
+        key: &[u8],
+    ) -> Result<Option<Bytes>> {
+        Ok(self.find(table, key, false)?.map(|(key, _)| key))
+    }
+
+    /// Find the entry of `key` in versioned table `table` visible at this
+    /// view's snapshot, with its stored value if `with_value`.
+    fn find(
+        &self,
+        table: &impl BackendReadTable<E>,
+        key: &[u8],
+        with_value: bool,
+    ) -> Result<Option<(Bytes, Option<Bytes>)>> {
+        find_visible(
+            table,
+            key,
+            &self.version,
+            with_value,
+            &mut self.scratch.borrow_mut(),
+            |version| self.ancestor(version),
+        )
+    }
+
+    /// Find the ancestor of this view's snapshot at `version`, walking the
+    /// snapshot index.
+    pub(crate) fn ancestor(&self, version: Version) -> Result<Option<SnapshotId>> {
+        let mut target_version = self.version.0;
+        let mut snapshot = self.snapshot_id.clone();
+
+        while target_version > version.0 {
+            let diff = target_version - version.0;
+
+            if diff == 1 {
+                let (_, s) = self.snapshot.read(&snapshot)?;
+                snapshot = s;
+                target_version = version.0;
+                break;
+            }
+
+            let skip_i = diff.ilog2();
+            let skip = 1 << skip_i;
+
+            log::trace!("Read snapshot: {snapshot:?}, target_version: {target_version}, version: {version}, skip_n: {skip_i}, skip numer is: {skip}");
+
+            if let Some(snapshot_id) = self.index.read(&snapshot, skip_i)? {
+                snapshot = snapshot_id;
+            } else {
+                break;
+            }
+
+            target_version -= skip;
+        }
+
+        if version.0 == target_version {
+            Ok(Some(snapshot))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Find the entry of `key` in versioned table `table` visible at `version`
+/// of a snapshot, with its stored value if `with_value`.
+///
+/// The visible entry is the newest one written by an ancestor, which
+/// `ancestor` resolves by version. Entries are searched from `version` down,
+/// in windows of versions doubling in size, so the search stops near the
+/// visible entry instead of reading the whole history of the key.
+pub(crate) fn find_visible<E: BackendError>(
+    table: &impl BackendReadTable<E>,
+    key: &[u8],
+    version: &Version,
+    with_value: bool,
+    scratch: &mut Bytes,
+    mut ancestor: impl FnMut(Version) -> Result<Option<SnapshotId>>,
+) -> Result<Option<(Bytes, Option<Bytes>)>> {
+    let key_len = key.len();
+
+    let mut high = version.0;
+    let mut width = 1u64;
+
+    loop {
+        let low = high.saturating_sub(width - 1);
+
+        scratch.clear();
+
+        scratch.extend_from_slice(key);
+        scratch.extend_from_slice(&Version::from(low).to_bytes());
+        scratch.extend_from_slice(&SnapshotId::root().to_bytes());
+
+        scratch.extend_from_slice(key);
+        scratch.extend_from_slice(&Version::from(high).to_bytes());
+        scratch.extend_from_slice(&SnapshotId::preroot().to_bytes());
+
+        let (begin, end) = scratch.split_at(key_len + 16);
+        let mut iter = table.range(begin, Some(end)).map_err(Error::backend)?;
+
+        let mut entries = Vec::new();
+
+        loop {
+            let entry = if with_value {
+                let entry = iter.back().map_err(Error::backend)?;
+                entry.map(|(k, v)| (k, Some(v)))
+            } else {
+                let entry = iter.back_key().map_err(Error::backend)?;
+                entry.map(|k| (k, None))
+            };
+
+            let Some((k, v)) = entry else {
+                break;
+            };
+
+            // Skip entries of longer keys that start with this key.
+            if k.len() == key_len + 16 {
+                entries.push((k, v));
             }
+        }
+
+        // Entries are ordered by version, so the first entry written by an
+        // ancestor from the end is the visible one.
+        let mut cached = None;
 
-            if sss == snapshot && version.0 == target_version {
-                log::trace!(
-                    "The snapshot: {sss:?} is ancestor of snapshot: {:?}",
-                    self.snapshot_id
-                );
+        for (k, v) in entries.into_iter().rev() {
+            let version = Version::from_bytes(&k[key_len..key_len + 8])?;
+            let sss = SnapshotId::from_bytes(&k[key_len + 8..])?;
 
-                let res = DataOp::from_bytes(v)?;
+            log::trace!("version: {version}, snapshot: {sss:?}");
 
-                return Ok(res.into());
+            // Forks write entries of the same version.
+            let found = match &mut cached {
+                Some((v, found)) if *v == version => found,
+                _ => {
+                    let found = ancestor(version.clone())?;
+                    &mut cached.insert((version, found)).1
+                }
+            };
+
+            if found.as_ref() == Some(&sss) {
+                log::trace!("The snapshot: {sss:?} is ancestor");
+
+                return Ok(Some((k, v)));
             }
         }
 
-        Ok(None)
+        if low == 0 {
+            return Ok(None);
+        }
+
+        high = low - 1;
+        width = width.saturating_mul(2);
+    }
+}
+
+/// A value borrowed from the backend by [`ReadTable::get_ref`].
+///
+/// Dereferences to the bytes of the value. The backend keeps the bytes
+/// alive, and may keep its transaction or pages pinned, until it is dropped.
+pub struct ValueRef<V> {
+    value: V,
+
+    /// Length of the value, without the data operation flag of versioned
+    /// tables.
+    len: usize,
+}
+
+impl<V: AsRef<[u8]>> ValueRef<V> {
+    fn new(value: V) -> Self {
+        let len = value.as_ref().len();
+
+        Self { value, len }
+    }
+
+    /// Strip the data operation flag of a versioned table entry, `None` if
+    /// the entry is a deletion.
+    fn from_data_op(value: V) -> Result<Option<Self>> {
+        let len = DataOp::value_len(value.as_ref())?;
+
+        Ok(len.map(|len| Self { value, len }))
+    }
+
+    /// Unwrap the value returned by the backend, which still ends with the
+    /// data operation flag for versioned tables.
+    pub fn into_inner(self) -> V {
+        self.value
+    }
+}
+
+impl<V: AsRef<[u8]>> Deref for ValueRef<V> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.value.as_ref()[..self.len]
+    }
+}
+
+impl<V: AsRef<[u8]>> AsRef<[u8]> for ValueRef<V> {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<V: AsRef<[u8]>> Debug for ValueRef<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ValueRef").field(&&**self).finish()
     }
 }
@@ -1,9 +1,9 @@
-use core::marker::PhantomData;
+use core::{cell::RefCell, marker::PhantomData};
 
 use crate::{
     backend::{BackendError, ReadTxn as BackendReadTxn},
     types::SnapshotId,
-    utils, Error, ReadTable, Result,
+    utils, Error, ReadTable, Result, View,
 };
 
 /// A read transaction for querying data at a specific snapshot.
@@ -43,15 +43,31 @@ where
         let index = utils::index_reader(&self.txn)?;
 
         let sr = utils::snapshot_reader(&self.txn)?;
-        let (version, _) = sr.read(&self.snapshot_id)?;
+        let info = sr.read_info(&self.snapshot_id)?;
+
+        #[cfg(feature = "merkle")]
+        let merkle = match (&table_type, info.state_root) {
+            (crate::types::TableType::Versioned, Some(state_root)) => Some((
+                self.txn
+                    .open_table(crate::consts::MERKLE_TABLE)
+                    .map_err(Error::backend)?,
+                state_root,
+            )),
+            _ => None,
+        };
 
         let table = ReadTable {
             table,
-            index,
-            snapshot: sr,
             table_type,
-            snapshot_id: self.snapshot_id.clone(),
-            version,
+            view: View {
+                index,
+                snapshot: sr,
+                snapshot_id: self.snapshot_id.clone(),
+                version: info.version,
+                scratch: RefCell::default(),
+            },
+            #[cfg(feature = "merkle")]
+            merkle,
             marker: PhantomData,
         };
 
@@ -716,4 +732,255 @@ pub mod read_tests {
 
         Ok(())
     }
+
+    /// Test that the newest write of an ancestor wins
+    pub fn test_overwrite_in_chain(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+        let table = "test_overwrite_chain";
+
+        // Create root
+        let rtxn = db.write(None)?;
+        rtxn.create_versioned_table(table)?;
+        let mut current = rtxn.commit()?;
+
+        // Overwrite and delete the same key along a chain
+        for i in 1u8..=6 {
+            let wtxn = db.write(Some(current))?;
+            {
+                let mut t = wtxn.open_table(table)?;
+                if i == 4 {
+                    t.del(vec![1])?;
+                } else {
+                    t.set(vec![1], vec![i])?;
+                }
+            }
+            current = wtxn.commit()?;
+
+            let rtxn = db.read(Some(current.clone()))?;
+            let t = rtxn.open_table(table)?;
+            let expected = if i == 4 { None } else { Some(vec![i]) };
+            assert_eq!(t.get(vec![1])?, expected);
+        }
+
+        Ok(())
+    }
+
+    /// Test keys that are prefixes of other keys
+    pub fn test_prefix_keys(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+        let table = "test_prefix";
+
+        // Create root
+        let rtxn = db.write(None)?;
+        rtxn.create_versioned_table(table)?;
+        let root = rtxn.commit()?;
+
+        // A longer key that starts with the shorter key and a valid version
+        // and snapshot ID.
+        let mut long = vec![2];
+        long.extend_from_slice(&1u64.to_be_bytes());
+        long.extend_from_slice(&1u64.to_be_bytes());
+
+        let wtxn = db.write(Some(root.clone()))?;
+        {
+            let mut t = wtxn.open_table(table)?;
+            t.set(long.clone(), vec![1])?;
+            t.set(vec![2, 0], vec![2])?;
+        }
+        let s1 = wtxn.commit()?;
+
+        {
+            let rtxn = db.read(Some(s1))?;
+            let t = rtxn.open_table(table)?;
+            assert_eq!(t.get(vec![2])?, None);
+            assert_eq!(t.get(vec![2, 0])?, Some(vec![2]));
+            assert_eq!(t.get(long)?, Some(vec![1]));
+        }
+
+        let wtxn = db.write(Some(root))?;
+        {
+            let mut t = wtxn.open_table(table)?;
+            t.del(vec![2])?;
+        }
+        let s2 = wtxn.commit()?;
+
+        {
+            let rtxn = db.read(Some(s2))?;
+            let t = rtxn.open_table(table)?;
+            assert_eq!(t.get(vec![2])?, None);
+            assert_eq!(t.get(vec![2, 0])?, None);
+        }
+
+        Ok(())
+    }
+
+    /// Test range reads of versioned and basic tables
+    pub fn test_range(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+
+        let wtxn = db.write(None)?;
+        wtxn.create_versioned_table("v")?;
+        wtxn.create_basic_table("b")?;
+        {
+            let mut t = wtxn.open_table("v")?;
+            t.set(vec![], vec![0])?;
+            t.set(vec![1], vec![1])?;
+            t.set(vec![2], vec![2])?;
+            t.set(vec![2, 0], vec![20])?;
+            t.set(vec![3], vec![3])?;
+        }
+        {
+            let mut t = wtxn.open_table("b")?;
+            t.set(vec![1], vec![1])?;
+            t.set(vec![2], vec![2])?;
+            t.set(vec![3], vec![3])?;
+        }
+        let root = wtxn.commit()?;
+
+        // Keys which are prefixes of other keys, one ending with a valid
+        // version and snapshot ID.
+        let mut long = vec![2];
+        long.extend_from_slice(&1u64.to_be_bytes());
+        long.extend_from_slice(&1u64.to_be_bytes());
+
+        let mut tip = root.clone();
+        for i in 0..5u8 {
+            let wtxn = db.write(Some(tip))?;
+            {
+                let mut t = wtxn.open_table("v")?;
+                t.set(vec![1], vec![10 + i])?;
+                t.set(vec![4, i], vec![i])?;
+                if i == 2 {
+                    t.del(vec![2])?;
+                    t.set(long.clone(), vec![5])?;
+                }
+            }
+            tip = wtxn.commit()?;
+        }
+
+        let wtxn = db.write(Some(root.clone()))?;
+        wtxn.open_table("v")?.del(vec![3])?;
+        wtxn.open_table("b")?.del(vec![2])?;
+        let fork = wtxn.commit()?;
+
+        let rtxn = db.read(Some(tip))?;
+        let t = rtxn.open_table("v")?;
+        assert_eq!(
+            t.range(vec![], vec![0xff])?,
+            vec![
+                (vec![], vec![0]),
+                (vec![1], vec![14]),
+                (vec![2, 0], vec![20]),
+                (long.clone(), vec![5]),
+                (vec![3], vec![3]),
+                (vec![4, 0], vec![0]),
+                (vec![4, 1], vec![1]),
+                (vec![4, 2], vec![2]),
+                (vec![4, 3], vec![3]),
+                (vec![4, 4], vec![4]),
+            ]
+        );
+
+        // Keys outside the range are skipped, even where their entries are
+        // inside it.
+        assert_eq!(
+            t.range(vec![1, 0], vec![2, 1])?,
+            vec![(vec![2, 0], vec![20]), (long.clone(), vec![5])]
+        );
+        assert_eq!(
+            t.range(vec![2], vec![4, 2])?,
+            vec![
+                (vec![2, 0], vec![20]),
+                (long.clone(), vec![5]),
+                (vec![3], vec![3]),
+                (vec![4, 0], vec![0]),
+                (vec![4, 1], vec![1]),
+            ]
+        );
+        assert_eq!(t.range(vec![], vec![1])?, vec![(vec![], vec![0])]);
+        assert_eq!(t.range(vec![3], vec![3])?, vec![]);
+        assert_eq!(t.range(vec![4], vec![3])?, vec![]);
+
+        let rtxn = db.read(Some(fork))?;
+        let t = rtxn.open_table("v")?;
+        assert_eq!(
+            t.range(vec![], vec![0xff])?,
+            vec![
+                (vec![], vec![0]),
+                (vec![1], vec![1]),
+                (vec![2], vec![2]),
+                (vec![2, 0], vec![20]),
+            ]
+        );
+
+        // Basic tables are not versioned.
+        let t = rtxn.open_table("b")?;
+        assert_eq!(
+            t.range(vec![], vec![0xff])?,
+            vec![(vec![1], vec![1]), (vec![3], vec![3])]
+        );
+        assert_eq!(t.range(vec![2], vec![3])?, vec![]);
+
+        let rtxn = db.read(Some(root))?;
+        let t = rtxn.open_table("v")?;
+        assert_eq!(
+            t.range(vec![1], vec![3])?,
+            vec![
+                (vec![1], vec![1]),
+                (vec![2], vec![2]),
+                (vec![2, 0], vec![20])
+            ]
+        );
+
+        Ok(())
+    }
+
+    /// Test borrowed reads of versioned and basic tables
+    pub fn test_get_ref(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+
+        let big = vec![7; 1 << 16];
+
+        let wtxn = db.write(None)?;
+        wtxn.create_versioned_table("v")?;
+        wtxn.create_basic_table("b")?;
+        {
+            let mut t = wtxn.open_table("v")?;
+            t.set(vec![1], big.clone())?;
+            t.set(vec![2], vec![])?;
+            t.set(vec![3], vec![3])?;
+        }
+        wtxn.open_table("b")?.set(vec![1], big.clone())?;
+        let root = wtxn.commit()?;
+
+        let wtxn = db.write(Some(root.clone()))?;
+        {
+            let mut t = wtxn.open_table("v")?;
+            t.set(vec![1], vec![1, 0])?;
+            t.del(vec![3])?;
+            t.set(vec![3, 0], vec![30])?;
+        }
+        let s1 = wtxn.commit()?;
+
+        let rtxn = db.read(Some(root))?;
+        let t = rtxn.open_table("v")?;
+        assert_eq!(t.get_ref(vec![1])?.as_deref(), Some(&big[..]));
+        assert_eq!(t.get_ref(vec![2])?.as_deref(), Some(&[][..]));
+        assert_eq!(t.get_ref(vec![3])?.as_deref(), Some(&[3][..]));
+        assert!(t.get_ref(vec![3, 0])?.is_none());
+
+        let rtxn = db.read(Some(s1))?;
+        let t = rtxn.open_table("v")?;
+        for key in [vec![1], vec![2], vec![3], vec![3, 0], vec![4]] {
+            let value = t.get_ref(key.clone())?;
+            assert_eq!(value.as_deref(), t.get(key)?.as_deref());
+        }
+        assert!(t.get_ref(vec![3])?.is_none());
+
+        let t = rtxn.open_table("b")?;
+        assert_eq!(t.get_ref(vec![1])?.as_deref(), Some(&big[..]));
+        assert!(t.get_ref(vec![2])?.is_none());
+
+        Ok(())
+    }
 }
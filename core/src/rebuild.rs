@@ -0,0 +1,289 @@
+//! Rebuilding of the snapshot index.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    backend::{Backend, Range, ReadTable, ReadTxn, WriteTable, WriteTxn},
+    types::{SnapshotId, Version},
+    utils::{self, consts, scan},
+    CrepeDB, Error, Result,
+};
+
+impl<B> CrepeDB<B>
+where
+    B: Backend,
+{
+    /// Rebuild the snapshot index from the snapshot table.
+    ///
+    /// Same as [`rebuild_index_batched`](Self::rebuild_index_batched) with a
+    /// single batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend fails.
+    pub fn rebuild_index(&self) -> Result<()> {
+        self.rebuild_index_batched(usize::MAX)
+    }
+
+    /// Rebuild the snapshot index from the snapshot table in batches.
+    ///
+    /// Removes every row of the snapshot index, then writes the entries of
+    /// all snapshots again, derived from the parent pointers in the snapshot
+    /// table with the same rules as a commit. Snapshots are visited in version
+    /// order, so the entries of their ancestors exist when they are needed.
+    ///
+    /// Snapshots whose parent or grandparent is missing or has a wrong version
+    /// are skipped, [`verify`](Self::verify) reports them.
+    ///
+    /// Every write transaction removes at most `batch_size` rows or indexes at
+    /// most `batch_size` snapshots, so the index is incomplete until this
+    /// returns. Reads of versioned tables may fail or return wrong values in
+    /// the meantime and no snapshot may be committed.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_size` - The maximum number of rows or snapshots per write
+    ///   transaction, at least 1
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend fails. The index is incomplete then and
+    /// the rebuild must be run again.
+    pub fn rebuild_index_batched(&self, batch_size: usize) -> Result<()> {
+        let batch_size = batch_size.max(1);
+
+        let snapshots = self.read_snapshots()?;
+
+        self.clear_index(batch_size)?;
+
+        // Snapshots up to version 1 have no entries.
+        let mut order: Vec<_> = snapshots
+            .iter()
+            .filter(|(_, (version, _))| *version >= 2)
+            .collect();
+        order.sort_by_key(|(id, (version, _))| (*version, **id));
+
+        for batch in order.chunks(batch_size) {
+            let txn = self.backend.write_txn().map_err(Error::backend)?;
+
+            {
+                let mut index = utils::index_writer(&txn)?;
+
+                for (&id, &(version, parent)) in batch {
+                    let grandparent = snapshots
+                        .get(&parent)
+                        .filter(|(v, _)| *v + 1 == version)
+                        .and_then(|(_, p)| snapshots.get_key_value(p))
+                        .filter(|(_, (v, _))| *v + 2 == version);
+
+                    let Some((&grandparent, _)) = grandparent else {
+                        log::warn!("Skip snapshot {id} with a broken lineage");
+                        continue;
+                    };
+
+                    index.write(&SnapshotId(id), &SnapshotId(grandparent), Version(version))?;
+                }
+            }
+
+            txn.commit().map_err(Error::backend)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the version and parent of every snapshot.
+    fn read_snapshots(&self) -> Result<BTreeMap<u64, (u64, u64)>> {
+        let txn = self.backend.read_txn().map_err(Error::backend)?;
+        let table = txn
+            .open_table(consts::SNAPSHOT_TABLE)
+            .map_err(Error::backend)?;
+
+        let mut rows = Vec::new();
+
+        scan(&table, |key, value| {
+            if key != consts::SNAPSHOT_NEXT_KEY
+                && key.len() == 8
+                && value.len() >= 16
+                && (value.len() - 16).is_multiple_of(consts::SNAPSHOT_FIELD_LEN)
+            {
+                rows.push((key, value));
+            }
+        })?;
+
+        let mut snapshots = BTreeMap::new();
+
+        for (key, value) in rows {
+            let version = utils::parse_u64(&value[..8])?;
+            let parent = utils::parse_u64(&value[8..16])?;
+
+            snapshots.insert(utils::parse_u64(&key)?, (version, parent));
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Remove every row of the snapshot index, `batch_size` rows per write
+    /// transaction.
+    fn clear_index(&self, batch_size: usize) -> Result<()> {
+        loop {
+            let txn = self.backend.write_txn().map_err(Error::backend)?;
+
+            let done = {
+                let mut table = txn
+                    .open_table(consts::SNAPSHOT_INDEX_TABLE)
+                    .map_err(Error::backend)?;
+
+                let mut keys = Vec::new();
+
+                {
+                    let mut range = table.range(&[], None).map_err(Error::backend)?;
+
+                    while keys.len() < batch_size {
+                        match range.back().map_err(Error::backend)? {
+                            Some((key, _)) => keys.push(key),
+                            None => break,
+                        }
+                    }
+                }
+
+                let done = keys.len() < batch_size;
+
+                for key in keys {
+                    table.del(&key).map_err(Error::backend)?;
+                }
+
+                done
+            };
+
+            txn.commit().map_err(Error::backend)?;
+
+            if done {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[cfg(feature = "tests")]
+pub mod rebuild_tests {
+    use alloc::{vec, vec::Vec};
+
+    use crate::{
+        backend::{Backend, WriteTable, WriteTxn},
+        types::SnapshotId,
+        utils::consts,
+        CrepeDB, Error, Result,
+    };
+
+    fn index_key(snapshot: &SnapshotId, n: u32) -> Vec<u8> {
+        let mut key = snapshot.to_bytes().to_vec();
+        key.extend_from_slice(&n.to_le_bytes());
+        key
+    }
+
+    fn read_all<B: Backend>(
+        db: &CrepeDB<B>,
+        snapshots: &[SnapshotId],
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        let mut values = Vec::new();
+
+        for snapshot in snapshots {
+            let txn = db.read(Some(snapshot.clone()))?;
+            let table = txn.open_table("t")?;
+
+            for key in 0..4u8 {
+                values.push(table.get(vec![key])?);
+            }
+        }
+
+        Ok(values)
+    }
+
+    pub fn test_rebuild_index(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+
+        let txn = db.write(None)?;
+        txn.create_versioned_table("t")?;
+        let root = txn.commit()?;
+
+        // A chain of 20 snapshots, every third one forked once more.
+        let mut snapshots = vec![root];
+        let mut tip = snapshots[0].clone();
+        for i in 1..=20u8 {
+            let txn = db.write(Some(tip.clone()))?;
+            txn.open_table("t")?.set(vec![i % 4], vec![i])?;
+            tip = txn.commit()?;
+            snapshots.push(tip.clone());
+
+            if i % 3 == 0 {
+                let txn = db.write(Some(tip.clone()))?;
+                txn.open_table("t")?.del(vec![(i + 1) % 4])?;
+                snapshots.push(txn.commit()?);
+            }
+        }
+
+        let values = read_all(&db, &snapshots)?;
+        let entries = db.verify()?.index_entries;
+
+        // Rebuilding a healthy index changes nothing.
+        db.rebuild_index()?;
+        assert!(db.verify()?.is_ok());
+        assert_eq!(db.verify()?.index_entries, entries);
+
+        // Remove, change and add entries.
+        let txn = db.backend().write_txn().map_err(Error::backend)?;
+        {
+            let mut index = txn
+                .open_table(consts::SNAPSHOT_INDEX_TABLE)
+                .map_err(Error::backend)?;
+
+            for snapshot in &snapshots[10..] {
+                index.del(&index_key(snapshot, 2)).map_err(Error::backend)?;
+            }
+
+            index
+                .set(&index_key(&snapshots[12], 1), &[0; 8])
+                .map_err(Error::backend)?;
+            index
+                .set(&index_key(&snapshots[3], 9), &[0; 8])
+                .map_err(Error::backend)?;
+        }
+        txn.commit().map_err(Error::backend)?;
+
+        assert!(!db.verify()?.is_ok());
+
+        db.rebuild_index_batched(3)?;
+
+        let report = db.verify()?;
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.index_entries, entries);
+        assert_eq!(read_all(&db, &snapshots)?, values);
+
+        // Lose the whole index.
+        let txn = db.backend().write_txn().map_err(Error::backend)?;
+        {
+            let mut index = txn
+                .open_table(consts::SNAPSHOT_INDEX_TABLE)
+                .map_err(Error::backend)?;
+
+            for snapshot in &snapshots {
+                for n in 0..8 {
+                    index.del(&index_key(snapshot, n)).map_err(Error::backend)?;
+                }
+            }
+        }
+        txn.commit().map_err(Error::backend)?;
+
+        assert_eq!(db.verify()?.index_entries, 0);
+
+        db.rebuild_index_batched(1)?;
+
+        let report = db.verify()?;
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.index_entries, entries);
+        assert_eq!(read_all(&db, &snapshots)?, values);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,372 @@
+//! Savepoints of write transactions.
+
+use alloc::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    string::String,
+    vec::Vec,
+};
+use core::fmt::{self, Debug};
+
+use crate::{
+    backend::{BackendError, ReadTable, WriteTable, WriteTxn as BackendWriteTxn},
+    types::Bytes,
+    utils::consts,
+    Error, Result, WriteTxn,
+};
+
+/// A change to undo on rollback.
+struct Undo {
+    table: String,
+    key: Bytes,
+    /// The value before the change, `None` if the key was missing.
+    value: Option<Bytes>,
+}
+
+/// An open savepoint.
+struct Mark {
+    id: u64,
+    /// The number of native savepoints before this one, `None` if the changes
+    /// since this savepoint are kept in the undo log.
+    native: Option<usize>,
+    /// The length of the undo log when the savepoint was created.
+    undo: usize,
+    /// The state hash of the transaction when the savepoint was created.
+    #[cfg(feature = "state-hash")]
+    state_hash: Option<crate::types::Hash>,
+}
+
+/// The savepoints of a write transaction.
+#[derive(Default)]
+pub(crate) struct Savepoints {
+    /// Open savepoints, oldest first.
+    marks: Vec<Mark>,
+    /// The number of native savepoints set in the backend transaction,
+    /// including released ones.
+    native: usize,
+    next_id: u64,
+    undo: Vec<Undo>,
+}
+
+impl Savepoints {
+    /// Record the current value of `key` in `table` before it is changed, if
+    /// the change must be undone by CrepeDB on rollback.
+    pub(crate) fn record<T, E>(&mut self, table: &T, key: &[u8]) -> Result<()>
+    where
+        T: ReadTable<E>,
+        E: BackendError,
+    {
+        if !matches!(self.marks.last(), Some(Mark { native: None, .. })) {
+            return Ok(());
+        }
+
+        let value = table.get(key).map_err(Error::backend)?;
+
+        self.undo.push(Undo {
+            table: table.name().into(),
+            key: key.to_vec(),
+            value,
+        });
+
+        Ok(())
+    }
+}
+
+/// A savepoint of a write transaction.
+///
+/// Created by [`WriteTxn::savepoint`]. Changes made to tables of the
+/// transaction after the savepoint can be undone with
+/// [`rollback_to`](Self::rollback_to), while the changes before it are kept.
+///
+/// Savepoints can be nested. A savepoint dropped without
+/// [`release`](Self::release) stays open until the transaction ends.
+pub struct Savepoint<'a, T, E> {
+    txn: &'a WriteTxn<T, E>,
+    depth: usize,
+    id: u64,
+}
+
+impl<'a, T, E> Debug for Savepoint<'a, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Savepoint")
+            .field("depth", &self.depth)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl<'a, T, E> Savepoint<'a, T, E>
+where
+    T: BackendWriteTxn<E>,
+    E: BackendError,
+{
+    /// Undo every change made to tables of the transaction since this
+    /// savepoint.
+    ///
+    /// The savepoint stays open, savepoints created after it are released.
+    /// Tables opened from the transaction should be dropped first, some
+    /// backends cannot open a table twice.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidSavepoint`] if this savepoint was released by
+    /// a rollback to an earlier one or by releasing an earlier one, or an
+    /// error if the backend fails.
+    pub fn rollback_to(&self) -> Result<()> {
+        let mut savepoints = self.txn.savepoints.borrow_mut();
+        let savepoints = &mut *savepoints;
+
+        let mark = self.mark(savepoints)?;
+        let (native, undo) = (mark.native, mark.undo);
+
+        #[cfg(feature = "state-hash")]
+        if let (Some(hash), Some(saved)) = (&self.txn.state_hash, mark.state_hash) {
+            *hash.borrow_mut() = saved;
+        }
+
+        savepoints.marks.truncate(self.depth + 1);
+
+        if let Some(native) = native {
+            // Released savepoints are still set in the backend.
+            for _ in native..savepoints.native {
+                self.txn
+                    .txn
+                    .rollback_to_savepoint()
+                    .map_err(Error::backend)?;
+            }
+
+            self.txn.txn.set_savepoint().map_err(Error::backend)?;
+            savepoints.native = native + 1;
+
+            return Ok(());
+        }
+
+        // Tables created after the savepoint are removed as a whole.
+        let created: BTreeSet<String> = savepoints.undo[undo..]
+            .iter()
+            .filter(|change| change.table == consts::META_TABLE && change.value.is_none())
+            .filter_map(|change| String::from_utf8(change.key.clone()).ok())
+            .collect();
+
+        let mut tables = BTreeMap::new();
+
+        for change in savepoints.undo.drain(undo..).rev() {
+            if created.contains(&change.table) {
+                continue;
+            }
+
+            let table = match tables.entry(change.table) {
+                Entry::Occupied(e) => e.into_mut(),
+                Entry::Vacant(e) => {
+                    let table = self.txn.txn.open_table(e.key()).map_err(Error::backend)?;
+                    e.insert(table)
+                }
+            };
+
+            match change.value {
+                Some(value) => table.set(&change.key, &value),
+                None => table.del(&change.key),
+            }
+            .map_err(Error::backend)?;
+        }
+
+        drop(tables);
+
+        for table in &created {
+            self.txn.txn.remove_table(table).map_err(Error::backend)?;
+        }
+
+        Ok(())
+    }
+
+    /// Release this savepoint and every savepoint created after it, keeping
+    /// their changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidSavepoint`] if this savepoint was already
+    /// released.
+    pub fn release(self) -> Result<()> {
+        let mut savepoints = self.txn.savepoints.borrow_mut();
+
+        self.mark(&savepoints)?;
+
+        savepoints.marks.truncate(self.depth);
+
+        if savepoints.marks.is_empty() {
+            savepoints.undo.clear();
+        }
+
+        Ok(())
+    }
+
+    fn mark<'s>(&self, savepoints: &'s Savepoints) -> Result<&'s Mark> {
+        savepoints
+            .marks
+            .get(self.depth)
+            .filter(|mark| mark.id == self.id)
+            .ok_or(Error::InvalidSavepoint)
+    }
+}
+
+impl<T, E> WriteTxn<T, E>
+where
+    T: BackendWriteTxn<E>,
+    E: BackendError,
+{
+    /// Create a savepoint in this transaction.
+    ///
+    /// Uses the savepoints of the backend if it has them, otherwise the
+    /// previous values of changed keys are kept in an undo log until the
+    /// savepoint is released.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend fails.
+    pub fn savepoint(&self) -> Result<Savepoint<'_, T, E>> {
+        let mut savepoints = self.savepoints.borrow_mut();
+
+        let native = if self.txn.set_savepoint().map_err(Error::backend)? {
+            savepoints.native += 1;
+            Some(savepoints.native - 1)
+        } else {
+            None
+        };
+
+        let id = savepoints.next_id;
+        savepoints.next_id += 1;
+
+        let depth = savepoints.marks.len();
+        let undo = savepoints.undo.len();
+
+        savepoints.marks.push(Mark {
+            id,
+            native,
+            undo,
+            #[cfg(feature = "state-hash")]
+            state_hash: self.state_hash.as_ref().map(|hash| *hash.borrow()),
+        });
+
+        Ok(Savepoint {
+            txn: self,
+            depth,
+            id,
+        })
+    }
+}
+
+#[doc(hidden)]
+#[cfg(feature = "tests")]
+pub mod savepoint_tests {
+    use alloc::vec;
+
+    use crate::{
+        backend::{Backend, Range, ReadTable, ReadTxn},
+        CrepeDB, Error, Result,
+    };
+
+    pub fn test_savepoints(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+
+        let txn = db.write(None)?;
+        txn.create_versioned_table("v")?;
+        txn.create_basic_table("b")?;
+        txn.open_table("v")?.set(vec![1], vec![1])?;
+        txn.open_table("b")?.set(vec![1], vec![1])?;
+        let root = txn.commit()?;
+
+        let txn = db.write(Some(root.clone()))?;
+        txn.open_table("v")?.set(vec![1], vec![2])?;
+
+        let outer = txn.savepoint()?;
+        {
+            let mut v = txn.open_table("v")?;
+            v.set(vec![1], vec![3])?;
+            v.set(vec![2], vec![3])?;
+
+            let mut b = txn.open_table("b")?;
+            b.set(vec![1], vec![3])?;
+            b.del(vec![1])?;
+            b.set(vec![2], vec![3])?;
+        }
+
+        let inner = txn.savepoint()?;
+        txn.open_table("v")?.del(vec![1])?;
+        txn.create_basic_table("c")?;
+        txn.open_table("c")?.set(vec![1], vec![4])?;
+
+        inner.rollback_to()?;
+        assert!(txn.open_table("c").is_err());
+
+        // The savepoint stays open after a rollback.
+        txn.open_table("v")?.set(vec![3], vec![4])?;
+        inner.rollback_to()?;
+        inner.release()?;
+
+        let nested = txn.savepoint()?;
+        txn.open_table("v")?.set(vec![4], vec![5])?;
+        let innermost = txn.savepoint()?;
+        txn.open_table("b")?.set(vec![4], vec![5])?;
+        innermost.release()?;
+
+        // Undoes the changes of the released inner savepoint too.
+        nested.rollback_to()?;
+
+        let kept = txn.savepoint()?;
+        txn.open_table("v")?.set(vec![5], vec![6])?;
+        kept.release()?;
+
+        // Releasing the outer savepoint releases `nested` too.
+        outer.release()?;
+        assert!(matches!(nested.rollback_to(), Err(Error::InvalidSavepoint)));
+
+        let snapshot = txn.commit()?;
+
+        let txn = db.read(Some(snapshot))?;
+        let v = txn.open_table("v")?;
+        assert_eq!(v.get(vec![1])?, Some(vec![3]));
+        assert_eq!(v.get(vec![2])?, Some(vec![3]));
+        assert_eq!(v.get(vec![3])?, None);
+        assert_eq!(v.get(vec![4])?, None);
+        assert_eq!(v.get(vec![5])?, Some(vec![6]));
+
+        let b = txn.open_table("b")?;
+        assert_eq!(b.get(vec![1])?, None);
+        assert_eq!(b.get(vec![2])?, Some(vec![3]));
+        assert_eq!(b.get(vec![4])?, None);
+        assert!(txn.open_table("c").is_err());
+
+        // The rows of the rolled back table are gone from the backend too.
+        let raw = db.backend().read_txn().map_err(Error::backend)?;
+        if let Ok(c) = raw.open_table("c") {
+            let mut range = c.range(&[], None).map_err(Error::backend)?;
+            assert_eq!(range.back().map_err(Error::backend)?, None);
+        }
+
+        // A rollback to the first savepoint undoes everything after it.
+        let txn = db.write(Some(root.clone()))?;
+        let first = txn.savepoint()?;
+        txn.open_table("v")?.set(vec![1], vec![7])?;
+        let second = txn.savepoint()?;
+        txn.open_table("b")?.set(vec![1], vec![7])?;
+        second.release()?;
+        let third = txn.savepoint()?;
+        txn.open_table("b")?.set(vec![2], vec![7])?;
+
+        first.rollback_to()?;
+        assert!(matches!(third.release(), Err(Error::InvalidSavepoint)));
+
+        txn.open_table("v")?.set(vec![2], vec![8])?;
+        let snapshot = txn.commit()?;
+
+        let txn = db.read(Some(snapshot))?;
+        let v = txn.open_table("v")?;
+        assert_eq!(v.get(vec![1])?, Some(vec![1]));
+        assert_eq!(v.get(vec![2])?, Some(vec![8]));
+
+        let b = txn.open_table("b")?;
+        assert_eq!(b.get(vec![1])?, None);
+        assert_eq!(b.get(vec![2])?, Some(vec![3]));
+
+        Ok(())
+    }
+}
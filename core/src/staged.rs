@@ -0,0 +1,271 @@
+//! Writes staged in memory and committed later.
+
+use alloc::{collections::BTreeMap, string::String};
+use core::fmt::{self, Debug};
+
+use crate::{backend::Backend, types::Bytes, types::SnapshotId, CrepeDB, Result};
+
+/// Changes to a new snapshot, recorded in memory.
+///
+/// Created by [`CrepeDB::stage`] and applied by [`CrepeDB::commit_staged`].
+/// No backend transaction is held while changes are staged, so other writers
+/// are not blocked, however long it takes to build the snapshot.
+///
+/// Reads see the staged changes on top of the parent snapshot. A clone can be
+/// changed independently of the original, to execute speculatively and keep or
+/// drop the result.
+pub struct StagedWrite<'db, B> {
+    db: &'db CrepeDB<B>,
+
+    parent: SnapshotId,
+
+    lane: u16,
+
+    /// Staged changes of every table, `None` for deleted keys.
+    tables: BTreeMap<String, BTreeMap<Bytes, Option<Bytes>>>,
+}
+
+impl<'db, B> Clone for StagedWrite<'db, B> {
+    fn clone(&self) -> Self {
+        Self {
+            db: self.db,
+            parent: self.parent.clone(),
+            lane: self.lane,
+            tables: self.tables.clone(),
+        }
+    }
+}
+
+impl<'db, B> Debug for StagedWrite<'db, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StagedWrite")
+            .field("parent", &self.parent)
+            .field("lane", &self.lane)
+            .field("tables", &self.tables)
+            .finish()
+    }
+}
+
+impl<'db, B> StagedWrite<'db, B>
+where
+    B: Backend,
+{
+    /// Allocate the snapshot ID of the commit in `lane`, lane 0 by default.
+    ///
+    /// See [`CrepeDB::write_in_lane`].
+    pub fn with_lane(mut self, lane: u16) -> Self {
+        self.lane = lane;
+        self
+    }
+
+    /// Get the snapshot the changes are staged on.
+    pub fn parent(&self) -> &SnapshotId {
+        &self.parent
+    }
+
+    /// Whether no change is staged.
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Get the value associated with a key.
+    ///
+    /// Returns the staged value if the key was set or deleted, otherwise the
+    /// value at the parent snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table
+    /// * `key` - The key to look up
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not staged and the table does not exist
+    /// or the backend fails.
+    pub fn get(&self, table: &str, key: Bytes) -> Result<Option<Bytes>> {
+        if let Some(value) = self.tables.get(table).and_then(|t| t.get(&key)) {
+            return Ok(value.clone());
+        }
+
+        let txn = self.db.read(Some(self.parent.clone()))?;
+
+        let value = txn.open_table(table)?.get(key)?;
+
+        Ok(value)
+    }
+
+    /// Stage setting a key-value pair in a table.
+    ///
+    /// The table is only checked on commit.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table
+    /// * `key` - The key to set
+    /// * `value` - The value to associate with the key
+    pub fn set(&mut self, table: &str, key: Bytes, value: Bytes) {
+        self.table(table).insert(key, Some(value));
+    }
+
+    /// Stage deleting a key from a table.
+    ///
+    /// The table is only checked on commit.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table
+    /// * `key` - The key to delete
+    pub fn del(&mut self, table: &str, key: Bytes) {
+        self.table(table).insert(key, None);
+    }
+
+    fn table(&mut self, table: &str) -> &mut BTreeMap<Bytes, Option<Bytes>> {
+        self.tables.entry(String::from(table)).or_default()
+    }
+}
+
+impl<B> CrepeDB<B>
+where
+    B: Backend,
+{
+    /// Stage changes to a new child of `parent` in memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `parent` does not exist or the backend fails.
+    pub fn stage(&self, parent: SnapshotId) -> Result<StagedWrite<'_, B>> {
+        // Fail early rather than on commit.
+        self.get_snapshot_info(parent.clone())?;
+
+        Ok(StagedWrite {
+            db: self,
+            parent,
+            lane: 0,
+            tables: BTreeMap::new(),
+        })
+    }
+
+    /// Commit staged changes as a new snapshot.
+    ///
+    /// The changes are applied in a single write transaction, which is retried
+    /// on conflicts, see [`write_with_retry`](Self::write_with_retry).
+    ///
+    /// # Returns
+    ///
+    /// The snapshot ID of the newly created snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a staged table does not exist or the commit fails.
+    pub fn commit_staged(&self, stage: StagedWrite<'_, B>) -> Result<SnapshotId> {
+        self.write_with_retry(stage.parent, stage.lane, |txn| {
+            for (name, changes) in &stage.tables {
+                let mut table = txn.open_table(name)?;
+
+                for (key, value) in changes {
+                    match value {
+                        Some(value) => table.set(key.clone(), value.clone())?,
+                        None => table.del(key.clone())?,
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[doc(hidden)]
+#[cfg(feature = "tests")]
+pub mod staged_tests {
+    use alloc::vec;
+
+    use crate::{backend::Backend, CrepeDB, Error, Result};
+
+    pub fn test_staged_write(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+
+        let txn = db.write(None)?;
+        txn.create_versioned_table("v")?;
+        txn.create_basic_table("b")?;
+        txn.open_table("v")?.set(vec![1], vec![1])?;
+        txn.open_table("v")?.set(vec![2], vec![2])?;
+        txn.open_table("b")?.set(vec![1], vec![1])?;
+        let root = txn.commit()?;
+
+        let mut stage = db.stage(root.clone())?;
+        assert!(stage.is_empty());
+
+        stage.set("v", vec![1], vec![10]);
+        stage.del("v", vec![2]);
+        stage.set("v", vec![3], vec![30]);
+        stage.set("b", vec![2], vec![20]);
+
+        // Reads see staged changes on top of the parent.
+        assert_eq!(stage.get("v", vec![1])?, Some(vec![10]));
+        assert_eq!(stage.get("v", vec![2])?, None);
+        assert_eq!(stage.get("v", vec![3])?, Some(vec![30]));
+        assert_eq!(stage.get("v", vec![4])?, None);
+        assert_eq!(stage.get("b", vec![1])?, Some(vec![1]));
+        assert_eq!(stage.get("b", vec![2])?, Some(vec![20]));
+
+        // Other snapshots can be committed while changes are staged.
+        let txn = db.write(Some(root.clone()))?;
+        txn.open_table("v")?.set(vec![1], vec![5])?;
+        let other = txn.commit()?;
+
+        // A clone diverges from the original.
+        let mut speculative = stage.clone();
+        speculative.set("v", vec![1], vec![11]);
+        speculative.del("v", vec![3]);
+        assert_eq!(stage.get("v", vec![1])?, Some(vec![10]));
+        assert_eq!(speculative.get("v", vec![1])?, Some(vec![11]));
+
+        let a = db.commit_staged(stage)?;
+        let b = db.commit_staged(speculative)?;
+
+        for (snapshot, values) in [
+            (root.clone(), [Some(vec![1]), Some(vec![2]), None]),
+            (other, [Some(vec![5]), Some(vec![2]), None]),
+            (a.clone(), [Some(vec![10]), None, Some(vec![30])]),
+            (b, [Some(vec![11]), None, None]),
+        ] {
+            let txn = db.read(Some(snapshot))?;
+            let table = txn.open_table("v")?;
+
+            for (key, value) in (1..=3u8).zip(values) {
+                assert_eq!(table.get(vec![key])?, value);
+            }
+        }
+
+        let txn = db.read(Some(root.clone()))?;
+        assert_eq!(txn.open_table("b")?.get(vec![2])?, Some(vec![20]));
+
+        // Stages on new snapshots build on them.
+        let mut stage = db.stage(a)?.with_lane(1);
+        assert_eq!(stage.get("v", vec![3])?, Some(vec![30]));
+        stage.del("v", vec![3]);
+        let c = db.commit_staged(stage)?;
+        assert_eq!(c.lane(), 1);
+        assert_eq!(db.read(Some(c))?.open_table("v")?.get(vec![3])?, None);
+
+        // An empty stage commits an unchanged child.
+        let d = db.commit_staged(db.stage(root.clone())?)?;
+        assert_eq!(
+            db.read(Some(d))?.open_table("v")?.get(vec![1])?,
+            Some(vec![1])
+        );
+
+        // A missing table fails the commit only.
+        let mut stage = db.stage(root)?;
+        stage.set("missing", vec![1], vec![1]);
+        assert!(db.commit_staged(stage).is_err());
+
+        assert!(matches!(
+            db.stage(crate::types::SnapshotId(100)),
+            Err(Error::MissingSnaopshot(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+//! Order-independent hashes of the versioned tables of snapshots.
+//!
+//! The state hash of a snapshot is the sum, modulo 2^256, of the hashes of all
+//! entries of its versioned tables, where the hash of an entry covers its
+//! table, key and value. A write subtracts the hash of the previous entry of
+//! the key and adds the hash of the new one, so the state hash only depends on
+//! the entries and not on the order they were written in.
+
+use alloc::boxed::Box;
+use core::cell::RefCell;
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    types::{Bytes, Hash},
+    Result,
+};
+
+/// Get the value of a key of a versioned table at the parent snapshot.
+pub(crate) type ParentValue<'a, T> = Box<dyn Fn(&T, &[u8]) -> Result<Option<Bytes>> + 'a>;
+
+/// The running state hash of a write transaction, used by its tables.
+pub(crate) struct Tracker<'a, T> {
+    pub(crate) hash: &'a RefCell<Hash>,
+    pub(crate) parent_value: ParentValue<'a, T>,
+}
+
+impl<T> Tracker<'_, T> {
+    /// Replace the entry of `key` in `table` with `previous` value by `value`,
+    /// `None` for a missing entry.
+    pub(crate) fn update(
+        &self,
+        table: &str,
+        key: &[u8],
+        previous: Option<&[u8]>,
+        value: Option<&[u8]>,
+    ) {
+        let mut hash = self.hash.borrow_mut();
+
+        if let Some(previous) = previous {
+            sub(&mut hash, &entry_hash(table, key, previous));
+        }
+
+        if let Some(value) = value {
+            add(&mut hash, &entry_hash(table, key, value));
+        }
+    }
+}
+
+fn entry_hash(table: &str, key: &[u8], value: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+
+    hasher.update((table.len() as u32).to_be_bytes());
+    hasher.update(table);
+    hasher.update((key.len() as u32).to_be_bytes());
+    hasher.update(key);
+    hasher.update(value);
+
+    hasher.finalize().into()
+}
+
+/// Add `x` to `hash` as big-endian integers, wrapping around.
+fn add(hash: &mut Hash, x: &Hash) {
+    let mut carry = 0;
+
+    for (a, b) in hash.iter_mut().zip(x).rev() {
+        let sum = *a as u16 + *b as u16 + carry;
+
+        *a = sum as u8;
+        carry = sum >> 8;
+    }
+}
+
+/// Subtract `x` from `hash` as big-endian integers, wrapping around.
+fn sub(hash: &mut Hash, x: &Hash) {
+    let mut borrow = 0;
+
+    for (a, b) in hash.iter_mut().zip(x).rev() {
+        let diff = *a as i16 - *b as i16 - borrow;
+
+        *a = diff as u8;
+        borrow = (diff < 0) as i16;
+    }
+}
+
+#[doc(hidden)]
+#[cfg(feature = "tests")]
+pub mod state_hash_tests {
+    use super::*;
+    use crate::{backend::Backend, types::SnapshotId, CrepeDB};
+
+    /// Compute the state hash of `snapshot` from its visible entries.
+    fn expected_hash<B: Backend>(
+        db: &CrepeDB<B>,
+        snapshot: &SnapshotId,
+        tables: &[&str],
+    ) -> Result<Option<Hash>> {
+        let txn = db.read(Some(snapshot.clone()))?;
+
+        let mut hash = Hash::default();
+
+        for name in tables {
+            for (key, value) in txn.open_table(name)?.range([], [0xff; 4])? {
+                add(&mut hash, &entry_hash(name, &key, &value));
+            }
+        }
+
+        Ok(Some(hash))
+    }
+
+    /// Test state hashes against hashes computed from scratch
+    pub fn test_state_hash(backend: impl Backend) -> Result<()> {
+        let mut x = [0xff; 32];
+        add(&mut x, &[1; 32]);
+        sub(&mut x, &[1; 32]);
+        assert_eq!(x, [0xff; 32]);
+
+        let db = CrepeDB::new(backend).with_state_hash(true);
+        let tables = ["a", "b"];
+
+        let txn = db.write(None)?;
+        txn.create_versioned_table("a")?;
+        txn.create_versioned_table("b")?;
+        txn.create_basic_table("c")?;
+        txn.open_table("a")?.set([0], [0])?;
+        txn.open_table("b")?.del([0])?;
+        let root = txn.commit()?;
+
+        assert_eq!(
+            db.state_hash(root.clone())?,
+            expected_hash(&db, &root, &tables)?
+        );
+
+        // Writes to two tables at once, overwrites and deletes in the same
+        // transaction and deletes of missing keys.
+        let txn = db.write(Some(root.clone()))?;
+        {
+            let mut a = txn.open_table("a")?;
+            let mut b = txn.open_table("b")?;
+
+            for i in 0..50u8 {
+                a.set([i], [i])?;
+                b.set([i], [i, i])?;
+            }
+
+            a.set([1], [1, 1])?;
+            a.del([2])?;
+            a.del([2])?;
+            a.del([200])?;
+            b.del([3])?;
+            b.set([3], [3])?;
+
+            txn.open_table("c")?.set([1], [1])?;
+        }
+        let s1 = txn.commit()?;
+
+        assert_eq!(
+            db.state_hash(s1.clone())?,
+            expected_hash(&db, &s1, &tables)?
+        );
+
+        // Rolled back writes do not change the hash.
+        let txn = db.write(Some(s1.clone()))?;
+        {
+            txn.open_table("a")?.set([10], [0])?;
+
+            let savepoint = txn.savepoint()?;
+            {
+                let mut a = txn.open_table("a")?;
+                a.set([11], [0])?;
+                a.del([12])?;
+            }
+            savepoint.rollback_to()?;
+
+            txn.open_table("a")?.del([13])?;
+        }
+        let s2 = txn.commit()?;
+
+        assert_eq!(
+            db.state_hash(s2.clone())?,
+            expected_hash(&db, &s2, &tables)?
+        );
+
+        // The same entries written in a different order on another fork give
+        // the same hash.
+        let txn = db.write(Some(root))?;
+        {
+            let mut b = txn.open_table("b")?;
+            for i in (0..50u8).rev() {
+                b.set([i], [i, i])?;
+            }
+            b.set([3], [3])?;
+
+            let mut a = txn.open_table("a")?;
+            a.set([1], [0])?;
+            for i in (0..50u8).rev() {
+                if i != 2 {
+                    a.set([i], [i])?;
+                }
+            }
+            a.set([1], [1, 1])?;
+        }
+        let fork = txn.commit()?;
+
+        assert_eq!(db.state_hash(fork.clone())?, db.state_hash(s1.clone())?);
+        assert_ne!(db.state_hash(fork.clone())?, db.state_hash(s2.clone())?);
+
+        // Hashes are kept by databases opened without the option.
+        let db = CrepeDB::new(db.into_backend());
+
+        let txn = db.write(Some(fork))?;
+        txn.open_table("b")?.del([0])?;
+        let s3 = txn.commit()?;
+        assert_eq!(
+            db.state_hash(s3.clone())?,
+            expected_hash(&db, &s3, &tables)?
+        );
+
+        let report = db.verify()?;
+        assert!(report.is_ok(), "{:?}", report.issues);
+
+        // Databases created without the option have no state hashes.
+        let db = CrepeDB::new(crate::memory::MemoryBackend::new());
+        let root = db.write(None)?.commit()?;
+        assert_eq!(db.state_hash(root)?, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+//! Tables with typed keys and values.
+
+use alloc::vec::Vec;
+use core::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+};
+
+use crate::{
+    backend::{
+        BackendError, ReadTable as BackendReadTable, ReadTxn as BackendReadTxn,
+        WriteTable as BackendWriteTable, WriteTxn as BackendWriteTxn,
+    },
+    codec::{Codec, TableCodec},
+    types::TableType,
+    Error, ReadTable, ReadTxn, Result, WriteTable, WriteTxn,
+};
+
+/// Marks the key, value and codec types without owning them.
+type Marker<K, V, C> = PhantomData<fn() -> (K, V, C)>;
+
+/// The name, type, key, value and codec of a table, declared once.
+///
+/// Declare a table as a constant and use it to create and open the table, so
+/// using it with other key or value types is a compile error.
+///
+/// ```ignore
+/// const BALANCES: TableDef<[u8; 20], u128, (Fixed, BigEndian)> = TableDef::versioned("balances");
+///
+/// let wtxn = db.write(None)?;
+/// wtxn.create_typed_table(&BALANCES)?;
+/// wtxn.open_typed_table(&BALANCES)?.set(&address, &100)?;
+/// ```
+pub struct TableDef<K, V, C> {
+    name: &'static str,
+
+    table_type: TableType,
+
+    marker: Marker<K, V, C>,
+}
+
+impl<K, V, C> TableDef<K, V, C> {
+    /// Declare a basic (non-versioned) table.
+    pub const fn basic(name: &'static str) -> Self {
+        Self {
+            name,
+            table_type: TableType::Basic,
+            marker: PhantomData,
+        }
+    }
+
+    /// Declare a versioned table.
+    pub const fn versioned(name: &'static str) -> Self {
+        Self {
+            name,
+            table_type: TableType::Versioned,
+            marker: PhantomData,
+        }
+    }
+
+    /// The name of the table.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Check that a stored table has the type of this definition.
+    fn check(&self, table_type: &TableType) -> Result<()> {
+        if *table_type != self.table_type {
+            return Err(Error::WrongTableType(table_type.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+impl<K, V, C> Clone for TableDef<K, V, C> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name,
+            table_type: self.table_type.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V, C> Debug for TableDef<K, V, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TableDef")
+            .field("name", &self.name)
+            .field("table_type", &self.table_type)
+            .finish()
+    }
+}
+
+/// A [`ReadTable`] or [`WriteTable`] with keys and values encoded by the
+/// codec `C`.
+///
+/// Opened by [`ReadTxn::open_typed_table`] and
+/// [`WriteTxn::open_typed_table`]. The untyped table is still available with
+/// [`TypedTable::inner`].
+pub struct TypedTable<T, K, V, C> {
+    table: T,
+
+    marker: Marker<K, V, C>,
+}
+
+/// A typed [`ReadTable`].
+pub type TypedReadTable<T, E, K, V, C> = TypedTable<ReadTable<T, E>, K, V, C>;
+
+/// A typed [`WriteTable`].
+pub type TypedWriteTable<'a, T, E, K, V, C> = TypedTable<WriteTable<'a, T, E>, K, V, C>;
+
+impl<T, K, V, C> TypedTable<T, K, V, C> {
+    fn new(table: T) -> Self {
+        Self {
+            table,
+            marker: PhantomData,
+        }
+    }
+
+    /// Get the untyped table.
+    pub fn inner(&mut self) -> &mut T {
+        &mut self.table
+    }
+
+    /// Unwrap the untyped table.
+    pub fn into_inner(self) -> T {
+        self.table
+    }
+}
+
+impl<T, E, K, V, C> TypedReadTable<T, E, K, V, C>
+where
+    T: BackendReadTable<E>,
+    E: BackendError,
+    C: TableCodec<K, V>,
+{
+    /// Get the value associated with a key.
+    ///
+    /// See [`ReadTable::get`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the read fails or the stored value can not be
+    /// decoded.
+    pub fn get(&self, key: &K) -> Result<Option<V>> {
+        let key = C::Key::encode(key)?;
+
+        match self.table.get_ref(key)? {
+            Some(value) => Ok(Some(C::Value::decode(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get all key-value pairs with keys in `begin..end`.
+    ///
+    /// See [`ReadTable::range`]. Pairs are ordered by their encoded keys, which
+    /// is the order of the keys for order preserving codecs, such as
+    /// [`BigEndian`](crate::BigEndian).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the read fails or a stored pair can not be decoded.
+    pub fn range(&self, begin: &K, end: &K) -> Result<Vec<(K, V)>> {
+        let begin = C::Key::encode(begin)?;
+        let end = C::Key::encode(end)?;
+
+        self.table
+            .range(begin, end)?
+            .into_iter()
+            .map(|(key, value)| Ok((C::Key::decode(&key)?, C::Value::decode(&value)?)))
+            .collect()
+    }
+}
+
+impl<'a, T, E, K, V, C> TypedWriteTable<'a, T, E, K, V, C>
+where
+    T: BackendWriteTable<E>,
+    E: BackendError,
+    C: TableCodec<K, V>,
+{
+    /// Set a key-value pair.
+    ///
+    /// See [`WriteTable::set`].
+    pub fn set(&mut self, key: &K, value: &V) -> Result<()> {
+        let key = C::Key::encode(key)?;
+        let value = C::Value::encode(value)?;
+
+        self.table.set(key, value)
+    }
+
+    /// Delete a key.
+    ///
+    /// See [`WriteTable::del`].
+    pub fn del(&mut self, key: &K) -> Result<()> {
+        let key = C::Key::encode(key)?;
+
+        self.table.del(key)
+    }
+}
+
+impl<T, E> ReadTxn<T, E>
+where
+    T: BackendReadTxn<E>,
+    E: BackendError,
+{
+    /// Open a typed table for reading.
+    ///
+    /// See [`ReadTxn::open_table`]. Returns [`Error::WrongTableType`] if the
+    /// table was created with another type than `def`.
+    pub fn open_typed_table<K, V, C>(
+        &self,
+        def: &TableDef<K, V, C>,
+    ) -> Result<TypedReadTable<T::Table<'_>, E, K, V, C>> {
+        let table = self.open_table(def.name)?;
+        def.check(&table.table_type)?;
+
+        Ok(TypedTable::new(table))
+    }
+}
+
+impl<T, E> WriteTxn<T, E>
+where
+    T: BackendWriteTxn<E>,
+    E: BackendError,
+{
+    /// Create a typed table with the type of its definition.
+    ///
+    /// See [`WriteTxn::create_basic_table`] and
+    /// [`WriteTxn::create_versioned_table`].
+    pub fn create_typed_table<K, V, C>(&self, def: &TableDef<K, V, C>) -> Result<()> {
+        match def.table_type {
+            TableType::Basic => self.create_basic_table(def.name),
+            TableType::Versioned => self.create_versioned_table(def.name),
+        }
+    }
+
+    /// Open a typed table for writing.
+    ///
+    /// See [`WriteTxn::open_table`]. Returns [`Error::WrongTableType`] if the
+    /// table was created with another type than `def`.
+    pub fn open_typed_table<K, V, C>(
+        &self,
+        def: &TableDef<K, V, C>,
+    ) -> Result<TypedWriteTable<'_, T::Table<'_>, E, K, V, C>> {
+        let table = self.open_table(def.name)?;
+        def.check(&table.table_type)?;
+
+        Ok(TypedTable::new(table))
+    }
+}
+
+#[doc(hidden)]
+#[cfg(feature = "tests")]
+pub mod typed_tests {
+    use alloc::{format, string::String, vec, vec::Vec};
+
+    use crate::{
+        backend::Backend, types::TableType, BigEndian, CrepeDB, Error, Fixed, Raw, Result, TableDef,
+    };
+
+    const ACCOUNTS: TableDef<[u8; 4], i64, (Fixed, BigEndian)> = TableDef::versioned("accounts");
+    const HEIGHTS: TableDef<u64, String, (BigEndian, Raw)> = TableDef::basic("heights");
+
+    /// Test typed tables with ordered key codecs
+    pub fn test_typed_tables(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+
+        let wtxn = db.write(None)?;
+        wtxn.create_typed_table(&ACCOUNTS)?;
+        wtxn.create_typed_table(&HEIGHTS)?;
+        {
+            let mut t = wtxn.open_typed_table(&ACCOUNTS)?;
+            t.set(&[0, 0, 0, 1], &-5)?;
+            t.set(&[0, 0, 0, 2], &i64::MAX)?;
+            t.set(&[0, 0, 0, 3], &0)?;
+        }
+        {
+            let mut t = wtxn.open_typed_table(&HEIGHTS)?;
+            for h in [256u64, 1, 2, 65536] {
+                t.set(&h, &format!("block {h}"))?;
+            }
+        }
+        let root = wtxn.commit()?;
+
+        let wtxn = db.write(Some(root.clone()))?;
+        {
+            let mut t = wtxn.open_typed_table(&ACCOUNTS)?;
+            t.set(&[0, 0, 0, 1], &-6)?;
+            t.del(&[0, 0, 0, 3])?;
+
+            // The untyped table accepts any bytes.
+            t.inner().set(vec![0, 0, 0, 4], vec![1])?;
+        }
+        let s1 = wtxn.commit()?;
+
+        let rtxn = db.read(Some(root))?;
+        let t = rtxn.open_typed_table(&ACCOUNTS)?;
+        assert_eq!(t.get(&[0, 0, 0, 1])?, Some(-5));
+        assert_eq!(t.get(&[0, 0, 0, 3])?, Some(0));
+        assert_eq!(t.get(&[0, 0, 0, 4])?, None);
+
+        let rtxn = db.read(Some(s1.clone()))?;
+        let t = rtxn.open_typed_table(&ACCOUNTS)?;
+        assert_eq!(t.get(&[0, 0, 0, 1])?, Some(-6));
+        assert_eq!(t.get(&[0, 0, 0, 3])?, None);
+        assert_eq!(
+            t.range(&[0; 4], &[0, 0, 0, 3])?,
+            vec![([0, 0, 0, 1], -6), ([0, 0, 0, 2], i64::MAX)]
+        );
+
+        // Values which are not encoded by the codec are errors.
+        assert!(matches!(
+            t.get(&[0, 0, 0, 4]),
+            Err(Error::WrongBytesLength(1))
+        ));
+
+        // Integer keys are ordered by value.
+        let t = rtxn.open_typed_table(&HEIGHTS)?;
+        let heights: Vec<_> = t
+            .range(&0, &u64::MAX)?
+            .into_iter()
+            .map(|(h, _)| h)
+            .collect();
+        assert_eq!(heights, vec![1, 2, 256, 65536]);
+        assert_eq!(t.get(&256)?, Some(String::from("block 256")));
+        assert_eq!(t.get(&3)?, None);
+
+        // A definition of another table type is rejected.
+        const WRONG: TableDef<u64, String, (BigEndian, Raw)> = TableDef::versioned("heights");
+        assert!(matches!(
+            rtxn.open_typed_table(&WRONG),
+            Err(Error::WrongTableType(TableType::Basic))
+        ));
+
+        let wtxn = db.write(Some(s1))?;
+        assert!(matches!(
+            wtxn.open_typed_table(&WRONG),
+            Err(Error::WrongTableType(TableType::Basic))
+        ));
+
+        Ok(())
+    }
+}
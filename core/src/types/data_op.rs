@@ -24,16 +24,23 @@ impl From<DataOp> for Option<Bytes> {
 }
 
 impl DataOp {
+    /// Flag of a set operation.
+    pub const SET_FLAG: u8 = 0x00;
+
+    /// Flag of a delete operation.
+    pub const DEL_FLAG: u8 = 0x01;
+
     /// Serialize the data operation to bytes.
     ///
     /// The operation type is encoded as a flag byte appended to the end.
+    #[allow(clippy::wrong_self_convention)]
     pub fn to_bytes(self) -> Vec<u8> {
         match self {
             Self::Set(mut v) => {
-                v.push(0x00);
+                v.push(Self::SET_FLAG);
                 v
             }
-            Self::Del => vec![0x01],
+            Self::Del => vec![Self::DEL_FLAG],
         }
     }
 
@@ -50,9 +57,25 @@ impl DataOp {
         let flag = bytes.pop().ok_or(Error::MissingDataOpFlag)?;
 
         match flag {
-            0x00 => Ok(DataOp::Set(bytes)),
-            0x01 => Ok(DataOp::Del),
+            Self::SET_FLAG => Ok(DataOp::Set(bytes)),
+            Self::DEL_FLAG => Ok(DataOp::Del),
             _ => Err(Error::UnexpectedDataOpType(flag)),
         }
     }
+
+    /// Get the length of the value of serialized data operation bytes,
+    /// without copying them. Returns `None` for a deletion.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the flag is missing or unknown.
+    pub fn value_len(bytes: &[u8]) -> Result<Option<usize>> {
+        let len = bytes.len().checked_sub(1).ok_or(Error::MissingDataOpFlag)?;
+
+        match bytes[len] {
+            Self::SET_FLAG => Ok(Some(len)),
+            Self::DEL_FLAG => Ok(None),
+            flag => Err(Error::UnexpectedDataOpType(flag)),
+        }
+    }
 }
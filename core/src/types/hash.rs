@@ -0,0 +1,4 @@
+//! Hash type definitions.
+
+/// A 32-byte hash, such as the state root of a snapshot.
+pub type Hash = [u8; 32];
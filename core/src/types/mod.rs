@@ -12,3 +12,6 @@ pub(crate) use data_op::*;
 
 mod bytes;
 pub use bytes::*;
+
+mod hash;
+pub use hash::*;
@@ -1,4 +1,7 @@
-use crate::{utils, Result};
+use crate::{
+    utils::{self, consts},
+    Result,
+};
 
 /// Unique identifier for a database snapshot.
 ///
@@ -42,4 +45,14 @@ impl SnapshotId {
     pub const fn root() -> Self {
         Self(0)
     }
+
+    /// Get the first snapshot ID allocated in `lane`.
+    pub(crate) const fn lane_start(lane: u16) -> Self {
+        Self((lane as u64) << consts::SNAPSHOT_LANE_SHIFT)
+    }
+
+    /// Get the lane this snapshot ID was allocated in.
+    pub const fn lane(&self) -> u16 {
+        (self.0 >> consts::SNAPSHOT_LANE_SHIFT) as u16
+    }
 }
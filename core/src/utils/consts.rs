@@ -1,3 +1,5 @@
+//! Names and keys of the internal tables used by CrepeDB.
+
 use seq_macro::seq;
 
 /// Name of meta table
@@ -7,12 +9,45 @@ pub const META_TABLE: &str = "__crepe_meta";
 
 /// Name of snapshot table
 ///
-/// snapshot_id(u64) => version,parent
+/// snapshot_id(u64) => version,parent,fields
 pub const SNAPSHOT_TABLE: &str = "__crepe_snapshot";
 
+/// Length of an optional field of a snapshot row, a tag byte followed by a
+/// 32-byte hash.
+pub const SNAPSHOT_FIELD_LEN: usize = 33;
+
+/// Tag of the state root field of a snapshot row.
+pub const SNAPSHOT_STATE_ROOT_FIELD: u8 = 1;
+
+/// Tag of the state hash field of a snapshot row.
+pub const SNAPSHOT_STATE_HASH_FIELD: u8 = 2;
+
 /// Name of index of snapshot
 ///
 /// snapshot_id(u64),k(u64) => snapshot_id(u64)
 pub const SNAPSHOT_INDEX_TABLE: &str = "__crepe_snapshot_index";
 
+/// Name of the table of Merkle tree nodes
+///
+/// tree,depth(u16),path => node, versioned
+pub const MERKLE_TABLE: &str = "__crepe_merkle";
+
+/// All internal tables, which are not listed in the meta table.
+pub const INTERNAL_TABLES: &[&str] = &[
+    META_TABLE,
+    SNAPSHOT_TABLE,
+    SNAPSHOT_INDEX_TABLE,
+    MERKLE_TABLE,
+];
+
+/// Key in the snapshot table which stores the next snapshot id.
+///
+/// This is the counter of lane 0. The counter of another lane is stored under
+/// this key followed by the lane as big-endian `u16`.
 pub const SNAPSHOT_NEXT_KEY: &[u8; 8] = &seq!(N in 0..8 { [ #(0xff,)* ] });
+
+/// Number of low bits of a snapshot ID counting snapshots within its lane.
+///
+/// The bits above hold the lane, so every lane allocates IDs from its own range
+/// and its own counter.
+pub const SNAPSHOT_LANE_SHIFT: u32 = 48;
@@ -1,7 +1,5 @@
 use core::marker::PhantomData;
 
-use alloc::vec::Vec;
-
 use crate::{
     backend::{BackendError, ReadTable, ReadTxn, WriteTable, WriteTxn},
     types::{SnapshotId, Version},
@@ -50,12 +48,9 @@ where
     E: BackendError,
 {
     pub fn read(&self, snapshot: &SnapshotId, n: u32) -> Result<Option<SnapshotId>> {
-        let mut key = Vec::with_capacity(12);
-
-        key.extend_from_slice(&snapshot.to_bytes());
-        key.extend_from_slice(&n.to_le_bytes());
+        let key = index_key(snapshot, n);
 
-        let bytes = self.table.get(key).map_err(Error::backend)?;
+        let bytes = self.table.get(&key).map_err(Error::backend)?;
 
         if let Some(bytes) = bytes {
             let s = SnapshotId::from_bytes(&bytes)?;
@@ -72,13 +67,10 @@ where
     E: BackendError,
 {
     fn write_index(&mut self, snapshot: &SnapshotId, n: u32, to: &SnapshotId) -> Result<()> {
-        let mut key = Vec::with_capacity(12);
-
-        key.extend_from_slice(&snapshot.to_bytes());
-        key.extend_from_slice(&n.to_le_bytes());
+        let key = index_key(snapshot, n);
 
         self.table
-            .set(key, to.to_bytes().to_vec())
+            .set(&key, &to.to_bytes())
             .map_err(Error::backend)?;
 
         Ok(())
@@ -129,3 +121,13 @@ where
         Ok(())
     }
 }
+
+/// Build the key of the `n`th skip of `snapshot`.
+fn index_key(snapshot: &SnapshotId, n: u32) -> [u8; 12] {
+    let mut key = [0; 12];
+
+    key[..8].copy_from_slice(&snapshot.to_bytes());
+    key[8..].copy_from_slice(&n.to_le_bytes());
+
+    key
+}
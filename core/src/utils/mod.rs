@@ -14,6 +14,9 @@ pub mod consts;
 mod parse;
 pub use parse::*;
 
+mod scan;
+pub use scan::*;
+
 pub fn fast_ceil_log2(n: u64) -> u32 {
     u64::BITS - n.leading_zeros()
 }
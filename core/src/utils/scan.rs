@@ -0,0 +1,20 @@
+use crate::{
+    backend::{BackendError, Range, ReadTable},
+    types::Bytes,
+    Error, Result,
+};
+
+/// Call `f` with every row of `table`.
+pub fn scan<T, E>(table: &T, mut f: impl FnMut(Bytes, Bytes)) -> Result<()>
+where
+    T: ReadTable<E>,
+    E: BackendError,
+{
+    let mut range = table.range(&[], None).map_err(Error::backend)?;
+
+    while let Some((key, value)) = range.back().map_err(Error::backend)? {
+        f(key, value);
+    }
+
+    Ok(())
+}
@@ -4,8 +4,8 @@ use alloc::vec::Vec;
 
 use crate::{
     backend::{BackendError, ReadTable, ReadTxn, WriteTable, WriteTxn},
-    types::{SnapshotId, Version},
-    Error, Result,
+    types::{Hash, SnapshotId, Version},
+    Error, Result, SnapshotInfo,
 };
 
 use super::consts;
@@ -15,6 +15,49 @@ pub struct SnapshotTable<T, E> {
     marker: PhantomData<E>,
 }
 
+/// Key of the next snapshot ID counter of `lane`.
+pub fn next_key(lane: u16) -> Vec<u8> {
+    let mut key = consts::SNAPSHOT_NEXT_KEY.to_vec();
+
+    if lane != 0 {
+        key.extend_from_slice(&lane.to_be_bytes());
+    }
+
+    key
+}
+
+/// Parse a snapshot row, skipping fields with unknown tags.
+pub fn parse_row(bytes: &[u8]) -> Result<SnapshotInfo> {
+    let version = Version::from_bytes(bytes)?;
+    let parent_snapshot_id = SnapshotId::from_bytes(&bytes[8..])?;
+
+    let fields = &bytes[16..];
+
+    if !fields.len().is_multiple_of(consts::SNAPSHOT_FIELD_LEN) {
+        return Err(Error::WrongBytesLength(bytes.len()));
+    }
+
+    let mut state_root = None;
+    let mut state_hash = None;
+
+    for field in fields.chunks(consts::SNAPSHOT_FIELD_LEN) {
+        let hash: Hash = field[1..].try_into().expect("checked length");
+
+        match field[0] {
+            consts::SNAPSHOT_STATE_ROOT_FIELD => state_root = Some(hash),
+            consts::SNAPSHOT_STATE_HASH_FIELD => state_hash = Some(hash),
+            _ => {}
+        }
+    }
+
+    Ok(SnapshotInfo {
+        version,
+        parent_snapshot_id,
+        state_root,
+        state_hash,
+    })
+}
+
 pub fn snapshot_reader<T, E>(txn: &T) -> Result<SnapshotTable<T::Table<'_>, E>>
 where
     T: ReadTxn<E>,
@@ -51,7 +94,7 @@ where
     pub fn read(&self, snapshot_id: &SnapshotId) -> Result<(Version, SnapshotId)> {
         let bytes = self
             .table
-            .get(snapshot_id.to_bytes().to_vec())
+            .get(&snapshot_id.to_bytes())
             .map_err(Error::backend)?
             .ok_or(Error::MissingSnaopshot(snapshot_id.clone()))?;
 
@@ -61,25 +104,38 @@ where
         Ok((r, s))
     }
 
-    pub fn has(&self, snapshot_id: &SnapshotId) -> Result<bool> {
+    pub fn read_info(&self, snapshot_id: &SnapshotId) -> Result<SnapshotInfo> {
         let bytes = self
             .table
-            .get(snapshot_id.to_bytes().to_vec())
-            .map_err(Error::backend)?;
-        Ok(bytes.is_some())
+            .get(&snapshot_id.to_bytes())
+            .map_err(Error::backend)?
+            .ok_or(Error::MissingSnaopshot(snapshot_id.clone()))?;
+
+        parse_row(&bytes)
     }
 
-    pub fn read_next_snapshot_id(&self) -> Result<SnapshotId> {
+    pub fn has(&self, snapshot_id: &SnapshotId) -> Result<bool> {
         let bytes = self
             .table
-            .get(consts::SNAPSHOT_NEXT_KEY.to_vec())
+            .get(&snapshot_id.to_bytes())
             .map_err(Error::backend)?;
+        Ok(bytes.is_some())
+    }
+
+    pub fn read_next_snapshot_id(&self, lane: u16) -> Result<SnapshotId> {
+        let bytes = self.table.get(&next_key(lane)).map_err(Error::backend)?;
 
-        if let Some(bytes) = bytes {
-            Ok(SnapshotId::from_bytes(&bytes)?)
-        } else {
-            Ok(SnapshotId::root())
+        let snapshot_id = match bytes {
+            Some(bytes) => SnapshotId::from_bytes(&bytes)?,
+            None => SnapshotId::lane_start(lane),
+        };
+
+        // The last ID of the last lane is the preroot.
+        if snapshot_id.lane() != lane || snapshot_id == SnapshotId::preroot() {
+            return Err(Error::LaneExhausted(lane));
         }
+
+        Ok(snapshot_id)
     }
 }
 
@@ -88,19 +144,24 @@ where
     T: WriteTable<E>,
     E: BackendError,
 {
-    pub fn write(
-        &mut self,
-        snapshot_id: &SnapshotId,
-        parent: &SnapshotId,
-        version: &Version,
-    ) -> Result<()> {
-        let mut value = Vec::with_capacity(16);
+    pub fn write(&mut self, snapshot_id: &SnapshotId, info: &SnapshotInfo) -> Result<()> {
+        let mut value = Vec::with_capacity(16 + 2 * consts::SNAPSHOT_FIELD_LEN);
 
-        value.extend_from_slice(&version.to_bytes());
-        value.extend_from_slice(&parent.to_bytes());
+        value.extend_from_slice(&info.version.to_bytes());
+        value.extend_from_slice(&info.parent_snapshot_id.to_bytes());
+
+        if let Some(state_root) = &info.state_root {
+            value.push(consts::SNAPSHOT_STATE_ROOT_FIELD);
+            value.extend_from_slice(state_root);
+        }
+
+        if let Some(state_hash) = &info.state_hash {
+            value.push(consts::SNAPSHOT_STATE_HASH_FIELD);
+            value.extend_from_slice(state_hash);
+        }
 
         self.table
-            .set(snapshot_id.to_bytes().to_vec(), value)
+            .set(&snapshot_id.to_bytes(), &value)
             .map_err(Error::backend)?;
 
         Ok(())
@@ -110,10 +171,7 @@ where
         let snapshot = SnapshotId(snapshot_id.0 + 1);
 
         self.table
-            .set(
-                consts::SNAPSHOT_NEXT_KEY.to_vec(),
-                snapshot.to_bytes().to_vec(),
-            )
+            .set(&next_key(snapshot_id.lane()), &snapshot.to_bytes())
             .map_err(Error::backend)?;
 
         Ok(())
@@ -1,7 +1,5 @@
 use core::marker::PhantomData;
 
-use alloc::vec;
-
 use crate::{
     backend::{BackendError, ReadTable, ReadTxn, WriteTable, WriteTxn},
     types::TableType,
@@ -11,7 +9,7 @@ use crate::{
 use super::consts;
 
 pub struct MetaTable<T, E> {
-    table: T,
+    pub(crate) table: T,
     marker: PhantomData<E>,
 }
 
@@ -59,7 +57,7 @@ where
     pub fn read_type(&self, table: &str) -> Result<TableType> {
         let bytes = self
             .table
-            .get(table.into())
+            .get(table.as_bytes())
             .map_err(Error::backend)?
             .ok_or(Error::MissingTable)?;
 
@@ -78,7 +76,7 @@ where
 {
     pub fn write_type(&mut self, table: &str, ty: &TableType) -> Result<()> {
         self.table
-            .set(table.into(), vec![ty.to_byte()])
+            .set(table.as_bytes(), &[ty.to_byte()])
             .map_err(Error::backend)?;
 
         Ok(())
@@ -0,0 +1,709 @@
+//! Integrity checks of the internal tables.
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec::Vec,
+};
+use core::fmt::{self, Display};
+
+use crate::{
+    backend::{Backend, BackendError, ReadTxn},
+    types::{Bytes, DataOp, SnapshotId, TableType, Version},
+    utils::{consts, scan},
+    CrepeDB, Error, Result,
+};
+
+/// An inconsistency found by [`CrepeDB::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// A row of the snapshot table has a malformed key or value.
+    MalformedSnapshot {
+        /// The key of the row.
+        key: Bytes,
+    },
+
+    /// The parent of a snapshot does not exist.
+    MissingParent {
+        /// The snapshot.
+        snapshot: SnapshotId,
+        /// The parent stored for the snapshot.
+        parent: SnapshotId,
+    },
+
+    /// The version of a snapshot is not one above the version of its parent,
+    /// or not the root version for the root.
+    WrongVersion {
+        /// The snapshot.
+        snapshot: SnapshotId,
+        /// The version stored for the snapshot.
+        version: Version,
+        /// The version derived from the parent.
+        expected: Version,
+    },
+
+    /// The next snapshot ID of a lane is missing or does not exceed every
+    /// snapshot ID of the lane.
+    NextSnapshotId {
+        /// The lane.
+        lane: u16,
+        /// The stored next snapshot ID.
+        next: Option<SnapshotId>,
+        /// The largest snapshot ID of the lane.
+        max: SnapshotId,
+    },
+
+    /// A row of the snapshot index has a malformed key or value.
+    MalformedIndex {
+        /// The key of the row.
+        key: Bytes,
+    },
+
+    /// The index entry `i(snapshot, n)` is missing, points to the wrong
+    /// ancestor or should not exist.
+    WrongIndex {
+        /// The snapshot.
+        snapshot: SnapshotId,
+        /// The level of the entry, which skips `2^n` versions.
+        n: u32,
+        /// The ancestor derived from the snapshot table.
+        expected: Option<SnapshotId>,
+        /// The ancestor stored in the index.
+        found: Option<SnapshotId>,
+    },
+
+    /// A table listed in the meta table has an invalid type.
+    InvalidTableType {
+        /// The name of the table.
+        table: String,
+        /// The stored type byte, `None` if the value is empty.
+        byte: Option<u8>,
+    },
+
+    /// A row of a versioned table has a key too short to hold a version and
+    /// a snapshot ID.
+    MalformedRow {
+        /// The name of the table.
+        table: String,
+        /// The key of the row.
+        key: Bytes,
+    },
+
+    /// A row of a versioned table does not hold a valid data operation.
+    InvalidDataOp {
+        /// The name of the table.
+        table: String,
+        /// The key of the row.
+        key: Bytes,
+    },
+
+    /// A row of a versioned table references a missing snapshot.
+    MissingRowSnapshot {
+        /// The name of the table.
+        table: String,
+        /// The key of the row.
+        key: Bytes,
+        /// The snapshot in the key.
+        snapshot: SnapshotId,
+    },
+
+    /// A row of a versioned table has another version than its snapshot.
+    WrongRowVersion {
+        /// The name of the table.
+        table: String,
+        /// The key of the row.
+        key: Bytes,
+        /// The version in the key.
+        version: Version,
+        /// The version of the snapshot.
+        expected: Version,
+    },
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedSnapshot { key } => {
+                write!(f, "malformed snapshot row 0x{}", hex::encode(key))
+            }
+            Self::MissingParent { snapshot, parent } => {
+                write!(f, "snapshot {} has missing parent {}", snapshot.0, parent.0)
+            }
+            Self::WrongVersion {
+                snapshot,
+                version,
+                expected,
+            } => write!(
+                f,
+                "snapshot {} has version {version}, expected {expected}",
+                snapshot.0
+            ),
+            Self::NextSnapshotId {
+                lane,
+                next: Some(next),
+                max,
+            } => write!(
+                f,
+                "next snapshot id {} of lane {lane} does not exceed snapshot {}",
+                next.0, max.0
+            ),
+            Self::NextSnapshotId {
+                lane,
+                next: None,
+                max,
+            } => write!(
+                f,
+                "next snapshot id of lane {lane} is missing, max snapshot {}",
+                max.0
+            ),
+            Self::MalformedIndex { key } => {
+                write!(f, "malformed index row 0x{}", hex::encode(key))
+            }
+            Self::WrongIndex {
+                snapshot,
+                n,
+                expected,
+                found,
+            } => write!(
+                f,
+                "index i({}, {n}) is {:?}, expected {:?}",
+                snapshot.0,
+                found.as_ref().map(|s| s.0),
+                expected.as_ref().map(|s| s.0)
+            ),
+            Self::InvalidTableType { table, byte } => {
+                write!(f, "table {table} has invalid type {byte:?}")
+            }
+            Self::MalformedRow { table, key } => {
+                write!(f, "malformed row 0x{} in table {table}", hex::encode(key))
+            }
+            Self::InvalidDataOp { table, key } => write!(
+                f,
+                "invalid data operation in row 0x{} of table {table}",
+                hex::encode(key)
+            ),
+            Self::MissingRowSnapshot {
+                table,
+                key,
+                snapshot,
+            } => write!(
+                f,
+                "row 0x{} of table {table} references missing snapshot {}",
+                hex::encode(key),
+                snapshot.0
+            ),
+            Self::WrongRowVersion {
+                table,
+                key,
+                version,
+                expected,
+            } => write!(
+                f,
+                "row 0x{} of table {table} has version {version}, expected {expected}",
+                hex::encode(key)
+            ),
+        }
+    }
+}
+
+/// The result of [`CrepeDB::verify`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// The number of snapshots.
+    pub snapshots: u64,
+    /// The number of rows in the snapshot index.
+    pub index_entries: u64,
+    /// The number of tables listed in the meta table.
+    pub tables: u64,
+    /// The number of rows in versioned tables.
+    pub rows: u64,
+    /// The inconsistencies found.
+    pub issues: Vec<Issue>,
+}
+
+impl VerifyReport {
+    /// Whether no inconsistency was found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Version and parent of every snapshot.
+type Snapshots = BTreeMap<u64, (Version, SnapshotId)>;
+
+impl<B> CrepeDB<B>
+where
+    B: Backend,
+{
+    /// Check the consistency of the database.
+    ///
+    /// Walks the snapshot table, the snapshot index, the meta table and all
+    /// versioned tables in a single read transaction and checks that:
+    ///
+    /// - every parent exists and versions increase by one along parent edges
+    /// - every index entry `i(V, n)` points to the ancestor at version `V - 2^n`
+    /// - `SNAPSHOT_NEXT_KEY` exceeds every snapshot ID
+    /// - every table in the meta table has a valid type
+    /// - every row of a versioned table holds a valid data operation and
+    ///   references an existing snapshot
+    ///
+    /// # Returns
+    ///
+    /// A report listing the inconsistencies found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend fails.
+    pub fn verify(&self) -> Result<VerifyReport> {
+        let txn = self.backend.read_txn().map_err(Error::backend)?;
+
+        let mut report = VerifyReport::default();
+
+        let snapshots = verify_snapshots(&txn, &mut report)?;
+        verify_index(&txn, &snapshots, &mut report)?;
+
+        for table in verify_meta(&txn, &mut report)? {
+            verify_rows(&txn, &table, &snapshots, &mut report)?;
+        }
+
+        Ok(report)
+    }
+}
+
+/// Parse a big-endian `u64` from exactly 8 bytes.
+fn parse(bytes: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(bytes);
+    u64::from_be_bytes(buf)
+}
+
+fn verify_snapshots<T, E>(txn: &T, report: &mut VerifyReport) -> Result<Snapshots>
+where
+    T: ReadTxn<E>,
+    E: BackendError,
+{
+    let table = txn
+        .open_table(consts::SNAPSHOT_TABLE)
+        .map_err(Error::backend)?;
+
+    let mut next = BTreeMap::new();
+    let mut snapshots = Snapshots::new();
+
+    scan(&table, |key, value| {
+        if key.starts_with(consts::SNAPSHOT_NEXT_KEY) {
+            match key[8..] {
+                [] => next.insert(0, SnapshotId::from_bytes(&value).ok()),
+                [a, b] => next.insert(
+                    u16::from_be_bytes([a, b]),
+                    SnapshotId::from_bytes(&value).ok(),
+                ),
+                _ => {
+                    report.issues.push(Issue::MalformedSnapshot { key });
+                    return;
+                }
+            };
+            return;
+        }
+
+        if key.len() != 8
+            || value.len() < 16
+            || !(value.len() - 16).is_multiple_of(consts::SNAPSHOT_FIELD_LEN)
+        {
+            report.issues.push(Issue::MalformedSnapshot { key });
+            return;
+        }
+
+        let id = parse(&key);
+        let version = Version(parse(&value[..8]));
+        let parent = SnapshotId(parse(&value[8..16]));
+
+        snapshots.insert(id, (version, parent));
+    })?;
+
+    report.snapshots = snapshots.len() as u64;
+
+    for (&id, (version, parent)) in &snapshots {
+        let snapshot = SnapshotId(id);
+
+        let expected = if snapshot == SnapshotId::root() {
+            Version::root()
+        } else if let Some((parent_version, _)) = snapshots.get(&parent.0) {
+            Version(parent_version.0 + 1)
+        } else {
+            report.issues.push(Issue::MissingParent {
+                snapshot,
+                parent: parent.clone(),
+            });
+            continue;
+        };
+
+        if *version != expected {
+            report.issues.push(Issue::WrongVersion {
+                snapshot,
+                version: version.clone(),
+                expected,
+            });
+        }
+    }
+
+    // IDs are ordered by lane, so the last ID of a lane is its largest one.
+    let mut max = BTreeMap::new();
+    for &id in snapshots.keys() {
+        max.insert(SnapshotId(id).lane(), id);
+    }
+
+    for (lane, max) in max {
+        let next = next.get(&lane).cloned().flatten();
+
+        if !matches!(&next, Some(next) if next.0 > max) {
+            report.issues.push(Issue::NextSnapshotId {
+                lane,
+                next,
+                max: SnapshotId(max),
+            });
+        }
+    }
+
+    Ok(snapshots)
+}
+
+/// Derive the index entries of all snapshots from the snapshot table.
+///
+/// Entry `n` of a snapshot points to its ancestor `2^n` versions back, so it
+/// is entry `n - 1` of entry `n - 1`. Snapshots are visited in version order
+/// to derive them from the entries of their ancestors. Snapshots with a broken
+/// lineage have no entries.
+fn derive_index(snapshots: &Snapshots) -> BTreeMap<u64, Vec<u64>> {
+    let mut order: Vec<_> = snapshots.iter().collect();
+    order.sort_by_key(|(id, (version, _))| (version.0, **id));
+
+    // Ancestors `2^n` versions back, starting with the parent.
+    let mut ancestors: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+
+    for (&id, (version, parent)) in order {
+        if id == SnapshotId::root().0 {
+            ancestors.insert(id, Vec::new());
+            continue;
+        }
+
+        let valid = snapshots
+            .get(&parent.0)
+            .is_some_and(|(v, _)| v.0 + 1 == version.0)
+            && ancestors.contains_key(&parent.0);
+
+        if !valid {
+            continue;
+        }
+
+        let mut entries = Vec::from([parent.0]);
+
+        for n in 1..u64::BITS {
+            if 1 << n > version.0 {
+                break;
+            }
+
+            let half = entries[n as usize - 1];
+            entries.push(ancestors[&half][n as usize - 1]);
+        }
+
+        ancestors.insert(id, entries);
+    }
+
+    ancestors
+}
+
+fn verify_index<T, E>(txn: &T, snapshots: &Snapshots, report: &mut VerifyReport) -> Result<()>
+where
+    T: ReadTxn<E>,
+    E: BackendError,
+{
+    let table = txn
+        .open_table(consts::SNAPSHOT_INDEX_TABLE)
+        .map_err(Error::backend)?;
+
+    let mut found = BTreeMap::new();
+
+    scan(&table, |key, value| {
+        report.index_entries += 1;
+
+        if key.len() != 12 || value.len() != 8 {
+            report.issues.push(Issue::MalformedIndex { key });
+            return;
+        }
+
+        let snapshot = parse(&key[..8]);
+        let n = u32::from_le_bytes([key[8], key[9], key[10], key[11]]);
+
+        found.insert((snapshot, n), parse(&value));
+    })?;
+
+    let ancestors = derive_index(snapshots);
+
+    let mut expected = BTreeMap::new();
+
+    for (&id, entries) in &ancestors {
+        // The parent is stored in the snapshot table.
+        for (n, &ancestor) in entries.iter().enumerate().skip(1) {
+            expected.insert((id, n as u32), ancestor);
+        }
+    }
+
+    let keys: BTreeSet<_> = expected.keys().chain(found.keys()).collect();
+
+    for &(id, n) in keys {
+        // Snapshots with a broken lineage are reported already.
+        if !ancestors.contains_key(&id) && snapshots.contains_key(&id) {
+            continue;
+        }
+
+        let expected = expected.get(&(id, n));
+        let found = found.get(&(id, n));
+
+        if expected != found {
+            report.issues.push(Issue::WrongIndex {
+                snapshot: SnapshotId(id),
+                n,
+                expected: expected.map(|s| SnapshotId(*s)),
+                found: found.map(|s| SnapshotId(*s)),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the meta table and return the names of the versioned tables.
+fn verify_meta<T, E>(txn: &T, report: &mut VerifyReport) -> Result<Vec<String>>
+where
+    T: ReadTxn<E>,
+    E: BackendError,
+{
+    let table = txn.open_table(consts::META_TABLE).map_err(Error::backend)?;
+
+    let mut versioned = Vec::new();
+
+    scan(&table, |key, value| {
+        report.tables += 1;
+
+        let table = String::from_utf8_lossy(&key).into_owned();
+        let byte = value.first().copied();
+
+        match byte.map(TableType::from_byte) {
+            Some(Ok(TableType::Versioned)) => versioned.push(table),
+            Some(Ok(TableType::Basic)) => {}
+            _ => report.issues.push(Issue::InvalidTableType { table, byte }),
+        }
+    })?;
+
+    Ok(versioned)
+}
+
+fn verify_rows<T, E>(
+    txn: &T,
+    name: &str,
+    snapshots: &Snapshots,
+    report: &mut VerifyReport,
+) -> Result<()>
+where
+    T: ReadTxn<E>,
+    E: BackendError,
+{
+    let table = txn.open_table(name).map_err(Error::backend)?;
+    let table_name = || String::from(name);
+
+    scan(&table, |key, value| {
+        report.rows += 1;
+
+        if key.len() < 16 {
+            report.issues.push(Issue::MalformedRow {
+                table: table_name(),
+                key,
+            });
+            return;
+        }
+
+        let suffix = key.len() - 16;
+        let version = Version(parse(&key[suffix..suffix + 8]));
+        let snapshot = SnapshotId(parse(&key[suffix + 8..]));
+
+        if DataOp::from_bytes(value).is_err() {
+            report.issues.push(Issue::InvalidDataOp {
+                table: table_name(),
+                key: key.clone(),
+            });
+        }
+
+        match snapshots.get(&snapshot.0) {
+            None => report.issues.push(Issue::MissingRowSnapshot {
+                table: table_name(),
+                key,
+                snapshot,
+            }),
+            Some((expected, _)) if *expected != version => {
+                report.issues.push(Issue::WrongRowVersion {
+                    table: table_name(),
+                    key,
+                    version,
+                    expected: expected.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    })?;
+
+    Ok(())
+}
+
+#[doc(hidden)]
+#[cfg(feature = "tests")]
+pub mod verify_tests {
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    use super::Issue;
+    use crate::{
+        backend::{Backend, WriteTable, WriteTxn},
+        types::{SnapshotId, Version},
+        utils::consts,
+        CrepeDB, Error, Result,
+    };
+
+    fn row_key(key: &[u8], version: u64, snapshot: u64) -> Vec<u8> {
+        let mut k = key.to_vec();
+        k.extend_from_slice(&version.to_be_bytes());
+        k.extend_from_slice(&snapshot.to_be_bytes());
+        k
+    }
+
+    pub fn test_verify(backend: impl Backend) -> Result<()> {
+        let db = CrepeDB::new(backend);
+
+        let txn = db.write(None)?;
+        txn.create_versioned_table("v")?;
+        txn.create_basic_table("b")?;
+        txn.open_table("b")?.set(vec![1], vec![1])?;
+        let root = txn.commit()?;
+
+        // A chain of 9 snapshots with a fork at version 3.
+        let mut snapshots = vec![root];
+        for i in 1..10u8 {
+            let txn = db.write(snapshots.last().cloned())?;
+            let mut table = txn.open_table("v")?;
+            table.set(vec![i], vec![i])?;
+            table.del(vec![i - 1])?;
+            drop(table);
+            snapshots.push(txn.commit()?);
+        }
+
+        let txn = db.write(Some(snapshots[3].clone()))?;
+        txn.open_table("v")?.set(vec![0], vec![0])?;
+        let fork = txn.commit()?;
+
+        let report = db.verify()?;
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.snapshots, 11);
+        assert_eq!(report.tables, 2);
+        assert_eq!(report.rows, 19);
+        // Versions 2..=9 have 1, 1, 2, 2, 2, 2, 3, 3 entries and the fork 2.
+        assert_eq!(report.index_entries, 18);
+
+        let txn = db.backend().write_txn().map_err(Error::backend)?;
+        {
+            let mut index = txn
+                .open_table(consts::SNAPSHOT_INDEX_TABLE)
+                .map_err(Error::backend)?;
+
+            let mut key = snapshots[5].to_bytes().to_vec();
+            key.extend_from_slice(&1u32.to_le_bytes());
+            index.del(&key).map_err(Error::backend)?;
+
+            let mut key = fork.to_bytes().to_vec();
+            key.extend_from_slice(&7u32.to_le_bytes());
+            index.set(&key, &[0; 8]).map_err(Error::backend)?;
+
+            let mut meta = txn.open_table(consts::META_TABLE).map_err(Error::backend)?;
+            meta.set(b"bad", &[9]).map_err(Error::backend)?;
+
+            let mut table = txn.open_table("v").map_err(Error::backend)?;
+            table
+                .set(&row_key(&[1], 1, 999), &[0])
+                .map_err(Error::backend)?;
+            table
+                .set(&row_key(&[2], 1, 1), &[5])
+                .map_err(Error::backend)?;
+            table
+                .set(&row_key(&[3], 5, 1), &[0])
+                .map_err(Error::backend)?;
+            // Keys of any length are checked.
+            table
+                .set(&row_key(&[0xff; 1100], 1, 999), &[0])
+                .map_err(Error::backend)?;
+
+            let mut snapshot = txn
+                .open_table(consts::SNAPSHOT_TABLE)
+                .map_err(Error::backend)?;
+            let mut value = 3u64.to_be_bytes().to_vec();
+            value.extend_from_slice(&400u64.to_be_bytes());
+            snapshot
+                .set(&500u64.to_be_bytes(), &value)
+                .map_err(Error::backend)?;
+        }
+        txn.commit().map_err(Error::backend)?;
+
+        let report = db.verify()?;
+
+        let expected = [
+            Issue::MissingParent {
+                snapshot: SnapshotId(500),
+                parent: SnapshotId(400),
+            },
+            Issue::NextSnapshotId {
+                lane: 0,
+                next: Some(SnapshotId(11)),
+                max: SnapshotId(500),
+            },
+            Issue::WrongIndex {
+                snapshot: snapshots[5].clone(),
+                n: 1,
+                expected: Some(snapshots[3].clone()),
+                found: None,
+            },
+            Issue::WrongIndex {
+                snapshot: fork,
+                n: 7,
+                expected: None,
+                found: Some(SnapshotId::root()),
+            },
+            Issue::InvalidTableType {
+                table: "bad".to_string(),
+                byte: Some(9),
+            },
+            Issue::MissingRowSnapshot {
+                table: "v".to_string(),
+                key: row_key(&[1], 1, 999),
+                snapshot: SnapshotId(999),
+            },
+            Issue::MissingRowSnapshot {
+                table: "v".to_string(),
+                key: row_key(&[0xff; 1100], 1, 999),
+                snapshot: SnapshotId(999),
+            },
+            Issue::InvalidDataOp {
+                table: "v".to_string(),
+                key: row_key(&[2], 1, 1),
+            },
+            Issue::WrongRowVersion {
+                table: "v".to_string(),
+                key: row_key(&[3], 5, 1),
+                version: Version(5),
+                expected: Version(1),
+            },
+        ];
+
+        for issue in &expected {
+            assert!(report.issues.contains(issue), "missing {issue:?}");
+        }
+        assert_eq!(report.issues.len(), expected.len(), "{:?}", report.issues);
+
+        Ok(())
+    }
+}
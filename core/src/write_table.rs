@@ -1,7 +1,8 @@
-use core::marker::PhantomData;
+use core::{cell::RefCell, marker::PhantomData};
 
 use crate::{
     backend::{BackendError, WriteTable as BackendWriteTable},
+    savepoint::Savepoints,
     types::{Bytes, DataOp, SnapshotId, TableType, Version},
     Error, Result,
 };
@@ -10,7 +11,7 @@ use crate::{
 ///
 /// Provides methods to modify data in the table. Changes are not persisted
 /// until the transaction is committed.
-pub struct WriteTable<T, E> {
+pub struct WriteTable<'a, T, E> {
     pub(crate) table: T,
 
     pub(crate) table_type: TableType,
@@ -18,10 +19,23 @@ pub struct WriteTable<T, E> {
     pub(crate) snapshot_id: SnapshotId,
     pub(crate) version: Version,
 
+    pub(crate) savepoints: &'a RefCell<Savepoints>,
+
+    /// Reused buffer for the versioned key and flagged value of a write.
+    pub(crate) scratch: Bytes,
+
+    /// Keys written to versioned tables, if the state root is maintained.
+    #[cfg(feature = "merkle")]
+    pub(crate) written: Option<&'a crate::merkle::Written>,
+
+    /// The running state hash, if it is maintained.
+    #[cfg(feature = "state-hash")]
+    pub(crate) state_hash: Option<crate::state_hash::Tracker<'a, T>>,
+
     pub(crate) marker: PhantomData<E>,
 }
 
-impl<T, E> WriteTable<T, E>
+impl<'a, T, E> WriteTable<'a, T, E>
 where
     T: BackendWriteTable<E>,
     E: BackendError,
@@ -39,23 +53,15 @@ where
     /// # Errors
     ///
     /// Returns an error if the operation fails.
-    pub fn set(&mut self, key: Bytes, value: Bytes) -> Result<()> {
+    pub fn set(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<()> {
         match self.table_type {
-            TableType::Basic => self.set_basic(key, value),
-            TableType::Versioned => self.set_versioned(key, value),
+            TableType::Basic => self.set_basic(key.as_ref(), value.as_ref()),
+            TableType::Versioned => self.write_versioned(key.as_ref(), Some(value.as_ref())),
         }
     }
 
-    fn set_basic(&mut self, key: Bytes, value: Bytes) -> Result<()> {
-        self.table.set(key, value).map_err(Error::backend)?;
-
-        Ok(())
-    }
-
-    fn set_versioned(&mut self, key: Bytes, value: Bytes) -> Result<()> {
-        let key = self.build_key(key);
-        let value = DataOp::Set(value).to_bytes();
-
+    fn set_basic(&mut self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.record(key)?;
         self.table.set(key, value).map_err(Error::backend)?;
 
         Ok(())
@@ -73,32 +79,96 @@ where
     /// # Errors
     ///
     /// Returns an error if the operation fails.
-    pub fn del(&mut self, key: Bytes) -> Result<()> {
+    pub fn del(&mut self, key: impl AsRef<[u8]>) -> Result<()> {
         match self.table_type {
-            TableType::Basic => self.del_basic(key),
-            TableType::Versioned => self.del_versioned(key),
+            TableType::Basic => self.del_basic(key.as_ref()),
+            TableType::Versioned => self.write_versioned(key.as_ref(), None),
         }
     }
 
-    fn del_basic(&mut self, key: Bytes) -> Result<()> {
+    fn del_basic(&mut self, key: &[u8]) -> Result<()> {
+        self.record(key)?;
         self.table.del(key).map_err(Error::backend)?;
 
         Ok(())
     }
 
-    fn del_versioned(&mut self, key: Bytes) -> Result<()> {
-        let key = self.build_key(key);
-        let value = DataOp::Del.to_bytes();
+    /// Write a set, or a deletion for `None`, of a versioned key.
+    fn write_versioned(&mut self, key: &[u8], value: Option<&[u8]>) -> Result<()> {
+        #[cfg(feature = "merkle")]
+        if let Some(written) = self.written {
+            crate::merkle::record(written, self.table.name(), key);
+        }
+
+        #[cfg(feature = "state-hash")]
+        if let Some(tracker) = &self.state_hash {
+            let len = versioned_row(
+                &mut self.scratch,
+                key,
+                &self.version,
+                &self.snapshot_id,
+                None,
+            );
+
+            // A write of this transaction hides the value of the parent.
+            let previous = match self
+                .table
+                .get(&self.scratch[..len])
+                .map_err(Error::backend)?
+            {
+                Some(row) => DataOp::from_bytes(row)?.into(),
+                None => (tracker.parent_value)(&self.table, key)?,
+            };
+
+            tracker.update(self.table.name(), key, previous.as_deref(), value);
+        }
 
+        let len = versioned_row(
+            &mut self.scratch,
+            key,
+            &self.version,
+            &self.snapshot_id,
+            value,
+        );
+        let (key, value) = self.scratch.split_at(len);
+
+        self.record(key)?;
         self.table.set(key, value).map_err(Error::backend)?;
 
         Ok(())
     }
 
-    fn build_key(&self, mut key: Bytes) -> Bytes {
-        key.extend_from_slice(&self.version.to_bytes());
-        key.extend_from_slice(&self.snapshot_id.to_bytes());
+    /// Record the current value of a raw key for rollbacks to savepoints.
+    fn record(&self, key: &[u8]) -> Result<()> {
+        self.savepoints.borrow_mut().record(&self.table, key)
+    }
+}
 
-        key
+/// Build the row of a write of `key` at `version` and `snapshot_id` in `buf`,
+/// a deletion for `None`.
+///
+/// The buffer holds the versioned key followed by the flagged value, the
+/// length of the versioned key is returned.
+pub(crate) fn versioned_row(
+    buf: &mut Bytes,
+    key: &[u8],
+    version: &Version,
+    snapshot_id: &SnapshotId,
+    value: Option<&[u8]>,
+) -> usize {
+    buf.clear();
+
+    buf.extend_from_slice(key);
+    buf.extend_from_slice(&version.to_bytes());
+    buf.extend_from_slice(&snapshot_id.to_bytes());
+
+    match value {
+        Some(value) => {
+            buf.extend_from_slice(value);
+            buf.push(DataOp::SET_FLAG);
+        }
+        None => buf.push(DataOp::DEL_FLAG),
     }
+
+    key.len() + 16
 }
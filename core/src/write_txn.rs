@@ -1,9 +1,10 @@
-use core::{fmt::Debug, marker::PhantomData};
+use core::{cell::RefCell, fmt::Debug, marker::PhantomData};
 
 use crate::{
     backend::{BackendError, WriteTxn as BackendWriteTxn},
-    types::{SnapshotId, TableType, Version},
-    utils, Error, Result, WriteTable,
+    savepoint::Savepoints,
+    types::{Bytes, SnapshotId, TableType, Version},
+    utils, Error, Result, SnapshotInfo, WriteTable,
 };
 
 /// A write transaction for modifying data and creating new snapshots.
@@ -26,6 +27,21 @@ pub struct WriteTxn<T, E> {
     /// The version number for this transaction.
     pub(crate) version: Version,
 
+    pub(crate) savepoints: RefCell<Savepoints>,
+
+    /// Keys written to versioned tables, if the state root is maintained.
+    #[cfg(feature = "merkle")]
+    pub(crate) written: Option<crate::merkle::Written>,
+
+    /// The state hash with the writes so far, if it is maintained.
+    #[cfg(feature = "state-hash")]
+    pub(crate) state_hash: Option<RefCell<crate::types::Hash>>,
+
+    /// Ancestors of the parent snapshot by version, resolved by the state
+    /// hash updates of versioned writes.
+    #[cfg(feature = "state-hash")]
+    pub(crate) parent_ancestors: RefCell<alloc::collections::BTreeMap<Version, Option<SnapshotId>>>,
+
     pub(crate) marker: PhantomData<E>,
 }
 
@@ -62,9 +78,7 @@ where
     ///
     /// Returns an error if the table already exists or cannot be created.
     pub fn create_basic_table(&self, table: &str) -> Result<()> {
-        let mut meta = utils::meta_writer(&self.txn)?;
-        meta.write_type(table, &TableType::Basic)?;
-        Ok(())
+        self.create_table(table, &TableType::Basic)
     }
 
     /// Create a new versioned table with full history tracking.
@@ -81,8 +95,17 @@ where
     ///
     /// Returns an error if the table already exists or cannot be created.
     pub fn create_versioned_table(&self, table: &str) -> Result<()> {
+        self.create_table(table, &TableType::Versioned)
+    }
+
+    fn create_table(&self, table: &str, ty: &TableType) -> Result<()> {
         let mut meta = utils::meta_writer(&self.txn)?;
-        meta.write_type(table, &TableType::Versioned)?;
+
+        self.savepoints
+            .borrow_mut()
+            .record(&meta.table, table.as_bytes())?;
+
+        meta.write_type(table, ty)?;
         Ok(())
     }
 
@@ -97,7 +120,7 @@ where
     /// # Errors
     ///
     /// Returns an error if the table does not exist or cannot be opened.
-    pub fn open_table(&self, table: &str) -> Result<WriteTable<T::Table<'_>, E>> {
+    pub fn open_table(&self, table: &str) -> Result<WriteTable<'_, T::Table<'_>, E>> {
         let meta = utils::meta_reader_by_write(&self.txn)?;
 
         let table_type = meta.read_type(table)?;
@@ -108,11 +131,137 @@ where
             snapshot_id: self.new_snapshot_id.clone(),
             table: self.txn.open_table(table).map_err(Error::backend)?,
             version: self.version.clone(),
+            savepoints: &self.savepoints,
+            scratch: Bytes::new(),
+            #[cfg(feature = "merkle")]
+            written: self.written.as_ref(),
+            #[cfg(feature = "state-hash")]
+            state_hash: self
+                .state_hash
+                .as_ref()
+                .map(|hash| crate::state_hash::Tracker {
+                    hash,
+                    parent_value: alloc::boxed::Box::new(|table: &_, key: &[u8]| {
+                        self.parent_value(table, key)
+                    }),
+                }),
         };
 
         Ok(table)
     }
 
+    /// Get the value of `key` in versioned table `table` at the parent
+    /// snapshot.
+    #[cfg(feature = "state-hash")]
+    fn parent_value(
+        &self,
+        table: &impl crate::backend::ReadTable<E>,
+        key: &[u8],
+    ) -> Result<Option<Bytes>> {
+        if self.version == Version::root() {
+            return Ok(None);
+        }
+
+        let found = crate::read_table::find_visible(
+            table,
+            key,
+            &(self.version.0 - 1).into(),
+            true,
+            &mut Bytes::new(),
+            |version| self.parent_ancestor(version),
+        )?;
+
+        match found {
+            Some((_, Some(value))) => Ok(crate::types::DataOp::from_bytes(value)?.into()),
+            _ => Ok(None),
+        }
+    }
+
+    /// Find the ancestor of the parent snapshot at `version`.
+    ///
+    /// Ancestors are cached for the transaction, so the index tables are only
+    /// opened for versions which were not resolved yet.
+    #[cfg(feature = "state-hash")]
+    fn parent_ancestor(&self, version: Version) -> Result<Option<SnapshotId>> {
+        if let Some(ancestor) = self.parent_ancestors.borrow().get(&version) {
+            return Ok(ancestor.clone());
+        }
+
+        let ancestor = match self.parent_view()? {
+            Some(view) => view.ancestor(version.clone())?,
+            None => None,
+        };
+
+        self.parent_ancestors
+            .borrow_mut()
+            .insert(version, ancestor.clone());
+
+        Ok(ancestor)
+    }
+
+    /// Open `table` for reading at the parent snapshot, `None` when creating
+    /// the root.
+    #[cfg(feature = "export")]
+    pub(crate) fn read_parent(
+        &self,
+        table: &str,
+        table_type: TableType,
+    ) -> Result<Option<crate::ReadTable<T::Table<'_>, E>>> {
+        let Some(view) = self.parent_view()? else {
+            return Ok(None);
+        };
+
+        Ok(Some(crate::ReadTable {
+            table: self.txn.open_table(table).map_err(Error::backend)?,
+            table_type,
+            view,
+            #[cfg(feature = "merkle")]
+            merkle: None,
+            marker: PhantomData,
+        }))
+    }
+
+    /// A view of the parent snapshot, `None` when creating the root.
+    #[cfg(feature = "state-hash")]
+    fn parent_view(&self) -> Result<Option<crate::View<T::Table<'_>, E>>> {
+        if self.version == Version::root() {
+            return Ok(None);
+        }
+
+        self.view(self.snapshot_id.clone(), (self.version.0 - 1).into())
+            .map(Some)
+    }
+
+    /// A view of `snapshot_id` at `version` in this transaction.
+    #[cfg(any(feature = "merkle", feature = "state-hash"))]
+    fn view(
+        &self,
+        snapshot_id: SnapshotId,
+        version: Version,
+    ) -> Result<crate::View<T::Table<'_>, E>> {
+        Ok(crate::View {
+            index: utils::index_writer(&self.txn)?,
+            snapshot: utils::snapshot_writer(&self.txn)?,
+            snapshot_id,
+            version,
+            scratch: RefCell::default(),
+        })
+    }
+
+    /// Open a versioned table for reading at the new snapshot.
+    ///
+    /// The row and index of the new snapshot must be written.
+    #[cfg(feature = "merkle")]
+    pub(crate) fn read_versioned(&self, table: &str) -> Result<crate::ReadTable<T::Table<'_>, E>> {
+        Ok(crate::ReadTable {
+            table: self.txn.open_table(table).map_err(Error::backend)?,
+            table_type: TableType::Versioned,
+            view: self.view(self.new_snapshot_id.clone(), self.version.clone())?,
+            merkle: None,
+            marker: PhantomData,
+        })
+    }
+
     /// Commit the write transaction.
     ///
     /// This persists all changes and creates a new snapshot. The snapshot ID
@@ -126,22 +275,53 @@ where
     ///
     /// Returns an error if the commit fails.
     pub fn commit(self) -> Result<SnapshotId> {
+        let info = SnapshotInfo {
+            version: self.version.clone(),
+            parent_snapshot_id: self.snapshot_id.clone(),
+            state_root: None,
+            #[cfg(feature = "state-hash")]
+            state_hash: self.state_hash.as_ref().map(|hash| *hash.borrow()),
+            #[cfg(not(feature = "state-hash"))]
+            state_hash: None,
+        };
+
         {
             let mut snapshot = utils::snapshot_writer(&self.txn)?;
 
             // write snapshot info
-            snapshot.write(&self.new_snapshot_id, &self.snapshot_id, &self.version)?;
+            snapshot.write(&self.new_snapshot_id, &info)?;
 
             // write next snapshot id
             snapshot.write_next_snapahot(&self.new_snapshot_id)?;
         }
 
-        if let Some(parent_snapshot_id) = self.parent_snapshot_id {
-            // Must not be root
-            // build index
+        {
+            // Opened for the root as well, so that backends which require
+            // tables to exist can read the root.
             let mut index = utils::index_writer(&self.txn)?;
 
-            index.write(&self.new_snapshot_id, &parent_snapshot_id, self.version)?;
+            if let Some(parent_snapshot_id) = &self.parent_snapshot_id {
+                // Must not be root
+                // build index
+                index.write(
+                    &self.new_snapshot_id,
+                    parent_snapshot_id,
+                    self.version.clone(),
+                )?;
+            }
+        }
+
+        // The tree is read at the new snapshot, so its row and index are
+        // written before.
+        #[cfg(feature = "merkle")]
+        if let Some(written) = &self.written {
+            let info = SnapshotInfo {
+                state_root: Some(crate::merkle::commit(&self, written.take())?),
+                ..info
+            };
+
+            let mut snapshot = utils::snapshot_writer(&self.txn)?;
+            snapshot.write(&self.new_snapshot_id, &info)?;
         }
 
         let new_snapshot_id = self.new_snapshot_id;
@@ -10,7 +10,7 @@
 //! - **Versioned Storage**: Track changes across multiple versions
 //! - **Snapshot Isolation**: Create and read from consistent snapshots
 //! - **Fork Support**: Create new branches from any snapshot
-//! - **Backend Abstraction**: Use different storage backends (e.g., redb, rocksdb, mdbx)
+//! - **Backend Abstraction**: Use different storage backends (e.g., redb, rocksdb, mdbx, sled, sqlite, fjall, lmdb)
 //!
 //! ## Example
 //!
@@ -63,6 +63,11 @@ pub mod types {
 /// - **RedbDatabase**: A simple, portable, high-performance embedded key-value database
 /// - **RocksdbDatabase**: A high-performance embedded database based on RocksDB
 /// - **MdbxDatabase**: A fast, compact, powerful embedded transactional key-value database
+/// - **SledDatabase**: A pure-Rust embedded database built on a lock-free B+ tree
+/// - **SqliteDatabase**: A portable single-file database based on SQLite
+/// - **FjallDatabase**: A pure-Rust LSM-tree based embedded key-value database
+/// - **LmdbDatabase**: A memory-mapped transactional key-value database based on LMDB
+/// - **MemoryBackend**: A `no_std` in-memory database built on `BTreeMap`
 ///
 /// ## Example
 ///
@@ -79,17 +84,47 @@ pub mod backend {
     ///
     /// Redb is a simple, portable, high-performance, ACID, embedded key-value database.
     #[cfg(any(feature = "backend-redb", docsrs))]
-    pub use crepedb_redb::RedbDatabase;
+    pub use crepedb_redb::{Durability, RedbDatabase, RedbOptions};
 
     /// RocksDB backend implementation.
     ///
     /// RocksDB is a high-performance embedded database for key-value data.
     #[cfg(any(feature = "backend-rocksdb", docsrs))]
-    pub use crepedb_rocksdb::RocksdbDatabase;
+    pub use crepedb_rocksdb::{DBCompressionType, RocksdbDatabase, RocksdbOptions, TableOptions};
 
     /// MDBX backend implementation.
     ///
     /// MDBX is a fast, compact, powerful, embedded, transactional key-value database.
     #[cfg(any(feature = "backend-mdbx", docsrs))]
-    pub use crepedb_mdbx::MdbxDatabase;
+    pub use crepedb_mdbx::{MdbxDatabase, MdbxOptions, SyncMode};
+
+    /// Sled backend implementation.
+    ///
+    /// Sled is a pure-Rust embedded database built on a lock-free B+ tree.
+    #[cfg(any(feature = "backend-sled", docsrs))]
+    pub use crepedb_sled::SledDatabase;
+
+    /// SQLite backend implementation.
+    ///
+    /// SQLite is a small, fast, self-contained, single-file SQL database engine.
+    #[cfg(any(feature = "backend-sqlite", docsrs))]
+    pub use crepedb_sqlite::SqliteDatabase;
+
+    /// Fjall backend implementation.
+    ///
+    /// Fjall is a pure-Rust, LSM-tree based embedded key-value storage engine.
+    #[cfg(any(feature = "backend-fjall", docsrs))]
+    pub use crepedb_fjall::{FjallDatabase, FjallError};
+
+    /// LMDB backend implementation.
+    ///
+    /// LMDB is a small, fast, memory-mapped, transactional key-value store.
+    #[cfg(any(feature = "backend-lmdb", docsrs))]
+    pub use crepedb_lmdb::{LmdbDatabase, LmdbOptions};
+
+    /// In-memory backend implementation.
+    ///
+    /// Keeps all tables in `BTreeMap`s and works in `no_std` environments.
+    #[cfg(any(feature = "backend-memory", docsrs))]
+    pub use crepedb_core::memory::MemoryBackend;
 }
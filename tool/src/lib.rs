@@ -3,3 +3,6 @@ pub use table::*;
 
 mod snapshot;
 pub use snapshot::*;
+
+mod verify;
+pub use verify::*;
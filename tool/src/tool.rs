@@ -2,12 +2,13 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand, ValueEnum};
-use crepedb_core::CrepeDB;
-use crepedb_redb::RedbDatabase;
-use crepedb_tool::{Snapshot, Table};
+use crepedb_core::{backend::Backend, CrepeDB};
+use crepedb_mdbx::{MdbxDatabase, MdbxOptions};
+use crepedb_redb::{RedbDatabase, RedbOptions};
+use crepedb_tool::{Snapshot, Table, Verify};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
-pub enum Backend {
+pub enum BackendKind {
     Redb,
     Mdbx,
 }
@@ -18,7 +19,7 @@ pub struct Args {
     #[arg(short, long, env = "CREPEDB_DB")]
     database: PathBuf,
     #[arg(short, long, env = "CREPEDB_BACKEND", default_value = "redb")]
-    backend: Backend,
+    backend: BackendKind,
 
     #[command(subcommand)]
     subcmd: SubCmd,
@@ -30,36 +31,56 @@ pub enum SubCmd {
     Snapshot(Snapshot),
     Value,
     Commit,
+    Verify(Verify),
 }
 
 impl SubCmd {
-    pub fn exec<B>(self, _db: CrepeDB<B>) -> Result<()> {
+    /// Commands that only inspect the database open it read-only and never create it.
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, Self::Verify(_))
+    }
+
+    pub fn exec<B: Backend>(self, db: CrepeDB<B>) -> Result<()> {
         match self {
             Self::Table(t) => t.exec()?,
             Self::Snapshot(s) => s.exec()?,
             Self::Value => {}
             Self::Commit => {}
+            Self::Verify(v) => v.exec(&db)?,
         }
 
         Ok(())
     }
 }
 
-fn main() {
+fn main() -> Result<()> {
     let args = Args::parse();
+    let read_only = args.subcmd.is_read_only();
 
     match args.backend {
-        Backend::Redb => {
-            let backend = RedbDatabase::open_or_create(&args.database).unwrap();
+        BackendKind::Redb => {
+            let backend = if read_only {
+                RedbOptions::new()
+                    .with_read_only(true)
+                    .open(&args.database)?
+            } else {
+                RedbDatabase::open_or_create(&args.database)?
+            };
             let db = CrepeDB::new(backend);
 
-            args.subcmd.exec(db).unwrap();
+            args.subcmd.exec(db)
         }
-        Backend::Mdbx => {
-            let backend = RedbDatabase::memory().unwrap();
+        BackendKind::Mdbx => {
+            let backend = if read_only {
+                MdbxOptions::new()
+                    .with_read_only(true)
+                    .open(&args.database)?
+            } else {
+                MdbxDatabase::open_or_create(&args.database)?
+            };
             let db = CrepeDB::new(backend);
 
-            args.subcmd.exec(db).unwrap();
+            args.subcmd.exec(db)
         }
-    };
+    }
 }
@@ -0,0 +1,27 @@
+use anyhow::{anyhow, bail, Result};
+use clap::Args;
+use crepedb_core::{backend::Backend, CrepeDB};
+
+#[derive(Debug, Args)]
+pub struct Verify {}
+
+impl Verify {
+    pub fn exec<B: Backend>(self, db: &CrepeDB<B>) -> Result<()> {
+        let report = db.verify().map_err(|e| anyhow!("{e:?}"))?;
+
+        println!("snapshots:      {}", report.snapshots);
+        println!("index entries:  {}", report.index_entries);
+        println!("tables:         {}", report.tables);
+        println!("versioned rows: {}", report.rows);
+
+        for issue in &report.issues {
+            println!("{issue}");
+        }
+
+        if !report.is_ok() {
+            bail!("found {} issues", report.issues.len());
+        }
+
+        Ok(())
+    }
+}